@@ -0,0 +1,929 @@
+// This file is @generated by prost-build-config.
+
+/// A book on a shelf.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Book {
+    /// Resource name of the book, e.g. `shelves/1/books/2`.
+    #[prost(string, tag = "1")]
+    #[serde(
+        rename = "name",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    #[serde(
+        rename = "title",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub title: ::prost::alloc::string::String,
+    #[prost(int32, tag = "3")]
+    #[serde(
+        rename = "pages",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub pages: i32,
+    #[prost(string, optional, tag = "4")]
+    #[serde(
+        rename = "author",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub author: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetBookRequest {
+    #[prost(string, tag = "1")]
+    #[serde(
+        rename = "name",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub name: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListBooksRequest {
+    #[prost(string, tag = "1")]
+    #[serde(
+        rename = "shelf",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub shelf: ::prost::alloc::string::String,
+    /// Maximum number of books to return.
+    #[prost(int32, tag = "2")]
+    #[serde(
+        rename = "pageSize",
+        alias = "page_size",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub page_size: i32,
+    #[prost(message, optional, tag = "3")]
+    #[serde(
+        rename = "filter",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub filter: ::core::option::Option<Filter>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Filter {
+    #[prost(string, optional, tag = "1")]
+    #[serde(
+        rename = "author",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub author: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(int32, tag = "2")]
+    #[serde(
+        rename = "minPages",
+        alias = "min_pages",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub min_pages: i32,
+    #[prost(enumeration = "Format", tag = "3")]
+    #[serde(
+        rename = "format",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        serialize_with = "::prost_helper::proto3::serialize_enum::<Format, _, _>",
+        deserialize_with = "::prost_helper::proto3::deserialize_enum::<Format, _, _>"
+    )]
+    pub format: i32,
+    #[prost(string, repeated, tag = "4")]
+    #[serde(
+        rename = "tags",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub tags: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListBooksResponse {
+    #[prost(message, repeated, tag = "1")]
+    #[serde(
+        rename = "books",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub books: ::prost::alloc::vec::Vec<Book>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateBookRequest {
+    #[prost(string, tag = "1")]
+    #[serde(
+        rename = "shelf",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub shelf: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    #[serde(
+        rename = "book",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub book: ::core::option::Option<Book>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MoveBookRequest {
+    #[prost(string, tag = "1")]
+    #[serde(
+        rename = "name",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    #[serde(
+        rename = "shelf",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub shelf: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Format {
+    Unspecified = 0,
+    Hardcover = 1,
+    Paperback = 2,
+}
+impl Format {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Format::Unspecified => "FORMAT_UNSPECIFIED",
+            Format::Hardcover => "HARDCOVER",
+            Format::Paperback => "PAPERBACK",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "FORMAT_UNSPECIFIED" => Some(Self::Unspecified),
+            "HARDCOVER" => Some(Self::Hardcover),
+            "PAPERBACK" => Some(Self::Paperback),
+            _ => None,
+        }
+    }
+}
+/// Async trait, dispatcher and mock of the `library.Library` service.
+pub mod library_service {
+    /// error of the service methods
+    pub type Error = ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>;
+    /// stream of requests or responses
+    pub type BoxStream<T> = ::core::pin::Pin<
+        ::std::boxed::Box<dyn ::futures_core::Stream<Item = T> + Send>,
+    >;
+    /// A library of books on shelves, served over HTTP/JSON.
+    pub trait Library: Send + Sync + 'static {
+        /// Gets a book.
+        fn get_book(
+            &self,
+            request: super::GetBookRequest,
+        ) -> impl ::core::future::Future<Output = Result<super::Book, Error>> + Send;
+        /// Lists the books of a shelf, or of all shelves.
+        fn list_books(
+            &self,
+            request: super::ListBooksRequest,
+        ) -> impl ::core::future::Future<
+            Output = Result<super::ListBooksResponse, Error>,
+        > + Send;
+        /// Creates a book on a shelf.
+        fn create_book(
+            &self,
+            request: super::CreateBookRequest,
+        ) -> impl ::core::future::Future<Output = Result<super::Book, Error>> + Send;
+        /// Moves a book to another shelf.
+        fn move_book(
+            &self,
+            request: super::MoveBookRequest,
+        ) -> impl ::core::future::Future<Output = Result<super::Book, Error>> + Send;
+        /// Copies a book to another shelf.
+        fn copy_book(
+            &self,
+            request: super::MoveBookRequest,
+        ) -> impl ::core::future::Future<Output = Result<super::Book, Error>> + Send;
+        /// Publishes a book or a draft.
+        fn publish_book(
+            &self,
+            request: super::GetBookRequest,
+        ) -> impl ::core::future::Future<Output = Result<super::Book, Error>> + Send;
+        /// Watches the books of a shelf, which has no HTTP rule.
+        fn watch_books(
+            &self,
+            request: super::ListBooksRequest,
+        ) -> impl ::core::future::Future<
+            Output = Result<BoxStream<Result<super::Book, Error>>, Error>,
+        > + Send;
+    }
+    /// A request to a method of `Library`.
+    pub enum LibraryRequest {
+        GetBook(super::GetBookRequest),
+        ListBooks(super::ListBooksRequest),
+        CreateBook(super::CreateBookRequest),
+        MoveBook(super::MoveBookRequest),
+        CopyBook(super::MoveBookRequest),
+        PublishBook(super::GetBookRequest),
+        WatchBooks(super::ListBooksRequest),
+    }
+    /// The response of a method of `Library`.
+    pub enum LibraryResponse {
+        GetBook(super::Book),
+        ListBooks(super::ListBooksResponse),
+        CreateBook(super::Book),
+        MoveBook(super::Book),
+        CopyBook(super::Book),
+        PublishBook(super::Book),
+        WatchBooks(BoxStream<Result<super::Book, Error>>),
+    }
+    /// Call the method of `service` which `request` is for.
+    pub async fn dispatch<T: Library>(
+        service: &T,
+        request: LibraryRequest,
+    ) -> Result<LibraryResponse, Error> {
+        match request {
+            LibraryRequest::GetBook(request) => {
+                service.get_book(request).await.map(LibraryResponse::GetBook)
+            }
+            LibraryRequest::ListBooks(request) => {
+                service.list_books(request).await.map(LibraryResponse::ListBooks)
+            }
+            LibraryRequest::CreateBook(request) => {
+                service.create_book(request).await.map(LibraryResponse::CreateBook)
+            }
+            LibraryRequest::MoveBook(request) => {
+                service.move_book(request).await.map(LibraryResponse::MoveBook)
+            }
+            LibraryRequest::CopyBook(request) => {
+                service.copy_book(request).await.map(LibraryResponse::CopyBook)
+            }
+            LibraryRequest::PublishBook(request) => {
+                service.publish_book(request).await.map(LibraryResponse::PublishBook)
+            }
+            LibraryRequest::WatchBooks(request) => {
+                service.watch_books(request).await.map(LibraryResponse::WatchBooks)
+            }
+        }
+    }
+    /// `Library` which answers with the handlers set on it, for tests. Methods without a handler
+    /// fail.
+    #[allow(clippy::type_complexity)]
+    #[derive(Default)]
+    pub struct MockLibrary {
+        get_book: Option<
+            ::std::boxed::Box<
+                dyn Fn(super::GetBookRequest) -> Result<super::Book, Error> + Send + Sync,
+            >,
+        >,
+        list_books: Option<
+            ::std::boxed::Box<
+                dyn Fn(
+                    super::ListBooksRequest,
+                ) -> Result<super::ListBooksResponse, Error> + Send + Sync,
+            >,
+        >,
+        create_book: Option<
+            ::std::boxed::Box<
+                dyn Fn(
+                    super::CreateBookRequest,
+                ) -> Result<super::Book, Error> + Send + Sync,
+            >,
+        >,
+        move_book: Option<
+            ::std::boxed::Box<
+                dyn Fn(
+                    super::MoveBookRequest,
+                ) -> Result<super::Book, Error> + Send + Sync,
+            >,
+        >,
+        copy_book: Option<
+            ::std::boxed::Box<
+                dyn Fn(
+                    super::MoveBookRequest,
+                ) -> Result<super::Book, Error> + Send + Sync,
+            >,
+        >,
+        publish_book: Option<
+            ::std::boxed::Box<
+                dyn Fn(super::GetBookRequest) -> Result<super::Book, Error> + Send + Sync,
+            >,
+        >,
+        watch_books: Option<
+            ::std::boxed::Box<
+                dyn Fn(
+                    super::ListBooksRequest,
+                ) -> Result<::std::vec::Vec<super::Book>, Error> + Send + Sync,
+            >,
+        >,
+    }
+    impl MockLibrary {
+        /// Answer `get_book` with `handler`.
+        pub fn with_get_book(
+            mut self,
+            handler: impl Fn(
+                super::GetBookRequest,
+            ) -> Result<super::Book, Error> + Send + Sync + 'static,
+        ) -> Self {
+            self.get_book = Some(::std::boxed::Box::new(handler));
+            self
+        }
+        /// Answer `list_books` with `handler`.
+        pub fn with_list_books(
+            mut self,
+            handler: impl Fn(
+                super::ListBooksRequest,
+            ) -> Result<super::ListBooksResponse, Error> + Send + Sync + 'static,
+        ) -> Self {
+            self.list_books = Some(::std::boxed::Box::new(handler));
+            self
+        }
+        /// Answer `create_book` with `handler`.
+        pub fn with_create_book(
+            mut self,
+            handler: impl Fn(
+                super::CreateBookRequest,
+            ) -> Result<super::Book, Error> + Send + Sync + 'static,
+        ) -> Self {
+            self.create_book = Some(::std::boxed::Box::new(handler));
+            self
+        }
+        /// Answer `move_book` with `handler`.
+        pub fn with_move_book(
+            mut self,
+            handler: impl Fn(
+                super::MoveBookRequest,
+            ) -> Result<super::Book, Error> + Send + Sync + 'static,
+        ) -> Self {
+            self.move_book = Some(::std::boxed::Box::new(handler));
+            self
+        }
+        /// Answer `copy_book` with `handler`.
+        pub fn with_copy_book(
+            mut self,
+            handler: impl Fn(
+                super::MoveBookRequest,
+            ) -> Result<super::Book, Error> + Send + Sync + 'static,
+        ) -> Self {
+            self.copy_book = Some(::std::boxed::Box::new(handler));
+            self
+        }
+        /// Answer `publish_book` with `handler`.
+        pub fn with_publish_book(
+            mut self,
+            handler: impl Fn(
+                super::GetBookRequest,
+            ) -> Result<super::Book, Error> + Send + Sync + 'static,
+        ) -> Self {
+            self.publish_book = Some(::std::boxed::Box::new(handler));
+            self
+        }
+        /// Answer `watch_books` with `handler`.
+        pub fn with_watch_books(
+            mut self,
+            handler: impl Fn(
+                super::ListBooksRequest,
+            ) -> Result<::std::vec::Vec<super::Book>, Error> + Send + Sync + 'static,
+        ) -> Self {
+            self.watch_books = Some(::std::boxed::Box::new(handler));
+            self
+        }
+    }
+    impl Library for MockLibrary {
+        async fn get_book(
+            &self,
+            request: super::GetBookRequest,
+        ) -> Result<super::Book, Error> {
+            let handler = self
+                .get_book
+                .as_ref()
+                .ok_or("MockLibrary::get_book is not set")?;
+            handler(request)
+        }
+        async fn list_books(
+            &self,
+            request: super::ListBooksRequest,
+        ) -> Result<super::ListBooksResponse, Error> {
+            let handler = self
+                .list_books
+                .as_ref()
+                .ok_or("MockLibrary::list_books is not set")?;
+            handler(request)
+        }
+        async fn create_book(
+            &self,
+            request: super::CreateBookRequest,
+        ) -> Result<super::Book, Error> {
+            let handler = self
+                .create_book
+                .as_ref()
+                .ok_or("MockLibrary::create_book is not set")?;
+            handler(request)
+        }
+        async fn move_book(
+            &self,
+            request: super::MoveBookRequest,
+        ) -> Result<super::Book, Error> {
+            let handler = self
+                .move_book
+                .as_ref()
+                .ok_or("MockLibrary::move_book is not set")?;
+            handler(request)
+        }
+        async fn copy_book(
+            &self,
+            request: super::MoveBookRequest,
+        ) -> Result<super::Book, Error> {
+            let handler = self
+                .copy_book
+                .as_ref()
+                .ok_or("MockLibrary::copy_book is not set")?;
+            handler(request)
+        }
+        async fn publish_book(
+            &self,
+            request: super::GetBookRequest,
+        ) -> Result<super::Book, Error> {
+            let handler = self
+                .publish_book
+                .as_ref()
+                .ok_or("MockLibrary::publish_book is not set")?;
+            handler(request)
+        }
+        async fn watch_books(
+            &self,
+            request: super::ListBooksRequest,
+        ) -> Result<BoxStream<Result<super::Book, Error>>, Error> {
+            let handler = self
+                .watch_books
+                .as_ref()
+                .ok_or("MockLibrary::watch_books is not set")?;
+            let responses = handler(request)?;
+            Ok(::std::boxed::Box::pin(IterStream(responses.into_iter().map(Ok))))
+        }
+    }
+    /// Stream of the items of an iterator.
+    struct IterStream<I>(I);
+    impl<I: Iterator + Unpin> ::futures_core::Stream for IterStream<I> {
+        type Item = I::Item;
+        fn poll_next(
+            mut self: ::core::pin::Pin<&mut Self>,
+            _cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<Option<I::Item>> {
+            ::core::task::Poll::Ready(self.0.next())
+        }
+    }
+}
+/// HTTP/JSON gateway of the `library.Library` service, from its `google.api.http` options.
+pub mod library_http {
+    use super::library_service::Library;
+    /// Router with the routes of the methods of `Library` which have HTTP rules.
+    pub fn router<T: Library>(service: ::std::sync::Arc<T>) -> ::axum::Router {
+        ::axum::Router::new()
+            .route(
+                "/v1/shelves/:p0/books/:p1",
+                ::axum::routing::get(get_book_0::<T>).post(route_0_post::<T>),
+            )
+            .route(
+                "/v1/shelves/:p0/books",
+                ::axum::routing::get(list_books_0::<T>).post(create_book_0::<T>),
+            )
+            .route("/v1/books", ::axum::routing::get(list_books_1::<T>))
+            .route("/v1/:p0", ::axum::routing::post(route_3_post::<T>))
+            .route("/v1/drafts/*p0", ::axum::routing::post(route_4_post::<T>))
+            .with_state(service)
+    }
+    #[allow(unused_variables, unused_mut)]
+    async fn get_book_0<T: Library>(
+        ::axum::extract::State(service): ::axum::extract::State<::std::sync::Arc<T>>,
+        ::axum::extract::Path(path): ::axum::extract::Path<Params>,
+        ::axum::extract::Query(query): ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {
+        let request = move || -> Result<super::GetBookRequest, String> {
+            let mut request: super::GetBookRequest = ::core::default::Default::default();
+            request.name = parse_param(
+                "name",
+                &["shelves", param(&path, "p0")?, "books", param(&path, "p1")?].join("/"),
+            )?;
+            Ok(request)
+        };
+        let request = match request() {
+            Ok(request) => request,
+            Err(message) => return error(::axum::http::StatusCode::BAD_REQUEST, message),
+        };
+        match service.get_book(request).await {
+            Ok(response) => json(::axum::http::StatusCode::OK, &response),
+            Err(e) => {
+                error(::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        }
+    }
+    #[allow(unused_variables, unused_mut)]
+    async fn list_books_0<T: Library>(
+        ::axum::extract::State(service): ::axum::extract::State<::std::sync::Arc<T>>,
+        ::axum::extract::Path(path): ::axum::extract::Path<Params>,
+        ::axum::extract::Query(query): ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {
+        let request = move || -> Result<super::ListBooksRequest, String> {
+            let mut request: super::ListBooksRequest = ::core::default::Default::default();
+            request.shelf = parse_param("shelf", param(&path, "p0")?)?;
+            if let Some(value) = query_param(&query, &["page_size", "pageSize"]) {
+                request.page_size = parse_param("page_size", value)?;
+            }
+            if let Some(value) = query_param(&query, &["filter.author"]) {
+                request
+                    .filter
+                    .get_or_insert_with(::core::default::Default::default)
+                    .author = Some(parse_param("filter.author", value)?);
+            }
+            if let Some(value) = query_param(
+                &query,
+                &["filter.min_pages", "filter.minPages"],
+            ) {
+                request
+                    .filter
+                    .get_or_insert_with(::core::default::Default::default)
+                    .min_pages = parse_param("filter.min_pages", value)?;
+            }
+            if let Some(value) = query_param(&query, &["filter.format"]) {
+                request
+                    .filter
+                    .get_or_insert_with(::core::default::Default::default)
+                    .format = parse_enum(
+                    "filter.format",
+                    value,
+                    super::Format::from_str_name,
+                )?;
+            }
+            if let Some(values) = query_values(&query, &["filter.tags"]) {
+                request
+                    .filter
+                    .get_or_insert_with(::core::default::Default::default)
+                    .tags = values
+                    .into_iter()
+                    .map(|value| parse_param("filter.tags", value))
+                    .collect::<Result<_, _>>()?;
+            }
+            Ok(request)
+        };
+        let request = match request() {
+            Ok(request) => request,
+            Err(message) => return error(::axum::http::StatusCode::BAD_REQUEST, message),
+        };
+        match service.list_books(request).await {
+            Ok(response) => json(::axum::http::StatusCode::OK, &response.books),
+            Err(e) => {
+                error(::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        }
+    }
+    #[allow(unused_variables, unused_mut)]
+    async fn list_books_1<T: Library>(
+        ::axum::extract::State(service): ::axum::extract::State<::std::sync::Arc<T>>,
+        ::axum::extract::Query(query): ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {
+        let request = move || -> Result<super::ListBooksRequest, String> {
+            let mut request: super::ListBooksRequest = ::core::default::Default::default();
+            if let Some(value) = query_param(&query, &["shelf"]) {
+                request.shelf = parse_param("shelf", value)?;
+            }
+            if let Some(value) = query_param(&query, &["page_size", "pageSize"]) {
+                request.page_size = parse_param("page_size", value)?;
+            }
+            if let Some(value) = query_param(&query, &["filter.author"]) {
+                request
+                    .filter
+                    .get_or_insert_with(::core::default::Default::default)
+                    .author = Some(parse_param("filter.author", value)?);
+            }
+            if let Some(value) = query_param(
+                &query,
+                &["filter.min_pages", "filter.minPages"],
+            ) {
+                request
+                    .filter
+                    .get_or_insert_with(::core::default::Default::default)
+                    .min_pages = parse_param("filter.min_pages", value)?;
+            }
+            if let Some(value) = query_param(&query, &["filter.format"]) {
+                request
+                    .filter
+                    .get_or_insert_with(::core::default::Default::default)
+                    .format = parse_enum(
+                    "filter.format",
+                    value,
+                    super::Format::from_str_name,
+                )?;
+            }
+            if let Some(values) = query_values(&query, &["filter.tags"]) {
+                request
+                    .filter
+                    .get_or_insert_with(::core::default::Default::default)
+                    .tags = values
+                    .into_iter()
+                    .map(|value| parse_param("filter.tags", value))
+                    .collect::<Result<_, _>>()?;
+            }
+            Ok(request)
+        };
+        let request = match request() {
+            Ok(request) => request,
+            Err(message) => return error(::axum::http::StatusCode::BAD_REQUEST, message),
+        };
+        match service.list_books(request).await {
+            Ok(response) => json(::axum::http::StatusCode::OK, &response),
+            Err(e) => {
+                error(::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        }
+    }
+    #[allow(unused_variables, unused_mut)]
+    async fn create_book_0<T: Library>(
+        ::axum::extract::State(service): ::axum::extract::State<::std::sync::Arc<T>>,
+        ::axum::extract::Path(path): ::axum::extract::Path<Params>,
+        ::axum::extract::Query(query): ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {
+        let request = move || -> Result<super::CreateBookRequest, String> {
+            let mut request: super::CreateBookRequest = ::core::default::Default::default();
+            request.book = parse_body(&body)?;
+            request.shelf = parse_param("shelf", param(&path, "p0")?)?;
+            Ok(request)
+        };
+        let request = match request() {
+            Ok(request) => request,
+            Err(message) => return error(::axum::http::StatusCode::BAD_REQUEST, message),
+        };
+        match service.create_book(request).await {
+            Ok(response) => json(::axum::http::StatusCode::OK, &response),
+            Err(e) => {
+                error(::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        }
+    }
+    #[allow(unused_variables, unused_mut)]
+    async fn move_book_0<T: Library>(
+        ::axum::extract::State(service): ::axum::extract::State<::std::sync::Arc<T>>,
+        ::axum::extract::Path(path): ::axum::extract::Path<Params>,
+        ::axum::extract::Query(query): ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {
+        let request = move || -> Result<super::MoveBookRequest, String> {
+            let mut request: super::MoveBookRequest = parse_body(&body)?;
+            Ok(request)
+        };
+        let request = match request() {
+            Ok(request) => request,
+            Err(message) => return error(::axum::http::StatusCode::BAD_REQUEST, message),
+        };
+        match service.move_book(request).await {
+            Ok(response) => json(::axum::http::StatusCode::OK, &response),
+            Err(e) => {
+                error(::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        }
+    }
+    #[allow(unused_variables, unused_mut)]
+    async fn copy_book_0<T: Library>(
+        ::axum::extract::State(service): ::axum::extract::State<::std::sync::Arc<T>>,
+        ::axum::extract::Path(path): ::axum::extract::Path<Params>,
+        ::axum::extract::Query(query): ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {
+        let request = move || -> Result<super::MoveBookRequest, String> {
+            let mut request: super::MoveBookRequest = parse_body(&body)?;
+            Ok(request)
+        };
+        let request = match request() {
+            Ok(request) => request,
+            Err(message) => return error(::axum::http::StatusCode::BAD_REQUEST, message),
+        };
+        match service.copy_book(request).await {
+            Ok(response) => json(::axum::http::StatusCode::OK, &response),
+            Err(e) => {
+                error(::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        }
+    }
+    #[allow(unused_variables, unused_mut)]
+    async fn publish_book_0<T: Library>(
+        ::axum::extract::State(service): ::axum::extract::State<::std::sync::Arc<T>>,
+        ::axum::extract::Path(path): ::axum::extract::Path<Params>,
+        ::axum::extract::Query(query): ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {
+        let request = move || -> Result<super::GetBookRequest, String> {
+            let mut request: super::GetBookRequest = ::core::default::Default::default();
+            request.name = parse_param(
+                "name",
+                &["shelves", param(&path, "p0")?, "books", param(&path, "p1")?].join("/"),
+            )?;
+            Ok(request)
+        };
+        let request = match request() {
+            Ok(request) => request,
+            Err(message) => return error(::axum::http::StatusCode::BAD_REQUEST, message),
+        };
+        match service.publish_book(request).await {
+            Ok(response) => json(::axum::http::StatusCode::OK, &response),
+            Err(e) => {
+                error(::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        }
+    }
+    #[allow(unused_variables, unused_mut)]
+    async fn publish_book_1<T: Library>(
+        ::axum::extract::State(service): ::axum::extract::State<::std::sync::Arc<T>>,
+        ::axum::extract::Path(path): ::axum::extract::Path<Params>,
+        ::axum::extract::Query(query): ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {
+        let request = move || -> Result<super::GetBookRequest, String> {
+            let mut request: super::GetBookRequest = ::core::default::Default::default();
+            request.name = parse_param(
+                "name",
+                &["drafts", param(&path, "p0")?].join("/"),
+            )?;
+            Ok(request)
+        };
+        let request = match request() {
+            Ok(request) => request,
+            Err(message) => return error(::axum::http::StatusCode::BAD_REQUEST, message),
+        };
+        match service.publish_book(request).await {
+            Ok(response) => json(::axum::http::StatusCode::OK, &response),
+            Err(e) => {
+                error(::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        }
+    }
+    #[allow(unused_mut)]
+    async fn route_0_post<T: Library>(
+        state: ::axum::extract::State<::std::sync::Arc<T>>,
+        mut path: ::axum::extract::Path<Params>,
+        query: ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {
+        let segment = path.get("p1").cloned().unwrap_or_default();
+        if let Some(value) = segment.strip_suffix(":publish").filter(|v| !v.is_empty()) {
+            path.0.insert("p1".to_owned(), value.to_owned());
+            return publish_book_0(state, path, query, body).await;
+        }
+        error(::axum::http::StatusCode::NOT_FOUND, "not found".to_owned())
+    }
+    #[allow(unused_mut)]
+    async fn route_3_post<T: Library>(
+        state: ::axum::extract::State<::std::sync::Arc<T>>,
+        mut path: ::axum::extract::Path<Params>,
+        query: ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {
+        let segment = path.get("p0").cloned().unwrap_or_default();
+        if segment == "books:move" {
+            return move_book_0(state, path, query, body).await;
+        }
+        if segment == "books:copy" {
+            return copy_book_0(state, path, query, body).await;
+        }
+        error(::axum::http::StatusCode::NOT_FOUND, "not found".to_owned())
+    }
+    #[allow(unused_mut)]
+    async fn route_4_post<T: Library>(
+        state: ::axum::extract::State<::std::sync::Arc<T>>,
+        mut path: ::axum::extract::Path<Params>,
+        query: ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {
+        let segment = path.get("p0").cloned().unwrap_or_default();
+        if let Some(value) = segment.strip_suffix(":publish").filter(|v| !v.is_empty()) {
+            path.0.insert("p0".to_owned(), value.to_owned());
+            return publish_book_1(state, path, query, body).await;
+        }
+        error(::axum::http::StatusCode::NOT_FOUND, "not found".to_owned())
+    }
+    type Params = ::std::collections::HashMap<String, String>;
+    /// the query parameters in their order, as repeated fields occur several times
+    type QueryParams = Vec<(String, String)>;
+    #[allow(dead_code)]
+    fn param<'a>(params: &'a Params, name: &str) -> Result<&'a str, String> {
+        params
+            .get(name)
+            .map(|value| value.trim_start_matches('/'))
+            .ok_or_else(|| format!("missing path parameter {}", name))
+    }
+    #[allow(dead_code)]
+    fn query_param<'a>(query: &'a QueryParams, names: &[&str]) -> Option<&'a str> {
+        names
+            .iter()
+            .find_map(|name| query.iter().find(|(key, _)| key == name))
+            .map(|(_, value)| value.as_str())
+    }
+    #[allow(dead_code)]
+    fn query_values<'a>(query: &'a QueryParams, names: &[&str]) -> Option<Vec<&'a str>> {
+        let values: Vec<_> = query
+            .iter()
+            .filter(|(key, _)| names.contains(&key.as_str()))
+            .map(|(_, value)| value.as_str())
+            .collect();
+        (!values.is_empty()).then_some(values)
+    }
+    #[allow(dead_code)]
+    fn parse_param<T: ::core::str::FromStr>(name: &str, value: &str) -> Result<T, String>
+    where
+        T::Err: ::core::fmt::Display,
+    {
+        value.parse().map_err(|e| format!("invalid value for {}: {}", name, e))
+    }
+    #[allow(dead_code)]
+    fn parse_enum<E: Into<i32>>(
+        name: &str,
+        value: &str,
+        from_str_name: fn(&str) -> Option<E>,
+    ) -> Result<i32, String> {
+        match value.parse() {
+            Ok(number) => Ok(number),
+            Err(_) => {
+                from_str_name(value)
+                    .map(Into::into)
+                    .ok_or_else(|| format!("invalid value for {}: {}", name, value))
+            }
+        }
+    }
+    #[allow(dead_code)]
+    fn parse_body<T: ::serde::de::DeserializeOwned>(body: &[u8]) -> Result<T, String> {
+        ::serde_json::from_slice(body).map_err(|e| format!("invalid body: {}", e))
+    }
+    fn json<T: ::serde::Serialize>(
+        status: ::axum::http::StatusCode,
+        value: &T,
+    ) -> ::axum::response::Response {
+        use ::axum::response::IntoResponse;
+        match ::serde_json::to_vec(value) {
+            Ok(body) => {
+                (
+                    status,
+                    [(::axum::http::header::CONTENT_TYPE, "application/json")],
+                    body,
+                )
+                    .into_response()
+            }
+            Err(e) => {
+                error(::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        }
+    }
+    fn error(
+        status: ::axum::http::StatusCode,
+        message: String,
+    ) -> ::axum::response::Response {
+        use ::axum::response::IntoResponse;
+        let body: ::serde_json::Map<String, ::serde_json::Value> = [
+            ("code".to_owned(), status.as_u16().into()),
+            ("message".to_owned(), message.into()),
+        ]
+            .into_iter()
+            .collect();
+        (status, ::axum::Json(body)).into_response()
+    }
+}
+impl ::prost_helper::proto3::Proto3Enum for Format {
+    fn name(value: i32) -> ::core::option::Option<&'static str> {
+        Self::try_from(value).ok().map(|v| v.as_str_name())
+    }
+    fn value(name: &str) -> ::core::option::Option<i32> {
+        Self::from_str_name(name).map(|v| v as i32)
+    }
+}
@@ -0,0 +1,15 @@
+// This file is @generated by prost-build-config.
+/// Protobuf package `google`.
+pub mod google {
+    /// Protobuf package `google.api`.
+    pub mod api {
+        include!("google.api.rs");
+    }
+}
+/// Protobuf package `library`.
+pub mod library {
+    include!("library.rs");
+}
+/// The encoded `FileDescriptorSet` of the compiled protobuf files and their imports, e.g. for
+/// `prost_reflect::DescriptorPool::decode`.
+pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!("descriptor_set.bin");
@@ -0,0 +1,102 @@
+// This file is @generated by prost-build-config.
+
+/// A user account.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct User {
+    #[prost(bytes = "vec", tag = "1")]
+    #[serde(
+        serialize_with = "prost_helper::serialize_id",
+        deserialize_with = "prost_helper::deserialize_id_vec"
+    )]
+    pub id: ::prost::alloc::vec::Vec<u8>,
+    /// Name shown to other users,
+    /// empty for the `id`.
+    #[prost(string, tag = "2")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub display_name: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "3")]
+    #[serde(
+        serialize_with = "prost_helper::serialize_buf",
+        deserialize_with = "prost_helper::deserialize_buf_vec"
+    )]
+    pub avatar: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    #[serde(
+        serialize_with = "prost_helper::serialize_repeat_id",
+        deserialize_with = "prost_helper::deserialize_repeat_id_vec"
+    )]
+    pub friend_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(enumeration = "Role", tag = "5")]
+    pub role: i32,
+    #[prost(string, optional, tag = "6")]
+    pub email: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "7")]
+    pub address: ::core::option::Option<Address>,
+    #[prost(map = "string, int64", tag = "8")]
+    pub counters: ::std::collections::HashMap<::prost::alloc::string::String, i64>,
+    #[prost(string, tag = "9")]
+    #[serde(skip_serializing)]
+    pub password: ::prost::alloc::string::String,
+    #[prost(oneof = "user::Login", tags = "10, 11")]
+    pub login: ::core::option::Option<user::Login>,
+}
+/// Nested message and enum types in `User`.
+pub mod user {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(tag = "type", content = "value", rename_all = "lowercase")]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Login {
+        #[prost(string, tag = "10")]
+        Username(::prost::alloc::string::String),
+        #[prost(uint64, tag = "11")]
+        Phone(u64),
+    }
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Address {
+    /// City or town.
+    #[prost(string, tag = "1")]
+    pub city: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub zip: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Role {
+    Unspecified = 0,
+    Admin = 1,
+    Member = 2,
+}
+impl Role {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Role::Unspecified => "ROLE_UNSPECIFIED",
+            Role::Admin => "ROLE_ADMIN",
+            Role::Member => "ROLE_MEMBER",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ROLE_UNSPECIFIED" => Some(Self::Unspecified),
+            "ROLE_ADMIN" => Some(Self::Admin),
+            "ROLE_MEMBER" => Some(Self::Member),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,5 @@
+// This file is @generated by prost-build-config.
+/// Protobuf package `account`.
+pub mod account {
+    include!("account.rs");
+}
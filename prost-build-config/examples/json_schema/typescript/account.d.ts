@@ -0,0 +1,36 @@
+// This file is @generated by prost-build-config.
+
+/**
+ * A user account.
+ *
+ * `account.User`
+ */
+export interface User {
+  address?: Address | null;
+  /** `base64url` string */
+  avatar?: string;
+  counters?: { [key: string]: number };
+  /**
+   * Name shown to other users,
+   * empty for the `id`.
+   */
+  displayName?: string;
+  email?: string | null;
+  /** `uuid` string */
+  friendIds?: string[];
+  /** `uuid` string */
+  id?: string;
+  login?: ({ type: "username"; value: string } | { type: "phone"; value: number }) | null;
+  role?: number;
+}
+
+/** `account.Address` */
+export interface Address {
+  /** City or town. */
+  city: string;
+  zip?: string;
+}
+
+/** `account.Role` */
+export type Role = "unspecified" | "admin" | "member";
+
@@ -0,0 +1,15 @@
+// This file is @generated by prost-build-config.
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CountBooksResponse {
+    #[prost(int64, tag = "1")]
+    #[serde(
+        rename = "count",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub count: i64,
+}
@@ -0,0 +1,207 @@
+// This file is @generated by prost-build-config.
+
+/// A book on a shelf.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Book {
+    /// Resource name of the book, e.g. `shelves/1/books/2`.
+    #[prost(string, tag = "1")]
+    #[serde(
+        rename = "name",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    #[serde(
+        rename = "title",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub title: ::prost::alloc::string::String,
+    #[prost(int32, tag = "3")]
+    #[serde(
+        rename = "pages",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub pages: i32,
+    #[prost(string, optional, tag = "4")]
+    #[serde(
+        rename = "author",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub author: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetBookRequest {
+    #[prost(string, tag = "1")]
+    #[serde(
+        rename = "name",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub name: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListBooksRequest {
+    #[prost(string, tag = "1")]
+    #[serde(
+        rename = "shelf",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub shelf: ::prost::alloc::string::String,
+    /// Maximum number of books to return.
+    #[prost(int32, tag = "2")]
+    #[serde(
+        rename = "pageSize",
+        alias = "page_size",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub page_size: i32,
+    #[prost(message, optional, tag = "3")]
+    #[serde(
+        rename = "filter",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub filter: ::core::option::Option<Filter>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Filter {
+    #[prost(string, optional, tag = "1")]
+    #[serde(
+        rename = "author",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub author: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(int32, tag = "2")]
+    #[serde(
+        rename = "minPages",
+        alias = "min_pages",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub min_pages: i32,
+    #[prost(enumeration = "Format", tag = "3")]
+    #[serde(
+        rename = "format",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        serialize_with = "::prost_helper::proto3::serialize_enum::<Format, _, _>",
+        deserialize_with = "::prost_helper::proto3::deserialize_enum::<Format, _, _>"
+    )]
+    pub format: i32,
+    #[prost(string, repeated, tag = "4")]
+    #[serde(
+        rename = "tags",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub tags: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListBooksResponse {
+    #[prost(message, repeated, tag = "1")]
+    #[serde(
+        rename = "books",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub books: ::prost::alloc::vec::Vec<Book>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateBookRequest {
+    #[prost(string, tag = "1")]
+    #[serde(
+        rename = "shelf",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub shelf: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    #[serde(
+        rename = "book",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub book: ::core::option::Option<Book>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MoveBookRequest {
+    #[prost(string, tag = "1")]
+    #[serde(
+        rename = "name",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    #[serde(
+        rename = "shelf",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub shelf: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Format {
+    Unspecified = 0,
+    Hardcover = 1,
+    Paperback = 2,
+}
+impl Format {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Format::Unspecified => "FORMAT_UNSPECIFIED",
+            Format::Hardcover => "HARDCOVER",
+            Format::Paperback => "PAPERBACK",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "FORMAT_UNSPECIFIED" => Some(Self::Unspecified),
+            "HARDCOVER" => Some(Self::Hardcover),
+            "PAPERBACK" => Some(Self::Paperback),
+            _ => None,
+        }
+    }
+}
+impl ::prost_helper::proto3::Proto3Enum for Format {
+    fn name(value: i32) -> ::core::option::Option<&'static str> {
+        Self::try_from(value).ok().map(|v| v.as_str_name())
+    }
+    fn value(name: &str) -> ::core::option::Option<i32> {
+        Self::from_str_name(name).map(|v| v as i32)
+    }
+}
@@ -0,0 +1,16 @@
+// This file is @generated by prost-build-config.
+/// Protobuf package `catalog`.
+pub mod catalog {
+    include!("catalog.rs");
+}
+/// Protobuf package `google`.
+pub mod google {
+    /// Protobuf package `google.api`.
+    pub mod api {
+        include!("google.api.rs");
+    }
+}
+/// Protobuf package `library`.
+pub mod library {
+    include!("library.rs");
+}
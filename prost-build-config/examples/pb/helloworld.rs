@@ -1,3 +1,5 @@
+// This file is @generated by prost-build-config.
+
 #[derive(serde::Serialize, serde::Deserialize, validator::Validate)]
 #[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -44,6 +46,21 @@ pub struct Hello {
     #[prost(message, optional, tag = "9")]
     #[validate(required)]
     pub world: ::core::option::Option<World>,
+    #[prost(oneof = "hello::Body", tags = "10, 11")]
+    pub body: ::core::option::Option<hello::Body>,
+}
+/// Nested message and enum types in `Hello`.
+pub mod hello {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(tag = "type", content = "value", rename_all = "lowercase")]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Body {
+        #[prost(string, tag = "10")]
+        Text(::prost::alloc::string::String),
+        #[prost(uint32, tag = "11")]
+        Code(u32),
+    }
 }
 #[derive(serde::Serialize, serde::Deserialize, validator::Validate)]
 #[serde(default)]
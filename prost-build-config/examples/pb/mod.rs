@@ -0,0 +1,5 @@
+// This file is @generated by prost-build-config.
+/// Protobuf package `helloworld`.
+pub mod helloworld {
+    include!("helloworld.rs");
+}
@@ -0,0 +1,318 @@
+// This file is @generated by prost-build-config.
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Sample {
+    #[prost(string, tag = "1")]
+    #[serde(
+        rename = "userName",
+        alias = "user_name",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub user_name: ::prost::alloc::string::String,
+    #[prost(int32, tag = "2")]
+    #[serde(
+        rename = "count",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub count: i32,
+    #[prost(int64, tag = "3")]
+    #[serde(
+        rename = "big",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub big: i64,
+    #[prost(uint64, tag = "4")]
+    #[serde(
+        rename = "ubig",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub ubig: u64,
+    #[prost(double, tag = "5")]
+    #[serde(
+        rename = "ratio",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub ratio: f64,
+    #[prost(float, tag = "6")]
+    #[serde(
+        rename = "score",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub score: f32,
+    #[prost(bool, tag = "7")]
+    #[serde(
+        rename = "active",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub active: bool,
+    #[prost(bytes = "vec", tag = "8")]
+    #[serde(
+        rename = "payload",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub payload: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration = "Color", tag = "9")]
+    #[serde(
+        rename = "color",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        serialize_with = "::prost_helper::proto3::serialize_enum::<Color, _, _>",
+        deserialize_with = "::prost_helper::proto3::deserialize_enum::<Color, _, _>"
+    )]
+    pub color: i32,
+    #[prost(enumeration = "sample::Kind", tag = "10")]
+    #[serde(
+        rename = "kind",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        serialize_with = "::prost_helper::proto3::serialize_enum::<sample::Kind, _, _>",
+        deserialize_with = "::prost_helper::proto3::deserialize_enum::<sample::Kind, _, _>"
+    )]
+    pub kind: i32,
+    #[prost(int64, repeated, tag = "11")]
+    #[serde(
+        rename = "ids",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub ids: ::prost::alloc::vec::Vec<i64>,
+    #[prost(enumeration = "Color", repeated, tag = "12")]
+    #[serde(
+        rename = "colors",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        serialize_with = "::prost_helper::proto3::serialize_enum::<Color, _, _>",
+        deserialize_with = "::prost_helper::proto3::deserialize_enum::<Color, _, _>"
+    )]
+    pub colors: ::prost::alloc::vec::Vec<i32>,
+    #[prost(map = "string, int64", tag = "13")]
+    #[serde(
+        rename = "counters",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub counters: ::std::collections::HashMap<::prost::alloc::string::String, i64>,
+    #[prost(map = "int32, enumeration(Color)", tag = "14")]
+    #[serde(
+        rename = "colorMap",
+        alias = "color_map",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        serialize_with = "::prost_helper::proto3::serialize_enum::<Color, _, _>",
+        deserialize_with = "::prost_helper::proto3::deserialize_enum::<Color, _, _>"
+    )]
+    pub color_map: ::std::collections::HashMap<i32, i32>,
+    #[prost(message, optional, tag = "15")]
+    #[serde(
+        rename = "nested",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub nested: ::core::option::Option<sample::Nested>,
+    #[prost(message, repeated, tag = "16")]
+    #[serde(
+        rename = "children",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub children: ::prost::alloc::vec::Vec<sample::Nested>,
+    #[prost(int32, optional, tag = "17")]
+    #[serde(
+        rename = "maybe",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub maybe: ::core::option::Option<i32>,
+    #[prost(message, optional, tag = "18")]
+    #[serde(
+        rename = "createdAt",
+        alias = "created_at",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "19")]
+    #[serde(
+        rename = "ttl",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub ttl: ::core::option::Option<::prost_types::Duration>,
+    #[prost(message, optional, tag = "20")]
+    #[serde(
+        rename = "wrapped",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub wrapped: ::core::option::Option<i64>,
+    #[prost(message, optional, tag = "21")]
+    #[serde(
+        rename = "label",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        deserialize_with = "::prost_helper::deserialize_null_default"
+    )]
+    pub label: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "22")]
+    #[serde(
+        rename = "extra",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub extra: ::core::option::Option<::prost_types::Struct>,
+    #[prost(message, optional, tag = "23")]
+    #[serde(
+        rename = "mask",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub mask: ::core::option::Option<::prost_types::FieldMask>,
+    #[prost(message, optional, tag = "27")]
+    #[serde(
+        rename = "nothing",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub nothing: ::core::option::Option<()>,
+    #[prost(message, optional, tag = "28")]
+    #[serde(
+        rename = "detail",
+        skip_serializing_if = "::prost_helper::proto3::is_default",
+        with = "::prost_helper::proto3"
+    )]
+    pub detail: ::core::option::Option<::prost_types::Any>,
+    #[prost(oneof = "sample::Choice", tags = "24, 25, 26")]
+    #[serde(flatten)]
+    pub choice: ::core::option::Option<sample::Choice>,
+}
+/// Nested message and enum types in `Sample`.
+pub mod sample {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(default)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Nested {
+        #[prost(int64, tag = "1")]
+        #[serde(
+            rename = "id",
+            skip_serializing_if = "::prost_helper::proto3::is_default",
+            with = "::prost_helper::proto3"
+        )]
+        pub id: i64,
+        #[prost(enumeration = "super::Color", tag = "2")]
+        #[serde(
+            rename = "color",
+            skip_serializing_if = "::prost_helper::proto3::is_default",
+            serialize_with = "::prost_helper::proto3::serialize_enum::<super::Color, _, _>",
+            deserialize_with = "::prost_helper::proto3::deserialize_enum::<super::Color, _, _>"
+        )]
+        pub color: i32,
+    }
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum Kind {
+        Unspecified = 0,
+        Primary = 1,
+    }
+    impl Kind {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                Kind::Unspecified => "KIND_UNSPECIFIED",
+                Kind::Primary => "PRIMARY",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "KIND_UNSPECIFIED" => Some(Self::Unspecified),
+                "PRIMARY" => Some(Self::Primary),
+                _ => None,
+            }
+        }
+    }
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Choice {
+        #[prost(string, tag = "24")]
+        #[serde(rename = "text")]
+        Text(::prost::alloc::string::String),
+        #[prost(int64, tag = "25")]
+        #[serde(rename = "number", with = "::prost_helper::proto3")]
+        Number(i64),
+        #[prost(enumeration = "super::Color", tag = "26")]
+        #[serde(
+            rename = "shade",
+            serialize_with = "::prost_helper::proto3::serialize_enum::<super::Color, _, _>",
+            deserialize_with = "::prost_helper::proto3::deserialize_enum::<super::Color, _, _>"
+        )]
+        Shade(i32),
+    }
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Color {
+    Unspecified = 0,
+    Red = 1,
+    Green = 2,
+}
+impl Color {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Color::Unspecified => "COLOR_UNSPECIFIED",
+            Color::Red => "RED",
+            Color::Green => "GREEN",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "COLOR_UNSPECIFIED" => Some(Self::Unspecified),
+            "RED" => Some(Self::Red),
+            "GREEN" => Some(Self::Green),
+            _ => None,
+        }
+    }
+}
+impl ::prost_helper::proto3::Proto3Enum for Color {
+    fn name(value: i32) -> ::core::option::Option<&'static str> {
+        Self::try_from(value).ok().map(|v| v.as_str_name())
+    }
+    fn value(name: &str) -> ::core::option::Option<i32> {
+        Self::from_str_name(name).map(|v| v as i32)
+    }
+}
+impl ::prost_helper::proto3::Proto3Enum for sample::Kind {
+    fn name(value: i32) -> ::core::option::Option<&'static str> {
+        Self::try_from(value).ok().map(|v| v.as_str_name())
+    }
+    fn value(name: &str) -> ::core::option::Option<i32> {
+        Self::from_str_name(name).map(|v| v as i32)
+    }
+}
@@ -0,0 +1,5 @@
+// This file is @generated by prost-build-config.
+/// Protobuf package `conformance`.
+pub mod conformance {
+    include!("conformance.rs");
+}
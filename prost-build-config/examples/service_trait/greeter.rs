@@ -0,0 +1,235 @@
+// This file is @generated by prost-build-config.
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HelloRequest {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HelloReply {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+/// Async trait, dispatcher and mock of the `greeter.Greeter` service.
+pub mod greeter_service {
+    /// error of the service methods
+    pub type Error = ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>;
+    /// stream of requests or responses
+    pub type BoxStream<T> = ::core::pin::Pin<
+        ::std::boxed::Box<dyn ::futures_core::Stream<Item = T> + Send>,
+    >;
+    /// The greeting service.
+    pub trait Greeter: Send + Sync + 'static {
+        /// Sends a greeting.
+        fn say_hello(
+            &self,
+            request: super::HelloRequest,
+        ) -> impl ::core::future::Future<
+            Output = Result<super::HelloReply, Error>,
+        > + Send;
+        /// Sends a greeting every second.
+        fn stream_hellos(
+            &self,
+            request: super::HelloRequest,
+        ) -> impl ::core::future::Future<
+            Output = Result<BoxStream<Result<super::HelloReply, Error>>, Error>,
+        > + Send;
+        /// Greets everyone at once.
+        fn greet_all(
+            &self,
+            request: BoxStream<super::HelloRequest>,
+        ) -> impl ::core::future::Future<
+            Output = Result<super::HelloReply, Error>,
+        > + Send;
+        /// Greets everyone back.
+        fn chat(
+            &self,
+            request: BoxStream<super::HelloRequest>,
+        ) -> impl ::core::future::Future<
+            Output = Result<BoxStream<Result<super::HelloReply, Error>>, Error>,
+        > + Send;
+    }
+    /// A request to a method of `Greeter`.
+    pub enum GreeterRequest {
+        SayHello(super::HelloRequest),
+        StreamHellos(super::HelloRequest),
+        GreetAll(BoxStream<super::HelloRequest>),
+        Chat(BoxStream<super::HelloRequest>),
+    }
+    /// The response of a method of `Greeter`.
+    pub enum GreeterResponse {
+        SayHello(super::HelloReply),
+        StreamHellos(BoxStream<Result<super::HelloReply, Error>>),
+        GreetAll(super::HelloReply),
+        Chat(BoxStream<Result<super::HelloReply, Error>>),
+    }
+    /// Call the method of `service` which `request` is for.
+    pub async fn dispatch<T: Greeter>(
+        service: &T,
+        request: GreeterRequest,
+    ) -> Result<GreeterResponse, Error> {
+        match request {
+            GreeterRequest::SayHello(request) => {
+                service.say_hello(request).await.map(GreeterResponse::SayHello)
+            }
+            GreeterRequest::StreamHellos(request) => {
+                service.stream_hellos(request).await.map(GreeterResponse::StreamHellos)
+            }
+            GreeterRequest::GreetAll(request) => {
+                service.greet_all(request).await.map(GreeterResponse::GreetAll)
+            }
+            GreeterRequest::Chat(request) => {
+                service.chat(request).await.map(GreeterResponse::Chat)
+            }
+        }
+    }
+    /// `Greeter` which answers with the handlers set on it, for tests. Methods without a handler
+    /// fail.
+    #[allow(clippy::type_complexity)]
+    #[derive(Default)]
+    pub struct MockGreeter {
+        say_hello: Option<
+            ::std::boxed::Box<
+                dyn Fn(
+                    super::HelloRequest,
+                ) -> Result<super::HelloReply, Error> + Send + Sync,
+            >,
+        >,
+        stream_hellos: Option<
+            ::std::boxed::Box<
+                dyn Fn(
+                    super::HelloRequest,
+                ) -> Result<::std::vec::Vec<super::HelloReply>, Error> + Send + Sync,
+            >,
+        >,
+        greet_all: Option<
+            ::std::boxed::Box<
+                dyn Fn(
+                    ::std::vec::Vec<super::HelloRequest>,
+                ) -> Result<super::HelloReply, Error> + Send + Sync,
+            >,
+        >,
+        chat: Option<
+            ::std::boxed::Box<
+                dyn Fn(
+                    ::std::vec::Vec<super::HelloRequest>,
+                ) -> Result<::std::vec::Vec<super::HelloReply>, Error> + Send + Sync,
+            >,
+        >,
+    }
+    impl MockGreeter {
+        /// Answer `say_hello` with `handler`.
+        pub fn with_say_hello(
+            mut self,
+            handler: impl Fn(
+                super::HelloRequest,
+            ) -> Result<super::HelloReply, Error> + Send + Sync + 'static,
+        ) -> Self {
+            self.say_hello = Some(::std::boxed::Box::new(handler));
+            self
+        }
+        /// Answer `stream_hellos` with `handler`.
+        pub fn with_stream_hellos(
+            mut self,
+            handler: impl Fn(
+                super::HelloRequest,
+            ) -> Result<
+                    ::std::vec::Vec<super::HelloReply>,
+                    Error,
+                > + Send + Sync + 'static,
+        ) -> Self {
+            self.stream_hellos = Some(::std::boxed::Box::new(handler));
+            self
+        }
+        /// Answer `greet_all` with `handler`.
+        pub fn with_greet_all(
+            mut self,
+            handler: impl Fn(
+                ::std::vec::Vec<super::HelloRequest>,
+            ) -> Result<super::HelloReply, Error> + Send + Sync + 'static,
+        ) -> Self {
+            self.greet_all = Some(::std::boxed::Box::new(handler));
+            self
+        }
+        /// Answer `chat` with `handler`.
+        pub fn with_chat(
+            mut self,
+            handler: impl Fn(
+                ::std::vec::Vec<super::HelloRequest>,
+            ) -> Result<
+                    ::std::vec::Vec<super::HelloReply>,
+                    Error,
+                > + Send + Sync + 'static,
+        ) -> Self {
+            self.chat = Some(::std::boxed::Box::new(handler));
+            self
+        }
+    }
+    impl Greeter for MockGreeter {
+        async fn say_hello(
+            &self,
+            request: super::HelloRequest,
+        ) -> Result<super::HelloReply, Error> {
+            let handler = self
+                .say_hello
+                .as_ref()
+                .ok_or("MockGreeter::say_hello is not set")?;
+            handler(request)
+        }
+        async fn stream_hellos(
+            &self,
+            request: super::HelloRequest,
+        ) -> Result<BoxStream<Result<super::HelloReply, Error>>, Error> {
+            let handler = self
+                .stream_hellos
+                .as_ref()
+                .ok_or("MockGreeter::stream_hellos is not set")?;
+            let responses = handler(request)?;
+            Ok(::std::boxed::Box::pin(IterStream(responses.into_iter().map(Ok))))
+        }
+        async fn greet_all(
+            &self,
+            request: BoxStream<super::HelloRequest>,
+        ) -> Result<super::HelloReply, Error> {
+            let handler = self
+                .greet_all
+                .as_ref()
+                .ok_or("MockGreeter::greet_all is not set")?;
+            handler(collect(request).await)
+        }
+        async fn chat(
+            &self,
+            request: BoxStream<super::HelloRequest>,
+        ) -> Result<BoxStream<Result<super::HelloReply, Error>>, Error> {
+            let handler = self.chat.as_ref().ok_or("MockGreeter::chat is not set")?;
+            let responses = handler(collect(request).await)?;
+            Ok(::std::boxed::Box::pin(IterStream(responses.into_iter().map(Ok))))
+        }
+    }
+    /// Stream of the items of an iterator.
+    struct IterStream<I>(I);
+    impl<I: Iterator + Unpin> ::futures_core::Stream for IterStream<I> {
+        type Item = I::Item;
+        fn poll_next(
+            mut self: ::core::pin::Pin<&mut Self>,
+            _cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<Option<I::Item>> {
+            ::core::task::Poll::Ready(self.0.next())
+        }
+    }
+    /// Collect the items of a stream.
+    async fn collect<T>(mut stream: BoxStream<T>) -> ::std::vec::Vec<T> {
+        let mut items = ::std::vec::Vec::new();
+        while let Some(item) = ::core::future::poll_fn(|cx| ::futures_core::Stream::poll_next(
+                stream.as_mut(),
+                cx,
+            ))
+            .await
+        {
+            items.push(item);
+        }
+        items
+    }
+}
@@ -0,0 +1,5 @@
+// This file is @generated by prost-build-config.
+/// Protobuf package `greeter`.
+pub mod greeter {
+    include!("greeter.rs");
+}
@@ -0,0 +1,424 @@
+//! Check mode: compare the generated code with the files in the output dir instead of writing
+//! it, so that CI can fail when checked-in code is out of date.
+
+use std::{
+    env,
+    fmt::Write,
+    fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// env var which switches `Builder::build_protos` to check mode, e.g. `PROST_BUILD_CONFIG_CHECK=1`
+pub(crate) const CHECK_ENV: &str = "PROST_BUILD_CONFIG_CHECK";
+
+/// lines of context around the changes in the diff
+const CONTEXT: usize = 3;
+
+/// Whether check mode is enabled by the env var. `0`, `false` and empty values disable it.
+pub(crate) fn is_enabled() -> bool {
+    env::var(CHECK_ENV).is_ok_and(|v| !matches!(v.as_str(), "" | "0" | "false"))
+}
+
+/// Compare the generated files with the files on disk, and return a unified diff of the stale
+/// ones, or an empty string if all are up to date.
+pub(crate) fn diff_files(files: &[(PathBuf, Vec<u8>)]) -> io::Result<String> {
+    let mut buf = String::new();
+    for (path, content) in files {
+        let prev = match fs::read(path) {
+            Ok(prev) => Some(prev),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        if prev.as_ref() == Some(content) {
+            continue;
+        }
+        let name = path.display();
+        match (
+            prev.as_deref().map(std::str::from_utf8),
+            std::str::from_utf8(content),
+        ) {
+            (None, Ok(new)) => buf.push_str(&unified_diff("/dev/null", &name.to_string(), "", new)),
+            (Some(Ok(old)), Ok(new)) => {
+                let (a, b) = (format!("a/{}", name), format!("b/{}", name));
+                buf.push_str(&unified_diff(&a, &b, old, new))
+            }
+            _ => {
+                let _ = writeln!(buf, "Binary file {} differs", name);
+            }
+        }
+    }
+    Ok(buf)
+}
+
+/// The files in `dir` which aren't among the generated `files`, e.g. of a removed proto file.
+/// Only files with the `extension` are listed, if it is given.
+pub(crate) fn extra_files(
+    dir: &Path,
+    extension: Option<&str>,
+    files: &[(PathBuf, Vec<u8>)],
+) -> io::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut extra = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let is_generated = files.iter().any(|(p, _)| *p == path);
+        let has_extension = extension.is_none_or(|ext| path.extension().is_some_and(|e| e == ext));
+        if path.is_file() && has_extension && !is_generated {
+            extra.push(path);
+        }
+    }
+    extra.sort();
+    Ok(extra)
+}
+
+/// A diff which removes the stale `paths`.
+pub(crate) fn diff_removed(paths: &[PathBuf]) -> io::Result<String> {
+    let mut buf = String::new();
+    for path in paths {
+        let content = fs::read(path)?;
+        let name = path.display();
+        match std::str::from_utf8(&content) {
+            Ok(old) => buf.push_str(&unified_diff(&format!("a/{}", name), "/dev/null", old, "")),
+            Err(_) => {
+                let _ = writeln!(buf, "Binary file {} is not generated anymore", name);
+            }
+        }
+    }
+    Ok(buf)
+}
+
+/// One line of the edit script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    /// unchanged line, with its index in the old and the new text
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// A unified diff of `old` and `new`, like `diff -u` prints it.
+fn unified_diff(old_name: &str, new_name: &str, old: &str, new: &str) -> String {
+    let a: Vec<_> = old.lines().collect();
+    let b: Vec<_> = new.lines().collect();
+    let edits = edit_script(&a, &b);
+
+    let mut buf = format!("--- {}\n+++ {}\n", old_name, new_name);
+    let changes: Vec<_> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !matches!(e, Edit::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    let mut idx = 0;
+    while idx < changes.len() {
+        // extend the hunk while the next change is within the context of this one
+        let start = changes[idx].saturating_sub(CONTEXT);
+        let mut end = changes[idx];
+        while idx < changes.len() && changes[idx] <= end + 2 * CONTEXT {
+            end = changes[idx];
+            idx += 1;
+        }
+        let end = (end + CONTEXT + 1).min(edits.len());
+        append_hunk(
+            &mut buf,
+            &edits[start..end],
+            line_counts(&edits[..start]),
+            &a,
+            &b,
+        );
+    }
+    buf
+}
+
+/// Append the hunk of `edits`, which start after `old_pos` lines of the old text and `new_pos`
+/// lines of the new text.
+fn append_hunk(
+    buf: &mut String,
+    edits: &[Edit],
+    (old_pos, new_pos): (usize, usize),
+    a: &[&str],
+    b: &[&str],
+) {
+    let (old_len, new_len) = line_counts(edits);
+    // an empty range is numbered by the line before it
+    let range = |pos: usize, len: usize| match len {
+        0 => format!("{},0", pos),
+        1 => format!("{}", pos + 1),
+        len => format!("{},{}", pos + 1, len),
+    };
+    let _ = writeln!(
+        buf,
+        "@@ -{} +{} @@",
+        range(old_pos, old_len),
+        range(new_pos, new_len)
+    );
+    for edit in edits {
+        let _ = match *edit {
+            Edit::Equal(i, _) => writeln!(buf, " {}", a[i]),
+            Edit::Delete(i) => writeln!(buf, "-{}", a[i]),
+            Edit::Insert(j) => writeln!(buf, "+{}", b[j]),
+        };
+    }
+}
+
+/// Number of lines of the old and the new text in `edits`.
+fn line_counts(edits: &[Edit]) -> (usize, usize) {
+    edits.iter().fold((0, 0), |(old, new), edit| match edit {
+        Edit::Equal(..) => (old + 1, new + 1),
+        Edit::Delete(_) => (old + 1, new),
+        Edit::Insert(_) => (old, new + 1),
+    })
+}
+
+/// The shortest edit script turning `a` into `b`, with the linear space variant of Myers'
+/// algorithm, so that large new or rewritten files don't need memory quadratic in the changes.
+fn edit_script(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let mut edits = Vec::with_capacity(a.len().max(b.len()));
+    diff_range(&mut edits, a, 0..a.len(), b, 0..b.len());
+    edits
+}
+
+/// Append the edits turning `a[old]` into `b[new]`, by splitting them at the middle snake.
+fn diff_range(
+    edits: &mut Vec<Edit>,
+    a: &[&str],
+    mut old: Range<usize>,
+    b: &[&str],
+    mut new: Range<usize>,
+) {
+    while !old.is_empty() && !new.is_empty() && a[old.start] == b[new.start] {
+        edits.push(Edit::Equal(old.start, new.start));
+        old.start += 1;
+        new.start += 1;
+    }
+    let mut suffix = 0;
+    while suffix < old.len()
+        && suffix < new.len()
+        && a[old.end - suffix - 1] == b[new.end - suffix - 1]
+    {
+        suffix += 1;
+    }
+    old.end -= suffix;
+    new.end -= suffix;
+
+    if old.is_empty() {
+        edits.extend(new.clone().map(Edit::Insert));
+    } else if new.is_empty() {
+        edits.extend(old.clone().map(Edit::Delete));
+    } else {
+        let (x, y) = middle_snake(&a[old.clone()], &b[new.clone()]);
+        diff_range(
+            edits,
+            a,
+            old.start..old.start + x,
+            b,
+            new.start..new.start + y,
+        );
+        diff_range(edits, a, old.start + x..old.end, b, new.start + y..new.end);
+    }
+    edits.extend((0..suffix).map(|i| Edit::Equal(old.end + i, new.end + i)));
+}
+
+/// The start of the middle snake of the shortest edit script turning `a` into `b`, which are
+/// both non-empty and differ in their first and last lines. The paths before and after it need
+/// about half the edits each.
+fn middle_snake(a: &[&str], b: &[&str]) -> (usize, usize) {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    // furthest x on each diagonal k, from the start and from the end of the texts
+    let mut forward = vec![0isize; 2 * offset as usize + 1];
+    let mut backward = vec![0isize; 2 * offset as usize + 1];
+    let idx = |k: isize| (k + offset) as usize;
+
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[idx(k - 1)] < forward[idx(k + 1)]) {
+                forward[idx(k + 1)]
+            } else {
+                forward[idx(k - 1)] + 1
+            };
+            let (x0, y0) = (x, x - k);
+            let mut y = y0;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[idx(k)] = x;
+            // the backward paths of the previous round are on the diagonals delta - k
+            if odd && (delta - k).abs() < d && x + backward[idx(delta - k)] >= n {
+                return (x0 as usize, y0 as usize);
+            }
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[idx(k - 1)] < backward[idx(k + 1)]) {
+                backward[idx(k + 1)]
+            } else {
+                backward[idx(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[idx(k)] = x;
+            if !odd && (delta - k).abs() <= d && x + forward[idx(delta - k)] >= n {
+                return ((n - x) as usize, (m - y) as usize);
+            }
+        }
+    }
+    unreachable!("the edit script of two texts is at most as long as both")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unified_diff_should_work() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nb\nC\nd\ne\nf\ng\nh\ni\nj\nk\n";
+        let expected = "--- a/x.rs
++++ b/x.rs
+@@ -1,6 +1,6 @@
+ a
+ b
+-c
++C
+ d
+ e
+ f
+@@ -8,3 +8,4 @@
+ h
+ i
+ j
++k
+";
+        assert_eq!(unified_diff("a/x.rs", "b/x.rs", old, new), expected);
+        assert_eq!(
+            unified_diff("/dev/null", "x.rs", "", "a\n"),
+            "--- /dev/null\n+++ x.rs\n@@ -0,0 +1 @@\n+a\n"
+        );
+    }
+
+    /// Length of the longest common subsequence, to check that the edit scripts are minimal.
+    fn lcs(a: &[&str], b: &[&str]) -> usize {
+        let mut row = vec![0; b.len() + 1];
+        for x in a {
+            let mut prev = 0;
+            for (j, y) in b.iter().enumerate() {
+                let cur = row[j + 1];
+                row[j + 1] = if x == y {
+                    prev + 1
+                } else {
+                    row[j + 1].max(row[j])
+                };
+                prev = cur;
+            }
+        }
+        row[b.len()]
+    }
+
+    #[test]
+    fn edit_script_should_be_shortest() {
+        // small texts over a small alphabet, from a fixed linear congruential generator
+        let mut seed = 42u64;
+        let mut text = |len: u64| -> Vec<&str> {
+            let mut lines = Vec::new();
+            for _ in 0..len {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                lines.push(["a", "b", "c", "d"][(seed >> 33) as usize % 4]);
+            }
+            lines
+        };
+        for i in 0..500 {
+            let a = text(i % 13);
+            let b = text(i % 7 + i % 5);
+            let edits = edit_script(&a, &b);
+            let (mut x, mut y) = (0, 0);
+            for edit in &edits {
+                match *edit {
+                    Edit::Equal(i, j) => {
+                        assert_eq!((i, j), (x, y));
+                        assert_eq!(a[i], b[j]);
+                        x += 1;
+                        y += 1;
+                    }
+                    Edit::Delete(i) => {
+                        assert_eq!(i, x);
+                        x += 1;
+                    }
+                    Edit::Insert(j) => {
+                        assert_eq!(j, y);
+                        y += 1;
+                    }
+                }
+            }
+            assert_eq!((x, y), (a.len(), b.len()));
+            let equal = edits
+                .iter()
+                .filter(|e| matches!(e, Edit::Equal(..)))
+                .count();
+            assert_eq!(equal, lcs(&a, &b), "{:?} -> {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn edit_script_of_large_files_should_work() {
+        let new: Vec<_> = (0..20_000)
+            .map(|i| if i % 2 == 0 { "a" } else { "b" })
+            .collect();
+        assert_eq!(edit_script(&[], &new).len(), new.len());
+        let old: Vec<_> = new[..2_000].iter().map(|_| "c").collect();
+        let new = &new[..2_000];
+        assert_eq!(edit_script(&old, new).len(), old.len() + new.len());
+    }
+
+    #[test]
+    fn diff_files_should_report_stale_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hello.rs");
+        fs::write(&path, "pub struct Hello;\n").unwrap();
+
+        let files = [(path.clone(), b"pub struct Hello;\n".to_vec())];
+        assert_eq!(diff_files(&files).unwrap(), "");
+
+        let files = [(path, b"pub struct World;\n".to_vec())];
+        let diff = diff_files(&files).unwrap();
+        assert!(diff.contains("-pub struct Hello;\n+pub struct World;\n"));
+    }
+
+    #[test]
+    fn extra_files_should_be_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let hello = dir.path().join("hello.rs");
+        let world = dir.path().join("world.rs");
+        fs::write(&hello, "pub struct Hello;\n").unwrap();
+        fs::write(&world, "pub struct World;\n").unwrap();
+        fs::write(dir.path().join("notes.md"), "notes\n").unwrap();
+
+        let files = [(hello, b"pub struct Hello;\n".to_vec())];
+        let extra = extra_files(dir.path(), Some("rs"), &files).unwrap();
+        assert_eq!(extra, std::slice::from_ref(&world));
+        assert_eq!(extra_files(dir.path(), None, &files).unwrap().len(), 2);
+        assert!(extra_files(&dir.path().join("none"), None, &files)
+            .unwrap()
+            .is_empty());
+
+        let diff = diff_removed(&extra).unwrap();
+        let expected = format!(
+            "--- a/{}\n+++ /dev/null\n@@ -1 +0,0 @@\n-pub struct World;\n",
+            world.display()
+        );
+        assert_eq!(diff, expected);
+    }
+}
@@ -0,0 +1,151 @@
+//! Comments of the protobuf declarations, from the source info of the descriptors, for the
+//! descriptions of the JSON Schemas and OpenAPI documents.
+
+use std::collections::HashMap;
+
+use prost_types::{
+    source_code_info::Location, DescriptorProto, EnumDescriptorProto, FileDescriptorProto,
+};
+
+use crate::path::package_prefix;
+
+// field numbers of the descriptors, which make up the paths of the locations
+const FILE_MESSAGE: i32 = 4;
+const FILE_ENUM: i32 = 5;
+const FILE_SERVICE: i32 = 6;
+const MESSAGE_FIELD: i32 = 2;
+const MESSAGE_NESTED: i32 = 3;
+const MESSAGE_ENUM: i32 = 4;
+const MESSAGE_ONEOF: i32 = 8;
+const ENUM_VALUE: i32 = 2;
+const SERVICE_METHOD: i32 = 2;
+
+struct Walker<'a> {
+    locations: HashMap<&'a [i32], &'a Location>,
+    comments: HashMap<String, String>,
+}
+
+/// The comments of the messages, fields, oneofs, enums, enum values, services and methods in
+/// `files` by fully-qualified name, e.g. `.helloworld.Hello.name`. Fields of a oneof are under
+/// their message. Leading comments are preferred over trailing ones.
+pub(crate) fn comments(files: &[FileDescriptorProto]) -> HashMap<String, String> {
+    let mut walker = Walker {
+        locations: HashMap::new(),
+        comments: HashMap::new(),
+    };
+    for file in files {
+        walker.locations.clear();
+        if let Some(info) = &file.source_code_info {
+            for location in &info.location {
+                walker.locations.insert(&location.path, location);
+            }
+        }
+        let package = package_prefix(file);
+        for (i, msg) in file.message_type.iter().enumerate() {
+            walker.message(&package, msg, &[FILE_MESSAGE, i as i32]);
+        }
+        for (i, e) in file.enum_type.iter().enumerate() {
+            walker.enumeration(&package, e, &[FILE_ENUM, i as i32]);
+        }
+        for (i, service) in file.service.iter().enumerate() {
+            let fq_name = format!("{}.{}", package, service.name());
+            let path = [FILE_SERVICE, i as i32];
+            walker.add(&fq_name, &path);
+            for (j, method) in service.method.iter().enumerate() {
+                let method_name = format!("{}.{}", fq_name, method.name());
+                walker.add(
+                    &method_name,
+                    &[&path[..], &[SERVICE_METHOD, j as i32]].concat(),
+                );
+            }
+        }
+    }
+    walker.comments
+}
+
+impl Walker<'_> {
+    fn message(&mut self, parent: &str, msg: &DescriptorProto, path: &[i32]) {
+        let fq_name = format!("{}.{}", parent, msg.name());
+        self.add(&fq_name, path);
+        for (i, field) in msg.field.iter().enumerate() {
+            let name = format!("{}.{}", fq_name, field.name());
+            self.add(&name, &[path, &[MESSAGE_FIELD, i as i32]].concat());
+        }
+        for (i, oneof) in msg.oneof_decl.iter().enumerate() {
+            let name = format!("{}.{}", fq_name, oneof.name());
+            self.add(&name, &[path, &[MESSAGE_ONEOF, i as i32]].concat());
+        }
+        for (i, nested) in msg.nested_type.iter().enumerate() {
+            self.message(
+                &fq_name,
+                nested,
+                &[path, &[MESSAGE_NESTED, i as i32]].concat(),
+            );
+        }
+        for (i, e) in msg.enum_type.iter().enumerate() {
+            self.enumeration(&fq_name, e, &[path, &[MESSAGE_ENUM, i as i32]].concat());
+        }
+    }
+
+    fn enumeration(&mut self, parent: &str, e: &EnumDescriptorProto, path: &[i32]) {
+        let fq_name = format!("{}.{}", parent, e.name());
+        self.add(&fq_name, path);
+        for (i, value) in e.value.iter().enumerate() {
+            let name = format!("{}.{}", fq_name, value.name());
+            self.add(&name, &[path, &[ENUM_VALUE, i as i32]].concat());
+        }
+    }
+
+    fn add(&mut self, fq_name: &str, path: &[i32]) {
+        let Some(location) = self.locations.get(path) else {
+            return;
+        };
+        let comment = [location.leading_comments(), location.trailing_comments()]
+            .into_iter()
+            .map(text)
+            .find(|c| !c.is_empty());
+        if let Some(comment) = comment {
+            self.comments.insert(fq_name.to_owned(), comment);
+        }
+    }
+}
+
+/// The text of a comment, without the space after the comment markers.
+fn text(comment: &str) -> String {
+    let lines: Vec<_> = comment
+        .lines()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end())
+        .collect();
+    lines.join("\n").trim().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Compiler;
+
+    #[test]
+    fn comments_should_be_collected() {
+        let includes = vec!["examples".to_owned()];
+        let files = vec!["examples/library.proto".to_owned()];
+        let fds = Compiler::Protoc.compile(&includes, &files, &[]).unwrap();
+        let comments = comments(&fds.file);
+        assert_eq!(
+            comments[".library.Library"],
+            "A library of books on shelves, served over HTTP/JSON."
+        );
+        assert_eq!(comments[".library.Library.GetBook"], "Gets a book.");
+        assert_eq!(comments[".library.Book"], "A book on a shelf.");
+        assert_eq!(
+            comments[".library.Book.name"],
+            "Resource name of the book, e.g. `shelves/1/books/2`."
+        );
+        assert!(!comments.contains_key(".library.Book.title"));
+    }
+
+    #[test]
+    fn comment_markers_should_be_removed() {
+        assert_eq!(text(" first\n second \n"), "first\nsecond");
+        assert_eq!(text("\n"), "");
+    }
+}
@@ -0,0 +1,171 @@
+//! Backends which parse the protobuf files into a `FileDescriptorSet`.
+
+use std::{fs, io, path::Path, process::Command};
+
+use prost::Message;
+use prost_types::FileDescriptorSet;
+use serde::{Deserialize, Serialize};
+
+/// The backend used to parse the protobuf files.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compiler {
+    /// run `protoc` from `PROTOC` or the bundled one from prost-build
+    #[default]
+    Protoc,
+    /// parse the files in pure Rust with `protox`, needs the `protox` feature
+    Protox,
+}
+
+impl Compiler {
+    /// Parse `files` with their imports and source info. `protox` fails if `protoc_args` are
+    /// given, as it can't apply them.
+    pub(crate) fn compile(
+        &self,
+        includes: &[String],
+        files: &[String],
+        protoc_args: &[String],
+    ) -> io::Result<FileDescriptorSet> {
+        let buf = self.compile_encoded(includes, files, protoc_args)?;
+        FileDescriptorSet::decode(&*buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Parse `files` like [`Compiler::compile`] into an encoded `FileDescriptorSet`, which keeps
+    /// the custom options that prost-types drops, e.g. `google.api.http`.
+    pub(crate) fn compile_encoded(
+        &self,
+        includes: &[String],
+        files: &[String],
+        protoc_args: &[String],
+    ) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Protoc => protoc(includes, files, protoc_args),
+            Self::Protox if !protoc_args.is_empty() => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "compiler `protox` doesn't support protoc_args: {}",
+                    protoc_args.join(" ")
+                ),
+            )),
+            Self::Protox => protox(includes, files),
+        }
+    }
+}
+
+fn protoc(includes: &[String], files: &[String], protoc_args: &[String]) -> io::Result<Vec<u8>> {
+    let tmp = tempfile::Builder::new()
+        .prefix("prost-build-config")
+        .tempdir()?;
+    let fds_path = tmp.path().join("prost-descriptor-set");
+
+    let protoc = prost_build::protoc_from_env();
+    let mut cmd = Command::new(&protoc);
+    cmd.arg("--include_imports")
+        .arg("--include_source_info")
+        .arg("-o")
+        .arg(&fds_path);
+    for include in includes {
+        if Path::new(include).exists() {
+            cmd.arg("-I").arg(include);
+        }
+    }
+    if let Some(protoc_include) = prost_build::protoc_include_from_env() {
+        cmd.arg("-I").arg(protoc_include);
+    }
+    cmd.args(protoc_args).args(files);
+
+    let output = cmd.output().map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("failed to invoke protoc (path: {:?}): {}", protoc, e),
+        )
+    })?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "protoc failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    fs::read(&fds_path)
+}
+
+#[cfg(feature = "protox")]
+fn protox(includes: &[String], files: &[String]) -> io::Result<Vec<u8>> {
+    // protoc skips include dirs which don't exist, and protox would fail on them
+    let includes = includes.iter().filter(|p| Path::new(p).exists());
+    let mut compiler = protox::Compiler::new(includes).map_err(protox_error)?;
+    compiler.include_imports(true).include_source_info(true);
+    compiler.open_files(files).map_err(protox_error)?;
+    Ok(compiler.encode_file_descriptor_set())
+}
+
+#[cfg(feature = "protox")]
+fn protox_error(e: protox::Error) -> io::Error {
+    use miette::Diagnostic;
+
+    // report errors as `file:line:column: message`, like protoc does
+    let location = e
+        .labels()
+        .and_then(|mut labels| labels.next())
+        .and_then(|label| {
+            let span = e.source_code()?.read_span(label.inner(), 0, 0).ok()?;
+            Some(format!("{}:{}", span.line() + 1, span.column() + 1))
+        });
+    let msg = match (e.file(), location) {
+        (Some(file), Some(location)) => format!("{}:{}: {}", file, location, e),
+        (Some(file), None) => format!("{}: {}", file, e),
+        _ => e.to_string(),
+    };
+    io::Error::other(format!("protox failed: {}", msg))
+}
+
+#[cfg(not(feature = "protox"))]
+fn protox(_includes: &[String], _files: &[String]) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "compiler `protox` needs the `protox` feature of prost-build-config",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiler_should_compile_example_protos() {
+        let includes = vec!["examples".to_owned()];
+        let files = vec!["examples/hello.proto".to_owned()];
+        let fds = Compiler::Protoc.compile(&includes, &files, &[]).unwrap();
+        assert!(fds.file.iter().any(|f| f.name() == "hello.proto"));
+
+        #[cfg(feature = "protox")]
+        {
+            let fds = Compiler::Protox.compile(&includes, &files, &[]).unwrap();
+            assert!(fds.file.iter().any(|f| f.name() == "hello.proto"));
+        }
+        #[cfg(not(feature = "protox"))]
+        assert_eq!(
+            Compiler::Protox
+                .compile(&includes, &files, &[])
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::Unsupported
+        );
+    }
+
+    #[test]
+    fn protox_should_reject_protoc_args() {
+        let includes = vec!["examples".to_owned()];
+        let files = vec!["examples/hello.proto".to_owned()];
+        let args = vec!["--experimental_allow_proto3_optional".to_owned()];
+        let err = Compiler::Protox
+            .compile(&includes, &files, &args)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            err.to_string(),
+            "compiler `protox` doesn't support protoc_args: --experimental_allow_proto3_optional"
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! The encoded `FileDescriptorSet` of `descriptor_set`, for runtime reflection, e.g. with
+//! `prost-reflect`, gRPC server reflection or a registry of `Any` types, and the
+//! `FILE_DESCRIPTOR_SET` constant which includes it in the include file.
+//!
+//! The descriptors are filtered on the wire, as decoding them with prost-types would drop the
+//! custom options such as `google.api.http`.
+
+use std::{fmt::Write, io};
+
+use prost::encoding::{decode_key, decode_varint, encode_key, encode_varint, WireType};
+
+/// `FileDescriptorSet.file`
+const SET_FILE: u32 = 1;
+/// `FileDescriptorProto.source_code_info`
+const FILE_SOURCE_CODE_INFO: u32 = 9;
+
+/// An encoded field of a message.
+struct Field<'a> {
+    tag: u32,
+    /// the whole field with its key
+    raw: &'a [u8],
+    /// the value of a length-delimited field
+    value: Option<&'a [u8]>,
+}
+
+/// `encoded_fds` without the source info, i.e. the comments and locations, of the files.
+pub(crate) fn strip_source_info(encoded_fds: &[u8]) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(encoded_fds.len());
+    for field in fields(encoded_fds)? {
+        match field.value {
+            Some(file) if field.tag == SET_FILE => {
+                let mut stripped = Vec::with_capacity(file.len());
+                for file_field in fields(file)? {
+                    if file_field.tag != FILE_SOURCE_CODE_INFO {
+                        stripped.extend_from_slice(file_field.raw);
+                    }
+                }
+                encode_key(SET_FILE, WireType::LengthDelimited, &mut buf);
+                encode_varint(stripped.len() as u64, &mut buf);
+                buf.extend_from_slice(&stripped);
+            }
+            _ => buf.extend_from_slice(field.raw),
+        }
+    }
+    Ok(buf)
+}
+
+/// The `FILE_DESCRIPTOR_SET` constant of the include file, which includes the file at `path`.
+/// With `from_out_dir` the path is relative to Cargo's `OUT_DIR`, else to the include file.
+pub(crate) fn constant(path: &str, from_out_dir: bool) -> String {
+    let include = match from_out_dir {
+        true => format!("include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{}\"))", path),
+        false => format!("include_bytes!(\"{}\")", path),
+    };
+    let mut buf = String::new();
+    buf.push_str(
+        "/// The encoded `FileDescriptorSet` of the compiled protobuf files and their imports, ",
+    );
+    buf.push_str("e.g. for\n/// `prost_reflect::DescriptorPool::decode`.\n");
+    let _ = writeln!(buf, "pub const FILE_DESCRIPTOR_SET: &[u8] = {};", include);
+    buf
+}
+
+/// Split an encoded message into its fields.
+fn fields(mut buf: &[u8]) -> io::Result<Vec<Field<'_>>> {
+    let mut fields = Vec::new();
+    while !buf.is_empty() {
+        let start = buf;
+        let (tag, wire_type) = decode_key(&mut buf).map_err(invalid)?;
+        let mut value = None;
+        let len = match wire_type {
+            WireType::Varint => {
+                decode_varint(&mut buf).map_err(invalid)?;
+                0
+            }
+            WireType::SixtyFourBit => 8,
+            WireType::ThirtyTwoBit => 4,
+            WireType::LengthDelimited => {
+                let len = decode_varint(&mut buf).map_err(invalid)? as usize;
+                value = buf.get(..len);
+                len
+            }
+            WireType::StartGroup | WireType::EndGroup => {
+                return Err(invalid("groups are not supported in descriptors"))
+            }
+        };
+        buf = buf
+            .get(len..)
+            .ok_or_else(|| invalid("the descriptors are truncated"))?;
+        let raw = &start[..start.len() - buf.len()];
+        fields.push(Field { tag, raw, value });
+    }
+    Ok(fields)
+}
+
+fn invalid(e: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Compiler;
+
+    use prost::Message;
+    use prost_reflect::{DescriptorPool, Value};
+    use prost_types::FileDescriptorSet;
+
+    #[allow(clippy::all, dead_code)]
+    mod gateway {
+        include!("../examples/gateway/mod.rs");
+    }
+
+    fn compile() -> Vec<u8> {
+        let includes = vec!["examples".to_owned()];
+        let files = vec!["examples/library.proto".to_owned()];
+        Compiler::Protoc
+            .compile_encoded(&includes, &files, &[])
+            .unwrap()
+    }
+
+    #[test]
+    fn source_info_should_be_stripped() {
+        let encoded = compile();
+        let stripped = strip_source_info(&encoded).unwrap();
+        assert!(stripped.len() < encoded.len());
+
+        let fds = FileDescriptorSet::decode(&*encoded).unwrap();
+        let mut expected = fds.clone();
+        for file in &mut expected.file {
+            file.source_code_info = None;
+        }
+        assert!(fds.file.iter().all(|f| f.source_code_info.is_some()));
+        assert_eq!(FileDescriptorSet::decode(&*stripped).unwrap(), expected);
+        assert!(strip_source_info(&encoded[..encoded.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn descriptor_set_should_be_usable_for_reflection() {
+        let pool = DescriptorPool::decode(gateway::FILE_DESCRIPTOR_SET).unwrap();
+        let book = pool.get_message_by_name("library.Book").unwrap();
+        assert!(book.get_field_by_name("title").is_some());
+        let file = pool.get_file_by_name("library.proto").unwrap();
+        assert!(file.file_descriptor_proto().source_code_info.is_none());
+
+        // custom options are kept
+        let http = pool.get_extension_by_name("google.api.http").unwrap();
+        let service = pool.get_service_by_name("library.Library").unwrap();
+        let get_book = service.methods().find(|m| m.name() == "GetBook").unwrap();
+        let options = get_book.options();
+        let rule = options.get_extension(&http);
+        let Value::Message(rule) = &*rule else {
+            panic!("HttpRule is a message");
+        };
+        assert_eq!(
+            rule.get_field_by_name("get").unwrap().as_str(),
+            Some("/v1/{name=shelves/*/books/*}")
+        );
+    }
+
+    #[test]
+    fn constant_should_include_descriptor_set() {
+        assert!(constant("fds.bin", false).contains("= include_bytes!(\"fds.bin\");"));
+        assert!(constant("fds.bin", true)
+            .contains("= include_bytes!(concat!(env!(\"OUT_DIR\"), \"/fds.bin\"));"));
+    }
+}
@@ -0,0 +1,409 @@
+//! Dry-run report of the attributes which the build options put on each message, enum, oneof,
+//! field and service, and of the options which match nothing.
+
+use std::{collections::HashSet, fmt};
+
+use serde::Serialize;
+
+use crate::path::{is_match, PathKind, ProtoPath};
+
+/// The section of the config a build option is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Section {
+    Messages,
+    Enums,
+    Oneofs,
+    Fields,
+    Services,
+    Sensitive,
+    /// attributes of the profile on the paths of a kind
+    Profile(PathKind),
+}
+
+/// A build option of the config, kept to explain where its attributes are put.
+#[derive(Debug)]
+pub(crate) struct Rule {
+    pub section: Section,
+    /// index of the option in its section
+    pub index: usize,
+    pub paths: Vec<String>,
+    pub attrs: Vec<String>,
+    /// attributes of the variants, for oneofs
+    pub variant_attrs: Vec<String>,
+}
+
+/// Which build option contributed an attribute, e.g. `fields[0]` through the path `Hello.msg`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleRef {
+    /// the option, e.g. `fields[0]`
+    pub rule: String,
+    /// the path of the option, empty if it has none
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExplainedAttr {
+    /// the attribute, e.g. `#[serde(default)]`
+    pub attr: String,
+    pub source: RuleRef,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExplainedPath {
+    pub kind: PathKind,
+    pub path: String,
+    pub attrs: Vec<ExplainedAttr>,
+}
+
+/// Report of [`crate::Builder::explain`]. Its `Display` impl prints it as a table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Explanation {
+    /// every message, enum, oneof, field and service with the attributes it receives. For
+    /// oneofs, field options apply to the field which holds the oneof
+    pub paths: Vec<ExplainedPath>,
+    /// paths of build options which match nothing, and options without paths
+    pub unmatched: Vec<RuleRef>,
+}
+
+impl Section {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Messages => "messages",
+            Self::Enums => "enums",
+            Self::Oneofs => "oneofs",
+            Self::Fields => "fields",
+            Self::Services => "services",
+            Self::Sensitive => "sensitive",
+            Self::Profile(_) => "profile",
+        }
+    }
+}
+
+impl Rule {
+    /// A rule of a build option with its resolved attributes, e.g. `serde(default)`.
+    pub fn new(section: Section, index: usize, paths: Vec<String>, attrs: Vec<String>) -> Self {
+        Self {
+            section,
+            index,
+            paths,
+            attrs: attrs.iter().map(|a| format!("#[{}]", a)).collect(),
+            variant_attrs: Vec::new(),
+        }
+    }
+
+    pub fn source(&self, path: &str) -> RuleRef {
+        let rule = match self.section {
+            Section::Sensitive | Section::Profile(_) => self.section.name().to_owned(),
+            section => format!("{}[{}]", section.name(), self.index),
+        };
+        RuleRef {
+            rule,
+            path: path.to_owned(),
+        }
+    }
+
+    /// The paths of this rule which match `path`, and the attributes the rule puts on `path`.
+    fn attrs_for(
+        &self,
+        path: &ProtoPath,
+        oneofs: &HashSet<&str>,
+    ) -> Option<(Vec<&str>, &[String])> {
+        let (fq_paths, attrs) = match (self.section, path.kind) {
+            (Section::Messages, PathKind::Message)
+            | (Section::Enums, PathKind::Enum)
+            | (Section::Oneofs, PathKind::Oneof)
+            | (Section::Fields, PathKind::Field | PathKind::Oneof)
+            | (Section::Services, PathKind::Service) => (vec![path.path.clone()], &self.attrs[..]),
+            (Section::Profile(kind), _) if kind == path.kind => {
+                (vec![path.path.clone()], &self.attrs[..])
+            }
+            // the variants of the matching oneofs
+            (Section::Oneofs, PathKind::Field) => match path.path.rsplit_once('.') {
+                Some((parent, _)) if oneofs.contains(parent) => {
+                    (vec![parent.to_owned()], &self.variant_attrs[..])
+                }
+                _ => return None,
+            },
+            // sensitive fields of a oneof can also be selected without the oneof
+            (Section::Sensitive, PathKind::Field) => {
+                let mut paths = vec![path.path.clone()];
+                if let Some((parent, name)) = path.path.rsplit_once('.') {
+                    if let Some((msg, _)) =
+                        parent.rsplit_once('.').filter(|_| oneofs.contains(parent))
+                    {
+                        paths.push(format!("{}.{}", msg, name));
+                    }
+                }
+                (paths, &self.attrs[..])
+            }
+            _ => return None,
+        };
+        let matchers: Vec<_> = self
+            .paths
+            .iter()
+            .filter(|p| fq_paths.iter().any(|fq| is_match(p, fq)))
+            .map(|p| p.as_str())
+            .collect();
+        if matchers.is_empty() {
+            return None;
+        }
+        Some((matchers, attrs))
+    }
+
+    /// Whether prost applies the attributes once per matching path, as it does for the
+    /// attributes of messages and fields. The others are applied once per message, enum, oneof,
+    /// field or service.
+    fn applies_per_path(&self) -> bool {
+        matches!(self.section, Section::Messages | Section::Fields)
+    }
+}
+
+/// Explain which attributes `rules` put on `paths`.
+pub(crate) fn explain(paths: Vec<ProtoPath>, rules: &[&Rule]) -> Explanation {
+    let oneofs: HashSet<&str> = paths
+        .iter()
+        .filter(|p| p.kind == PathKind::Oneof)
+        .map(|p| p.path.as_str())
+        .collect();
+
+    let mut used = HashSet::new();
+    let explained = paths
+        .iter()
+        .map(|path| {
+            let mut attrs = Vec::new();
+            // a service uses the first option which matches it
+            let mut has_service = false;
+            for rule in rules {
+                if rule.section == Section::Services && has_service {
+                    continue;
+                }
+                let Some((matchers, rule_attrs)) = rule.attrs_for(path, &oneofs) else {
+                    continue;
+                };
+                has_service |= rule.section == Section::Services;
+                for matcher in &matchers {
+                    used.insert((rule.section.name(), rule.index, matcher.to_string()));
+                }
+                let applied = match rule.applies_per_path() {
+                    true => &matchers[..],
+                    false => &matchers[..1],
+                };
+                for matcher in applied {
+                    attrs.extend(rule_attrs.iter().map(|attr| ExplainedAttr {
+                        attr: attr.clone(),
+                        source: rule.source(matcher),
+                    }));
+                }
+            }
+            ExplainedPath {
+                kind: path.kind,
+                path: path.path.clone(),
+                attrs,
+            }
+        })
+        .collect();
+
+    let mut unmatched = Vec::new();
+    for rule in rules {
+        if rule.paths.is_empty() {
+            unmatched.push(rule.source(""));
+        }
+        for p in &rule.paths {
+            if !used.contains(&(rule.section.name(), rule.index, p.clone())) {
+                unmatched.push(rule.source(p));
+            }
+        }
+    }
+    Explanation {
+        paths: explained,
+        unmatched,
+    }
+}
+
+impl fmt::Display for RuleRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.as_str() {
+            "" => write!(f, "{} (no paths)", self.rule),
+            path => write!(f, "{} ({})", self.rule, path),
+        }
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<_> = self
+            .paths
+            .iter()
+            .flat_map(|p| {
+                p.attrs.iter().enumerate().map(move |(i, attr)| {
+                    // the path is only printed on its first row
+                    let (kind, path) = match i {
+                        0 => (p.kind.to_string(), p.path.as_str()),
+                        _ => (String::new(), ""),
+                    };
+                    (kind, path, attr.attr.as_str(), attr.source.to_string())
+                })
+            })
+            .collect();
+        let path_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0).max(4);
+        let attr_width = rows.iter().map(|r| r.2.len()).max().unwrap_or(0).max(9);
+
+        writeln!(
+            f,
+            "{:<8} {:<path_width$} {:<attr_width$} RULE",
+            "KIND", "PATH", "ATTRIBUTE"
+        )?;
+        for (kind, path, attr, source) in rows {
+            writeln!(
+                f,
+                "{:<8} {:<path_width$} {:<attr_width$} {}",
+                kind, path, attr, source
+            )?;
+        }
+        if !self.unmatched.is_empty() {
+            writeln!(f, "\nRules that matched nothing:")?;
+            for rule in &self.unmatched {
+                writeln!(f, "  {}", rule)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(kind: PathKind, path: &str) -> ProtoPath {
+        ProtoPath {
+            kind,
+            path: path.to_owned(),
+        }
+    }
+
+    fn rule(section: Section, index: usize, paths: &[&str], attrs: &[&str]) -> Rule {
+        Rule {
+            section,
+            index,
+            paths: paths.iter().map(|s| s.to_string()).collect(),
+            attrs: attrs.iter().map(|s| s.to_string()).collect(),
+            variant_attrs: vec![],
+        }
+    }
+
+    #[test]
+    fn overlapping_paths_should_apply_attrs_per_path() {
+        let paths = vec![
+            path(PathKind::Message, ".hello.Hello"),
+            path(PathKind::Field, ".hello.Hello.msg"),
+            path(PathKind::Service, ".hello.Greeter"),
+        ];
+        let rules = [
+            rule(
+                Section::Messages,
+                0,
+                &[".hello", "Hello"],
+                &["#[derive(Eq)]"],
+            ),
+            rule(
+                Section::Sensitive,
+                0,
+                &["msg", "Hello.msg"],
+                &["#[serde(skip)]"],
+            ),
+            rule(
+                Section::Services,
+                0,
+                &["Greeter"],
+                &["server: #[derive(Debug)]"],
+            ),
+            rule(
+                Section::Services,
+                1,
+                &[".hello"],
+                &["client: #[derive(Clone)]"],
+            ),
+        ];
+        let explanation = explain(paths, &rules.iter().collect::<Vec<_>>());
+
+        let sources: Vec<Vec<_>> = explanation
+            .paths
+            .iter()
+            .map(|p| p.attrs.iter().map(|a| a.source.to_string()).collect())
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                vec!["messages[0] (.hello)", "messages[0] (Hello)"],
+                vec!["sensitive (msg)"],
+                vec!["services[0] (Greeter)"],
+            ]
+        );
+        let unmatched: Vec<_> = explanation
+            .unmatched
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(unmatched, ["services[1] (.hello)"]);
+    }
+
+    #[test]
+    fn explain_should_report_attrs_and_unmatched_rules() {
+        let paths = vec![
+            path(PathKind::Message, ".hello.Hello"),
+            path(PathKind::Field, ".hello.Hello.msg"),
+            path(PathKind::Field, ".hello.Hello.body.text"),
+            path(PathKind::Oneof, ".hello.Hello.body"),
+            path(PathKind::Message, ".hello.World"),
+            path(PathKind::Field, ".hello.World.msg"),
+        ];
+        let mut oneof = rule(Section::Oneofs, 0, &["Hello.body"], &["#[serde(untagged)]"]);
+        oneof.variant_attrs = vec!["#[serde(skip)]".to_owned()];
+        let rules = [
+            rule(Section::Messages, 0, &[".hello"], &["#[derive(Eq)]"]),
+            rule(
+                Section::Fields,
+                0,
+                &["msg", "missing"],
+                &["#[validate(email)]"],
+            ),
+            rule(Section::Fields, 1, &[], &["#[serde(default)]"]),
+            oneof,
+            rule(Section::Sensitive, 0, &["Hello.text"], &["#[serde(skip)]"]),
+        ];
+        let explanation = explain(paths, &rules.iter().collect::<Vec<_>>());
+
+        let attrs: Vec<_> = explanation
+            .paths
+            .iter()
+            .map(|p| {
+                let attrs: Vec<_> = p.attrs.iter().map(|a| a.source.to_string()).collect();
+                format!("{} {}", p.path, attrs.join(", "))
+            })
+            .collect();
+        assert_eq!(
+            attrs,
+            [
+                ".hello.Hello messages[0] (.hello)",
+                ".hello.Hello.msg fields[0] (msg)",
+                ".hello.Hello.body.text oneofs[0] (Hello.body), sensitive (Hello.text)",
+                ".hello.Hello.body oneofs[0] (Hello.body)",
+                ".hello.World messages[0] (.hello)",
+                ".hello.World.msg fields[0] (msg)",
+            ]
+        );
+        let unmatched: Vec<_> = explanation
+            .unmatched
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(unmatched, ["fields[0] (missing)", "fields[1] (no paths)"]);
+
+        let table = explanation.to_string();
+        assert!(table.starts_with("KIND     PATH"));
+        assert!(table.contains("\nfield    .hello.Hello.msg "));
+        assert!(table.ends_with(
+            "Rules that matched nothing:\n  fields[0] (missing)\n  fields[1] (no paths)\n"
+        ));
+    }
+}
@@ -0,0 +1,101 @@
+//! Formatting of the generated code.
+
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// How the generated code is formatted.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// leave the generated code as it is
+    None,
+    /// format in process with `prettyplease`
+    #[default]
+    Prettyplease,
+    /// format with `rustfmt` from `RUSTFMT` or the PATH
+    Rustfmt,
+}
+
+impl Format {
+    /// Format the generated `code`. If it can't be formatted, a cargo warning is printed and the
+    /// code is returned unchanged.
+    pub(crate) fn format(&self, name: &str, code: String) -> String {
+        let result = match self {
+            Self::None => return code,
+            Self::Prettyplease => prettyplease(&code),
+            Self::Rustfmt => rustfmt(&code),
+        };
+        match result {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                println!("cargo:warning=Failed to format {}. Err: {}", name, e);
+                code
+            }
+        }
+    }
+}
+
+fn prettyplease(code: &str) -> io::Result<String> {
+    let file = syn::parse_file(code).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(prettyplease::unparse(&file))
+}
+
+/// Run rustfmt on `code` through stdin, so that only the generated code is formatted.
+fn rustfmt(code: &str) -> io::Result<String> {
+    let rustfmt = std::env::var_os("RUSTFMT").unwrap_or_else(|| "rustfmt".into());
+    let mut child = Command::new(&rustfmt)
+        .args(["--edition", "2021", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("failed to invoke rustfmt (path: {:?}): {}", rustfmt, e),
+            )
+        })?;
+
+    // write from another thread, rustfmt may fill up stdout before it reads all of stdin
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = code.to_owned();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output()?;
+    writer
+        .join()
+        .map_err(|_| io::Error::other("failed to write to rustfmt"))??;
+
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "rustfmt failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = "pub struct Hello { pub msg: String }";
+
+    #[test]
+    fn format_should_work() {
+        assert_eq!(Format::None.format("hello.rs", CODE.to_owned()), CODE);
+        assert_eq!(
+            Format::Prettyplease.format("hello.rs", CODE.to_owned()),
+            "pub struct Hello {\n    pub msg: String,\n}\n"
+        );
+    }
+
+    #[test]
+    fn invalid_code_should_be_left_unchanged() {
+        let code = "pub struct {".to_owned();
+        assert_eq!(Format::Prettyplease.format("hello.rs", code.clone()), code);
+    }
+}
@@ -0,0 +1,1269 @@
+//! HTTP/JSON gateway: an axum router for the methods with `google.api.http` options, which
+//! transcodes the requests and responses with serde_json and calls the async service trait.
+//!
+//! prost-types drops custom options, so the HTTP rules are decoded from the encoded descriptors.
+
+use std::{collections::HashMap, fmt::Write, io};
+
+use prost::Message;
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+};
+
+use crate::{
+    ident::{resolve_ident, to_snake, to_upper_camel},
+    path::{self, package_prefix, real_oneof},
+    service::ServiceFlag,
+};
+
+/// The descriptors down to the method options, with only the fields needed for the HTTP rules.
+mod raw {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FileDescriptorSet {
+        #[prost(message, repeated, tag = "1")]
+        pub file: Vec<FileDescriptorProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FileDescriptorProto {
+        #[prost(string, optional, tag = "2")]
+        pub package: Option<String>,
+        #[prost(message, repeated, tag = "6")]
+        pub service: Vec<ServiceDescriptorProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ServiceDescriptorProto {
+        #[prost(string, optional, tag = "1")]
+        pub name: Option<String>,
+        #[prost(message, repeated, tag = "2")]
+        pub method: Vec<MethodDescriptorProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct MethodDescriptorProto {
+        #[prost(string, optional, tag = "1")]
+        pub name: Option<String>,
+        #[prost(message, optional, tag = "4")]
+        pub options: Option<MethodOptions>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct MethodOptions {
+        /// the `google.api.http` extension
+        #[prost(message, optional, tag = "72295728")]
+        pub http: Option<HttpRule>,
+    }
+
+    /// `google.api.HttpRule`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct HttpRule {
+        #[prost(oneof = "Pattern", tags = "2, 3, 4, 5, 6, 8")]
+        pub pattern: Option<Pattern>,
+        #[prost(string, tag = "7")]
+        pub body: String,
+        #[prost(string, tag = "12")]
+        pub response_body: String,
+        #[prost(message, repeated, tag = "11")]
+        pub additional_bindings: Vec<HttpRule>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Pattern {
+        #[prost(string, tag = "2")]
+        Get(String),
+        #[prost(string, tag = "3")]
+        Put(String),
+        #[prost(string, tag = "4")]
+        Post(String),
+        #[prost(string, tag = "5")]
+        Delete(String),
+        #[prost(string, tag = "6")]
+        Patch(String),
+        #[prost(message, tag = "8")]
+        Custom(CustomHttpPattern),
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CustomHttpPattern {
+        #[prost(string, tag = "1")]
+        pub kind: String,
+        #[prost(string, tag = "2")]
+        pub path: String,
+    }
+}
+
+pub(crate) use raw::HttpRule;
+use raw::Pattern;
+
+/// The HTTP rules of the methods, and the messages they bind fields of.
+pub(crate) struct Gateway<'a> {
+    /// services whose option has `http` set
+    services: ServiceFlag,
+    /// HTTP rules by fully-qualified method name, e.g. `.greeter.Greeter.SayHello`
+    rules: &'a HashMap<String, HttpRule>,
+    /// all messages by fully-qualified name
+    messages: HashMap<String, &'a DescriptorProto>,
+    /// whether the `google.protobuf` types are generated instead of using prost-types
+    compile_well_known_types: bool,
+}
+
+/// An HTTP binding of a method, resolved against the request message.
+struct Binding {
+    /// `get`, `post`, ...: the name of axum's method router function
+    method: &'static str,
+    /// the path in axum's syntax, e.g. `/v1/shelves/:p0`
+    route: String,
+    /// the custom verb of the last segment
+    verb: Option<Verb>,
+    /// assignments of the path variables, e.g. `request.name = ...;`
+    path_vars: Vec<String>,
+    /// whether the body is the whole request, for `body: "*"`
+    whole_body: bool,
+    /// assignment of the body to a field of the request, if it has one
+    body: Option<String>,
+    /// assignments of the query parameters
+    query: Vec<String>,
+    /// the field of the response to return, empty for the whole response
+    response_body: Option<String>,
+}
+
+/// Decode the HTTP rules of the methods from the encoded `FileDescriptorSet`, by
+/// fully-qualified method name.
+pub(crate) fn http_rules(encoded_fds: &[u8]) -> io::Result<HashMap<String, HttpRule>> {
+    let fds = raw::FileDescriptorSet::decode(encoded_fds)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut rules = HashMap::new();
+    for file in fds.file {
+        // the same prefix as `package_prefix`, which needs a `prost_types` file
+        let package = match file.package.as_deref() {
+            None | Some("") => String::new(),
+            Some(package) => format!(".{}", package),
+        };
+        for service in file.service {
+            let fq_service = format!("{}.{}", package, service.name.unwrap_or_default());
+            for method in service.method {
+                if let Some(rule) = method.options.and_then(|o| o.http) {
+                    let fq_name = format!("{}.{}", fq_service, method.name.unwrap_or_default());
+                    rules.insert(fq_name, rule);
+                }
+            }
+        }
+    }
+    Ok(rules)
+}
+
+/// The HTTP method of a rule in lowercase, e.g. `get`, and its path template.
+pub(crate) fn pattern(rule: &HttpRule) -> Result<(&'static str, &str), String> {
+    match &rule.pattern {
+        Some(Pattern::Get(path)) => Ok(("get", path)),
+        Some(Pattern::Put(path)) => Ok(("put", path)),
+        Some(Pattern::Post(path)) => Ok(("post", path)),
+        Some(Pattern::Delete(path)) => Ok(("delete", path)),
+        Some(Pattern::Patch(path)) => Ok(("patch", path)),
+        Some(Pattern::Custom(custom)) => match custom.kind.as_str() {
+            "HEAD" => Ok(("head", &custom.path)),
+            "OPTIONS" => Ok(("options", &custom.path)),
+            "TRACE" => Ok(("trace", &custom.path)),
+            kind => Err(format!("unsupported method {}", kind)),
+        },
+        None => Err("no pattern".to_owned()),
+    }
+}
+
+impl<'a> Gateway<'a> {
+    pub fn new(
+        services: ServiceFlag,
+        rules: &'a HashMap<String, HttpRule>,
+        files: &'a [FileDescriptorProto],
+        compile_well_known_types: bool,
+    ) -> Self {
+        Self {
+            services,
+            rules,
+            messages: path::messages(files),
+            compile_well_known_types,
+        }
+    }
+
+    /// The HTTP rule of the method `fq_method`, e.g. `.greeter.Greeter.SayHello`.
+    pub fn rule(&self, fq_method: &str) -> Option<&'a HttpRule> {
+        self.rules.get(fq_method)
+    }
+
+    /// The `<service>_http` modules of the services of `file` which have `http` set.
+    pub fn extra_code(&self, file: &FileDescriptorProto) -> io::Result<String> {
+        let mut buf = String::new();
+        let package = package_prefix(file);
+        for service in &file.service {
+            let fq_service = format!("{}.{}", package, service.name());
+            if !self.services.is_set(&fq_service) {
+                continue;
+            }
+            self.append_service(&mut buf, &fq_service, file, service.name())?;
+        }
+        Ok(buf)
+    }
+
+    fn append_service(
+        &self,
+        buf: &mut String,
+        fq_service: &str,
+        file: &FileDescriptorProto,
+        name: &str,
+    ) -> io::Result<()> {
+        let service = file
+            .service
+            .iter()
+            .find(|s| s.name() == name)
+            .expect("service of the file");
+        let trait_name = to_upper_camel(name);
+        let module = to_snake(name);
+
+        // handlers and routes of every binding of the unary methods with HTTP rules
+        let mut handlers = String::new();
+        let mut routes: Vec<(String, Vec<RouteHandler>)> = Vec::new();
+        for method in &service.method {
+            let fq_method = format!("{}.{}", fq_service, method.name());
+            let Some(rule) = self.rules.get(&fq_method) else {
+                continue;
+            };
+            if method.client_streaming() || method.server_streaming() {
+                println!(
+                    "cargo:warning=Skipped the HTTP rule of {}, streaming methods are not supported",
+                    fq_method
+                );
+                continue;
+            }
+            let request = self.message(method.input_type())?;
+            let response = self.message(method.output_type())?;
+            let bindings = std::iter::once(rule).chain(&rule.additional_bindings);
+            for (index, rule) in bindings.enumerate() {
+                let rust_type = |fq_name: &str| self.rust_type(file, &module, fq_name);
+                let binding = self
+                    .binding(rule, request, response, &rust_type)
+                    .map_err(|e| invalid(format!("HTTP rule of {}: {}", fq_method, e)))?;
+                let handler = format!("{}_{}", to_snake(method.name()), index);
+                append_handler(
+                    &mut handlers,
+                    &handler,
+                    &trait_name,
+                    &to_snake(method.name()),
+                    &self.rust_type(file, &module, method.input_type()),
+                    &binding,
+                );
+                let route = RouteHandler {
+                    method: binding.method,
+                    handler,
+                    verb: binding.verb,
+                };
+                match routes.iter_mut().find(|(r, _)| *r == binding.route) {
+                    Some((_, methods)) => methods.push(route),
+                    None => routes.push((binding.route, vec![route])),
+                }
+            }
+        }
+        if routes.is_empty() {
+            return Ok(());
+        }
+
+        let _ = writeln!(
+            buf,
+            "/// HTTP/JSON gateway of the `{}` service, from its `google.api.http` options.",
+            fq_service.trim_start_matches('.')
+        );
+        let _ = writeln!(buf, "pub mod {}_http {{", module);
+        let _ = writeln!(buf, "    use super::{}_service::{};\n", module, trait_name);
+        let _ = writeln!(
+            buf,
+            "    /// Router with the routes of the methods of `{}` which have HTTP rules.",
+            trait_name
+        );
+        let _ = writeln!(
+            buf,
+            "    pub fn router<T: {}>(service: ::std::sync::Arc<T>) -> ::axum::Router {{",
+            trait_name
+        );
+        buf.push_str("        ::axum::Router::new()\n");
+        for (index, (route, route_handlers)) in routes.iter().enumerate() {
+            let mut methods = Vec::new();
+            for handler in route_handlers {
+                if !methods.contains(&handler.method) {
+                    methods.push(handler.method);
+                }
+            }
+            let mut method_routers = Vec::new();
+            for method in methods {
+                let same_method: Vec<_> = route_handlers
+                    .iter()
+                    .filter(|h| h.method == method)
+                    .collect();
+                let handler = match &same_method[..] {
+                    [h] if h.verb.is_none() => h.handler.clone(),
+                    _ if same_method.iter().filter(|h| h.verb.is_none()).count() > 1 => {
+                        return Err(invalid(format!(
+                            "HTTP rules of {} have the same route {} {}",
+                            fq_service, method, route
+                        )));
+                    }
+                    _ => {
+                        let dispatcher = format!("route_{}_{}", index, method);
+                        append_verb_dispatcher(
+                            &mut handlers,
+                            &dispatcher,
+                            &trait_name,
+                            &same_method,
+                        );
+                        dispatcher
+                    }
+                };
+                method_routers.push(format!("{}({}::<T>)", method, handler));
+            }
+            let _ = writeln!(
+                buf,
+                "            .route(\"{}\", ::axum::routing::{})",
+                route,
+                method_routers.join(".")
+            );
+        }
+        buf.push_str("            .with_state(service)\n    }\n");
+        buf.push_str(&handlers);
+        buf.push_str(HELPERS);
+        buf.push_str("}\n");
+        Ok(())
+    }
+
+    /// The Rust path of the message `fq_name` from the `<service>_http` module.
+    fn rust_type(&self, file: &FileDescriptorProto, module: &str, fq_name: &str) -> String {
+        match fq_name.strip_prefix(".google.protobuf.") {
+            Some(name) if !self.compile_well_known_types => format!("::prost_types::{}", name),
+            _ => {
+                let http_module = format!("{}_http", module);
+                let scope: Vec<_> = file
+                    .package()
+                    .split('.')
+                    .filter(|s| !s.is_empty())
+                    .chain([http_module.as_str()])
+                    .collect();
+                resolve_ident(&scope, fq_name)
+            }
+        }
+    }
+
+    pub fn message(&self, fq_name: &str) -> io::Result<&'a DescriptorProto> {
+        self.messages
+            .get(fq_name)
+            .copied()
+            .ok_or_else(|| invalid(format!("message {} not found", fq_name)))
+    }
+
+    /// The binding of `rule`. `rust_type` is the Rust path of a message or enum from the handlers.
+    fn binding(
+        &self,
+        rule: &HttpRule,
+        request: &'a DescriptorProto,
+        response: &'a DescriptorProto,
+        rust_type: &dyn Fn(&str) -> String,
+    ) -> Result<Binding, String> {
+        let (method, template) = pattern(rule)?;
+        let template = parse_template(template)?;
+
+        let mut bound = Vec::new();
+        let mut path_vars = Vec::new();
+        for (field_path, parts) in &template.vars {
+            let value = match &parts[..] {
+                [Part::Param(param)] => format!("param(&path, \"{}\")?", param),
+                // a multi-segment variable is joined from its segments
+                parts => {
+                    let parts: Vec<_> = parts
+                        .iter()
+                        .map(|part| match part {
+                            Part::Literal(literal) => format!("\"{}\"", literal),
+                            Part::Param(param) => format!("param(&path, \"{}\")?", param),
+                        })
+                        .collect();
+                    format!("&[{}].join(\"/\")", parts.join(", "))
+                }
+            };
+            let field = self.field_path(request, field_path)?;
+            path_vars.push(field.assign(field_path, &value, rust_type)?);
+            bound.push(field_path.clone());
+        }
+
+        let body = match rule.body.as_str() {
+            "" | "*" => None,
+            field_path => {
+                let field = self.field_path(request, field_path)?;
+                bound.push(field_path.to_owned());
+                Some(format!("{} = parse_body(&body)?;", field.lvalue()))
+            }
+        };
+
+        let mut query = Vec::new();
+        if rule.body != "*" {
+            for field in self.query_fields(request, &bound) {
+                let json_path: Vec<_> = field.fields.iter().map(|f| f.json_name()).collect();
+                let mut names = vec![field.path.clone()];
+                if json_path.join(".") != field.path {
+                    names.push(json_path.join("."));
+                }
+                let names: Vec<_> = names.iter().map(|n| format!("\"{}\"", n)).collect();
+                let (var, getter, assign) = match field.is_repeated() {
+                    // every occurrence of a repeated field, e.g. `?tags=a&tags=b`
+                    true => (
+                        "values",
+                        "query_values",
+                        field.assign_all(&field.path, "values", rust_type),
+                    ),
+                    false => (
+                        "value",
+                        "query_param",
+                        field.assign(&field.path, "value", rust_type),
+                    ),
+                };
+                if let Ok(assign) = assign {
+                    query.push(format!(
+                        "if let Some({}) = {}(&query, &[{}]) {{\n                {}\n            }}",
+                        var,
+                        getter,
+                        names.join(", "),
+                        assign
+                    ));
+                }
+            }
+        }
+
+        let response_body = match rule.response_body.as_str() {
+            "" => None,
+            field_path => {
+                let field = self.field_path(response, field_path)?;
+                Some(field.access())
+            }
+        };
+
+        Ok(Binding {
+            method,
+            route: template.route,
+            verb: template.verb,
+            path_vars,
+            whole_body: rule.body == "*",
+            body,
+            query,
+            response_body,
+        })
+    }
+
+    /// Resolve a dotted field path of `msg`.
+    pub fn field_path(
+        &self,
+        mut msg: &'a DescriptorProto,
+        field_path: &str,
+    ) -> Result<FieldPath<'a>, String> {
+        let mut fields = Vec::new();
+        let names: Vec<_> = field_path.split('.').collect();
+        for (i, name) in names.iter().enumerate() {
+            let field = msg
+                .field
+                .iter()
+                .find(|f| f.name() == *name)
+                .ok_or_else(|| format!("field {} not found", field_path))?;
+            if real_oneof(msg, field.oneof_index, field.proto3_optional()).is_some() {
+                return Err(format!("oneof field {} is not supported", field_path));
+            }
+            fields.push(field);
+            if i + 1 < names.len() {
+                if field.r#type() != Type::Message || field.label() == Label::Repeated {
+                    return Err(format!("{} is not a message field", name));
+                }
+                msg = self
+                    .messages
+                    .get(field.type_name())
+                    .ok_or_else(|| format!("message {} not found", field.type_name()))?;
+            }
+        }
+        Ok(FieldPath {
+            path: field_path.to_owned(),
+            fields,
+        })
+    }
+
+    /// The scalar fields of `msg` which aren't `bound` by the path or the body, which are read
+    /// from the query parameters named by their field path. Repeated fields are read from every
+    /// occurrence of their parameter.
+    pub fn query_fields(&self, msg: &'a DescriptorProto, bound: &[String]) -> Vec<FieldPath<'a>> {
+        let mut fields = Vec::new();
+        self.collect_query_fields(msg, "", bound, &mut fields, &mut Vec::new());
+        fields
+    }
+
+    fn collect_query_fields(
+        &self,
+        msg: &'a DescriptorProto,
+        prefix: &str,
+        bound: &[String],
+        fields: &mut Vec<FieldPath<'a>>,
+        visited: &mut Vec<&'a DescriptorProto>,
+    ) {
+        if visited.iter().any(|m| std::ptr::eq(*m, msg)) {
+            return;
+        }
+        visited.push(msg);
+        for field in &msg.field {
+            let field_path = format!("{}{}", prefix, field.name());
+            let is_bound = bound
+                .iter()
+                .any(|b| *b == field_path || field_path.starts_with(&format!("{}.", b)));
+            if is_bound || real_oneof(msg, field.oneof_index, field.proto3_optional()).is_some() {
+                continue;
+            }
+            match field.r#type() {
+                // repeated messages and maps have no query form
+                Type::Message if field.label() == Label::Repeated => {}
+                Type::Message => {
+                    if let Some(nested) = self.messages.get(field.type_name()) {
+                        let prefix = format!("{}.", field_path);
+                        self.collect_query_fields(nested, &prefix, bound, fields, visited);
+                    }
+                }
+                Type::Group | Type::Bytes => {}
+                _ => fields.extend(self.field_path(visited[0], &field_path)),
+            }
+        }
+        visited.pop();
+    }
+}
+
+/// A resolved dotted field path.
+pub(crate) struct FieldPath<'a> {
+    /// the dotted path, e.g. `filter.author`
+    pub path: String,
+    /// the fields along the path
+    pub fields: Vec<&'a FieldDescriptorProto>,
+}
+
+impl FieldPath<'_> {
+    /// `request.a.get_or_insert_with(..).b`, for assigning the last field.
+    fn lvalue(&self) -> String {
+        let mut expr = "request".to_owned();
+        for (i, field) in self.fields.iter().enumerate() {
+            let _ = write!(expr, ".{}", to_snake(field.name()));
+            if i + 1 < self.fields.len() {
+                expr.push_str(".get_or_insert_with(::core::default::Default::default)");
+            }
+        }
+        expr
+    }
+
+    /// `response.a.unwrap_or_default().b`, for reading the last field of the response.
+    fn access(&self) -> String {
+        let mut expr = "response".to_owned();
+        for (i, field) in self.fields.iter().enumerate() {
+            let _ = write!(expr, ".{}", to_snake(field.name()));
+            if i + 1 < self.fields.len() {
+                expr.push_str(".unwrap_or_default()");
+            }
+        }
+        expr
+    }
+
+    fn is_repeated(&self) -> bool {
+        self.last().label() == Label::Repeated
+    }
+
+    fn last(&self) -> &FieldDescriptorProto {
+        self.fields.last().expect("field path is not empty")
+    }
+
+    /// Assign the last field from the `&str` expression `value`. Enums are parsed from their
+    /// names or numbers, with the Rust path of the enum from `rust_type`.
+    fn assign(
+        &self,
+        field_path: &str,
+        value: &str,
+        rust_type: &dyn Fn(&str) -> String,
+    ) -> Result<String, String> {
+        if self.is_repeated() {
+            return Err(format!("repeated field {} is not supported", field_path));
+        }
+        let parsed = format!("{}?", self.parse(field_path, value, rust_type)?);
+        let value = match self.last().proto3_optional() {
+            true => format!("Some({})", parsed),
+            false => parsed,
+        };
+        Ok(format!("{} = {};", self.lvalue(), value))
+    }
+
+    /// Assign the last field, which is repeated, from the `Vec<&str>` expression `values`.
+    fn assign_all(
+        &self,
+        field_path: &str,
+        values: &str,
+        rust_type: &dyn Fn(&str) -> String,
+    ) -> Result<String, String> {
+        let parsed = self.parse(field_path, "value", rust_type)?;
+        Ok(format!(
+            "{} = {}.into_iter().map(|value| {}).collect::<Result<_, _>>()?;",
+            self.lvalue(),
+            values,
+            parsed
+        ))
+    }
+
+    /// The `Result` of parsing the last field from the `&str` expression `value`.
+    fn parse(
+        &self,
+        field_path: &str,
+        value: &str,
+        rust_type: &dyn Fn(&str) -> String,
+    ) -> Result<String, String> {
+        let field = self.last();
+        match field.r#type() {
+            Type::Message | Type::Group | Type::Bytes => {
+                Err(format!("{} is not a scalar field", field_path))
+            }
+            Type::Enum => Ok(format!(
+                "parse_enum(\"{}\", {}, {}::from_str_name)",
+                field_path,
+                value,
+                rust_type(field.type_name())
+            )),
+            _ => Ok(format!("parse_param(\"{}\", {})", field_path, value)),
+        }
+    }
+}
+
+/// A path template in axum's syntax, with the path variables.
+#[derive(Debug, PartialEq)]
+struct Template {
+    route: String,
+    /// field paths of the variables, with the parts their values are joined from
+    vars: Vec<(String, Vec<Part>)>,
+    /// the custom verb of the last segment
+    verb: Option<Verb>,
+}
+
+/// A custom verb, e.g. `:publish`. axum doesn't split a segment into a parameter and a literal,
+/// so the last segment is a parameter, which the handler matches against the verb.
+#[derive(Debug, Clone, PartialEq)]
+struct Verb {
+    /// name of the axum path parameter of the last segment
+    param: String,
+    /// the literal before the verb, or `None` if it follows a variable
+    literal: Option<String>,
+    /// the verb without the colon
+    verb: String,
+}
+
+/// A handler of a route.
+struct RouteHandler {
+    method: &'static str,
+    handler: String,
+    verb: Option<Verb>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Part {
+    Literal(String),
+    /// name of an axum path parameter
+    Param(String),
+}
+
+/// Parse an HTTP rule path template, e.g. `/v1/{name=shelves/*}/books:list`.
+fn parse_template(template: &str) -> Result<Template, String> {
+    let path = template
+        .strip_prefix('/')
+        .ok_or_else(|| format!("path {} doesn't start with /", template))?;
+    // split on the slashes outside of variables
+    let mut segments = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in path.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '/' if depth == 0 => {
+                segments.push(&path[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&path[start..]);
+
+    let mut route = String::new();
+    let mut vars = Vec::new();
+    let mut verb = None;
+    let mut params = 0;
+    let mut param = |wildcard: bool, route: &mut String| {
+        let name = format!("p{}", params);
+        params += 1;
+        let _ = write!(route, "/{}{}", if wildcard { '*' } else { ':' }, name);
+        name
+    };
+    let last = segments.len() - 1;
+    for (i, segment) in segments.into_iter().enumerate() {
+        let (segment, custom_verb) = split_verb(segment);
+        if custom_verb.is_some() && i != last {
+            return Err(format!(
+                "custom verb before the last segment in {}",
+                template
+            ));
+        }
+        if custom_verb == Some("") {
+            return Err(format!("empty custom verb in {}", template));
+        }
+        let (field_path, pattern) = match segment.strip_prefix('{') {
+            Some(var) => {
+                let var = var
+                    .strip_suffix('}')
+                    .ok_or_else(|| format!("invalid variable {} in {}", segment, template))?;
+                let (field_path, pattern) = var.split_once('=').unwrap_or((var, "*"));
+                (Some(field_path), pattern)
+            }
+            None => (None, segment),
+        };
+        let patterns: Vec<_> = pattern.split('/').collect();
+        let mut parts = Vec::new();
+        for (j, pattern) in patterns.iter().enumerate() {
+            let custom_verb = custom_verb.filter(|_| j + 1 == patterns.len());
+            let part = match (*pattern, custom_verb) {
+                ("*", None) => Part::Param(param(false, &mut route)),
+                ("**", None) => Part::Param(param(true, &mut route)),
+                (literal, None) => {
+                    let _ = write!(route, "/{}", literal);
+                    Part::Literal(literal.to_owned())
+                }
+                // the verb is stripped from a variable, and compared with a literal
+                (pattern, Some(custom_verb)) => {
+                    let name = param(pattern == "**", &mut route);
+                    let literal = (!matches!(pattern, "*" | "**")).then(|| pattern.to_owned());
+                    verb = Some(Verb {
+                        param: name.clone(),
+                        literal: literal.clone(),
+                        verb: custom_verb.to_owned(),
+                    });
+                    literal.map_or(Part::Param(name), Part::Literal)
+                }
+            };
+            parts.push(part);
+        }
+        if let Some(field_path) = field_path {
+            vars.push((field_path.to_owned(), parts));
+        }
+    }
+    // axum only allows a wildcard parameter at the end of the route
+    if route
+        .find("/*")
+        .is_some_and(|start| route[start + 2..].contains('/'))
+    {
+        return Err(format!("** is not the last segment of {}", template));
+    }
+    Ok(Template { route, vars, verb })
+}
+
+/// Split the custom verb off a segment, e.g. `books:move` or `{name}:move`. A colon within a
+/// variable isn't a verb.
+fn split_verb(segment: &str) -> (&str, Option<&str>) {
+    let start = segment.rfind('}').map_or(0, |end| end + 1);
+    match segment[start..].find(':') {
+        Some(colon) => (
+            &segment[..start + colon],
+            Some(&segment[start + colon + 1..]),
+        ),
+        None => (segment, None),
+    }
+}
+
+/// Append the handler of a binding, which builds the request, calls the service and returns the
+/// response as JSON.
+fn append_handler(
+    buf: &mut String,
+    handler: &str,
+    trait_name: &str,
+    method: &str,
+    request_type: &str,
+    binding: &Binding,
+) {
+    // axum rejects the `Path` extractor on routes without parameters
+    let path = match binding.route.contains("/:") || binding.route.contains("/*") {
+        true => "\n        ::axum::extract::Path(path): ::axum::extract::Path<Params>,",
+        false => "",
+    };
+    let _ = write!(
+        buf,
+        r#"
+    #[allow(unused_variables, unused_mut)]
+    async fn {handler}<T: {trait_name}>(
+        ::axum::extract::State(service): ::axum::extract::State<::std::sync::Arc<T>>,{path}
+        ::axum::extract::Query(query): ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {{
+        let request = move || -> Result<{request_type}, String> {{
+"#
+    );
+    let init = match binding.whole_body {
+        true => "parse_body(&body)?",
+        false => "::core::default::Default::default()",
+    };
+    let _ = writeln!(
+        buf,
+        "            let mut request: {} = {};",
+        request_type, init
+    );
+    for line in binding
+        .body
+        .iter()
+        .chain(&binding.path_vars)
+        .chain(&binding.query)
+    {
+        let _ = writeln!(buf, "            {}", line);
+    }
+    let response = match &binding.response_body {
+        Some(field) => format!("&{}", field),
+        None => "&response".to_owned(),
+    };
+    let _ = write!(
+        buf,
+        r#"            Ok(request)
+        }};
+        let request = match request() {{
+            Ok(request) => request,
+            Err(message) => return error(::axum::http::StatusCode::BAD_REQUEST, message),
+        }};
+        match service.{method}(request).await {{
+            Ok(response) => json(::axum::http::StatusCode::OK, {response}),
+            Err(e) => error(::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        }}
+    }}
+"#
+    );
+}
+
+/// Append the handler of the bindings of a route and method which differ in their custom verb. It
+/// calls the handler of the verb in the last segment, or the one without a verb. A verb after a
+/// variable is stripped from its parameter, and the literal verbs are matched first.
+fn append_verb_dispatcher(
+    buf: &mut String,
+    dispatcher: &str,
+    trait_name: &str,
+    handlers: &[&RouteHandler],
+) {
+    let param = handlers
+        .iter()
+        .find_map(|h| h.verb.as_ref().map(|verb| verb.param.as_str()))
+        .unwrap_or_default();
+    let _ = write!(
+        buf,
+        r#"
+    #[allow(unused_mut)]
+    async fn {dispatcher}<T: {trait_name}>(
+        state: ::axum::extract::State<::std::sync::Arc<T>>,
+        mut path: ::axum::extract::Path<Params>,
+        query: ::axum::extract::Query<QueryParams>,
+        body: ::axum::body::Bytes,
+    ) -> ::axum::response::Response {{
+        let segment = path.get("{param}").cloned().unwrap_or_default();
+"#
+    );
+    let mut verbs: Vec<_> = handlers
+        .iter()
+        .filter_map(|h| Some((h.verb.as_ref()?, &h.handler)))
+        .collect();
+    verbs.sort_by_key(|(verb, _)| verb.literal.is_none());
+    for (verb, handler) in verbs {
+        let _ = match &verb.literal {
+            Some(literal) => write!(
+                buf,
+                r#"        if segment == "{literal}:{verb}" {{
+            return {handler}(state, path, query, body).await;
+        }}
+"#,
+                verb = verb.verb
+            ),
+            None => write!(
+                buf,
+                r#"        if let Some(value) = segment.strip_suffix(":{verb}").filter(|v| !v.is_empty()) {{
+            path.0.insert("{param}".to_owned(), value.to_owned());
+            return {handler}(state, path, query, body).await;
+        }}
+"#,
+                verb = verb.verb
+            ),
+        };
+    }
+    match handlers.iter().find(|h| h.verb.is_none()) {
+        Some(handler) => {
+            let _ = writeln!(
+                buf,
+                "        {}(state, path, query, body).await",
+                handler.handler
+            );
+        }
+        None => buf.push_str(
+            "        error(::axum::http::StatusCode::NOT_FOUND, \"not found\".to_owned())\n",
+        ),
+    }
+    buf.push_str("    }\n");
+}
+
+/// Helpers of the generated handlers.
+const HELPERS: &str = r#"
+    type Params = ::std::collections::HashMap<String, String>;
+
+    /// the query parameters in their order, as repeated fields occur several times
+    type QueryParams = Vec<(String, String)>;
+
+    #[allow(dead_code)]
+    fn param<'a>(params: &'a Params, name: &str) -> Result<&'a str, String> {
+        params
+            .get(name)
+            .map(|value| value.trim_start_matches('/'))
+            .ok_or_else(|| format!("missing path parameter {}", name))
+    }
+
+    #[allow(dead_code)]
+    fn query_param<'a>(query: &'a QueryParams, names: &[&str]) -> Option<&'a str> {
+        names
+            .iter()
+            .find_map(|name| query.iter().find(|(key, _)| key == name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[allow(dead_code)]
+    fn query_values<'a>(query: &'a QueryParams, names: &[&str]) -> Option<Vec<&'a str>> {
+        let values: Vec<_> = query
+            .iter()
+            .filter(|(key, _)| names.contains(&key.as_str()))
+            .map(|(_, value)| value.as_str())
+            .collect();
+        (!values.is_empty()).then_some(values)
+    }
+
+    #[allow(dead_code)]
+    fn parse_param<T: ::core::str::FromStr>(name: &str, value: &str) -> Result<T, String>
+    where
+        T::Err: ::core::fmt::Display,
+    {
+        value.parse().map_err(|e| format!("invalid value for {}: {}", name, e))
+    }
+
+    #[allow(dead_code)]
+    fn parse_enum<E: Into<i32>>(
+        name: &str,
+        value: &str,
+        from_str_name: fn(&str) -> Option<E>,
+    ) -> Result<i32, String> {
+        match value.parse() {
+            Ok(number) => Ok(number),
+            Err(_) => from_str_name(value)
+                .map(Into::into)
+                .ok_or_else(|| format!("invalid value for {}: {}", name, value)),
+        }
+    }
+
+    #[allow(dead_code)]
+    fn parse_body<T: ::serde::de::DeserializeOwned>(body: &[u8]) -> Result<T, String> {
+        ::serde_json::from_slice(body).map_err(|e| format!("invalid body: {}", e))
+    }
+
+    fn json<T: ::serde::Serialize>(
+        status: ::axum::http::StatusCode,
+        value: &T,
+    ) -> ::axum::response::Response {
+        use ::axum::response::IntoResponse;
+        match ::serde_json::to_vec(value) {
+            Ok(body) => (
+                status,
+                [(::axum::http::header::CONTENT_TYPE, "application/json")],
+                body,
+            )
+                .into_response(),
+            Err(e) => error(::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        }
+    }
+
+    fn error(status: ::axum::http::StatusCode, message: String) -> ::axum::response::Response {
+        use ::axum::response::IntoResponse;
+        let body: ::serde_json::Map<String, ::serde_json::Value> = [
+            ("code".to_owned(), status.as_u16().into()),
+            ("message".to_owned(), message.into()),
+        ]
+        .into_iter()
+        .collect();
+        (status, ::axum::Json(body)).into_response()
+    }
+"#;
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BuildConfig, Builder};
+
+    use std::sync::Arc;
+
+    use axum::{body::Body, http::Request, Router};
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    #[allow(clippy::all, dead_code)]
+    mod gateway {
+        include!("../examples/gateway/mod.rs");
+    }
+
+    use gateway::library::{library_http, library_service::MockLibrary, Book, ListBooksResponse};
+
+    fn router() -> Router {
+        let service = MockLibrary::default()
+            .with_get_book(|request| {
+                Ok(Book {
+                    name: request.name,
+                    title: "Dune".to_owned(),
+                    ..Default::default()
+                })
+            })
+            .with_list_books(|request| {
+                let filter = request.filter.unwrap_or_default();
+                let title = format!(
+                    "{}/{}/{:?}/{}/{:?}/{}",
+                    request.shelf,
+                    request.page_size,
+                    filter.author,
+                    filter.min_pages,
+                    filter.format(),
+                    filter.tags.join(",")
+                );
+                Ok(ListBooksResponse {
+                    books: vec![Book {
+                        title,
+                        ..Default::default()
+                    }],
+                })
+            })
+            .with_create_book(|request| {
+                let book = request.book.unwrap_or_default();
+                Ok(Book {
+                    name: format!("shelves/{}/books/1", request.shelf),
+                    ..book
+                })
+            })
+            .with_move_book(|request| {
+                Err(format!("can't move {} to {}", request.name, request.shelf).into())
+            })
+            .with_copy_book(|request| {
+                Ok(Book {
+                    name: format!("shelves/{}/books/2", request.shelf),
+                    ..Default::default()
+                })
+            })
+            .with_publish_book(|request| {
+                Ok(Book {
+                    name: request.name,
+                    title: "published".to_owned(),
+                    ..Default::default()
+                })
+            });
+        library_http::router(Arc::new(service))
+    }
+
+    async fn call(method: &str, uri: &str, body: &str) -> (u16, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body.to_owned()))
+            .unwrap();
+        let response = router().oneshot(request).await.unwrap();
+        let status = response.status().as_u16();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn generate_gateway_code() {
+        let config = BuildConfig::from_path("examples/gateway.yml").unwrap();
+        Builder::from(config).build_protos();
+    }
+
+    #[test]
+    fn path_templates_should_be_parsed() {
+        let template = parse_template("/v1/{name=shelves/*/books/**}").unwrap();
+        assert_eq!(template.route, "/v1/shelves/:p0/books/*p1");
+        assert_eq!(
+            template.vars,
+            [(
+                "name".to_owned(),
+                vec![
+                    Part::Literal("shelves".to_owned()),
+                    Part::Param("p0".to_owned()),
+                    Part::Literal("books".to_owned()),
+                    Part::Param("p1".to_owned()),
+                ]
+            )]
+        );
+
+        let template = parse_template("/v1/shelves/{shelf.id}/books:batchGet").unwrap();
+        assert_eq!(template.route, "/v1/shelves/:p0/:p1");
+        assert_eq!(
+            template.vars,
+            [("shelf.id".to_owned(), vec![Part::Param("p0".to_owned())])]
+        );
+        let verb = |param: &str, literal: Option<&str>, verb: &str| Verb {
+            param: param.to_owned(),
+            literal: literal.map(str::to_owned),
+            verb: verb.to_owned(),
+        };
+        assert_eq!(template.verb, Some(verb("p1", Some("books"), "batchGet")));
+
+        // a verb after a variable is stripped from its last parameter
+        let template = parse_template("/v1/{name=shelves/*/books/*}:publish").unwrap();
+        assert_eq!(template.route, "/v1/shelves/:p0/books/:p1");
+        assert_eq!(template.verb, Some(verb("p1", None, "publish")));
+        let template = parse_template("/v1/{name=operations/**}:cancel").unwrap();
+        assert_eq!(template.route, "/v1/operations/*p0");
+        assert_eq!(
+            template.vars,
+            [(
+                "name".to_owned(),
+                vec![
+                    Part::Literal("operations".to_owned()),
+                    Part::Param("p0".to_owned()),
+                ]
+            )]
+        );
+        assert_eq!(template.verb, Some(verb("p0", None, "cancel")));
+
+        assert!(parse_template("v1/books").is_err());
+        assert!(parse_template("/v1/{name}x").is_err());
+        assert!(parse_template("/v1/books:move/x").is_err());
+        let err = parse_template("/v1/{name=shelves/**}/books").unwrap_err();
+        assert_eq!(
+            err,
+            "** is not the last segment of /v1/{name=shelves/**}/books"
+        );
+        assert!(parse_template("/v1/**/books").is_err());
+    }
+
+    #[tokio::test]
+    async fn path_variables_should_be_bound() {
+        let (status, body) = call("GET", "/v1/shelves/s%201/books/b1", "").await;
+        assert_eq!(status, 200);
+        assert_eq!(
+            body,
+            json!({ "name": "shelves/s 1/books/b1", "title": "Dune" })
+        );
+    }
+
+    #[tokio::test]
+    async fn query_parameters_should_be_bound() {
+        let uri = "/v1/shelves/s1/books?pageSize=10&filter.author=Herbert&filter.min_pages=300";
+        let (status, body) = call("GET", uri, "").await;
+        assert_eq!(status, 200);
+        assert_eq!(
+            body,
+            json!([{ "title": "s1/10/Some(\"Herbert\")/300/Unspecified/" }])
+        );
+
+        // additional bindings get their own routes
+        let (status, body) = call("GET", "/v1/books?shelf=s2", "").await;
+        assert_eq!(status, 200);
+        assert_eq!(
+            body,
+            json!({ "books": [{ "title": "s2/0/None/0/Unspecified/" }] })
+        );
+
+        // enums are read from their names or numbers
+        for format in ["PAPERBACK", "2"] {
+            let uri = format!("/v1/books?filter.format={}", format);
+            let (status, body) = call("GET", &uri, "").await;
+            assert_eq!(status, 200);
+            assert_eq!(body["books"][0]["title"], "/0/None/0/Paperback/");
+        }
+
+        // repeated fields are read from every occurrence
+        let uri = "/v1/books?filter.tags=sf&page_size=1&filter.tags=classic";
+        let (status, body) = call("GET", uri, "").await;
+        assert_eq!(status, 200);
+        assert_eq!(
+            body["books"][0]["title"],
+            "/1/None/0/Unspecified/sf,classic"
+        );
+        let (status, _) = call("GET", "/v1/books?filter.format=EBOOK", "").await;
+        assert_eq!(status, 400);
+
+        let (status, body) = call("GET", "/v1/books?page_size=ten", "").await;
+        assert_eq!(status, 400);
+        assert_eq!(body["code"], 400);
+    }
+
+    #[tokio::test]
+    async fn body_should_be_bound() {
+        let book = r#"{ "title": "Dune", "pages": 412 }"#;
+        let (status, body) = call("POST", "/v1/shelves/s1/books", book).await;
+        assert_eq!(status, 200);
+        assert_eq!(
+            body,
+            json!({ "name": "shelves/s1/books/1", "title": "Dune", "pages": 412 })
+        );
+
+        let (status, _) = call("POST", "/v1/shelves/s1/books", "{").await;
+        assert_eq!(status, 400);
+    }
+
+    #[tokio::test]
+    async fn service_errors_should_be_returned() {
+        let request = r#"{ "name": "b1", "shelf": "s2" }"#;
+        let (status, body) = call("POST", "/v1/books:move", request).await;
+        assert_eq!(status, 500);
+        assert_eq!(
+            body,
+            json!({ "code": 500, "message": "can't move b1 to s2" })
+        );
+    }
+
+    #[tokio::test]
+    async fn custom_verbs_should_be_matched_exactly() {
+        let request = r#"{ "name": "b1", "shelf": "s2" }"#;
+        let (status, body) = call("POST", "/v1/books:copy", request).await;
+        assert_eq!(status, 200);
+        assert_eq!(body, json!({ "name": "shelves/s2/books/2" }));
+
+        for uri in ["/v1/booksX", "/v1/books:moveX", "/v1/shelves:move"] {
+            let (status, body) = call("POST", uri, request).await;
+            assert_eq!(status, 404, "{}", uri);
+            assert_eq!(body["code"], 404);
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_verbs_after_variables_should_be_matched() {
+        let (status, body) = call("POST", "/v1/shelves/s1/books/b1:publish", "").await;
+        assert_eq!(status, 200);
+        assert_eq!(
+            body,
+            json!({ "name": "shelves/s1/books/b1", "title": "published" })
+        );
+        let (status, body) = call("POST", "/v1/drafts/2024/b1:publish", "").await;
+        assert_eq!(status, 200);
+        assert_eq!(body["name"], "drafts/2024/b1");
+
+        // the route of the verb is the one of GetBook, which still works
+        let (status, body) = call("GET", "/v1/shelves/s1/books/b1", "").await;
+        assert_eq!(status, 200);
+        assert_eq!(body["title"], "Dune");
+
+        for uri in [
+            "/v1/shelves/s1/books/b1",
+            "/v1/shelves/s1/books/b1:publishX",
+            "/v1/shelves/s1/books/:publish",
+            "/v1/drafts/b1",
+        ] {
+            let (status, body) = call("POST", uri, "").await;
+            assert_eq!(status, 404, "{}", uri);
+            assert_eq!(body["code"], 404);
+        }
+    }
+}
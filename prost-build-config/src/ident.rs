@@ -0,0 +1,71 @@
+//! Rust identifiers for protobuf names, the same as the ones prost generates.
+
+use heck::{ToSnakeCase, ToUpperCamelCase};
+
+/// Converts a protobuf name to a `snake_case` Rust identifier.
+pub(crate) fn to_snake(s: &str) -> String {
+    let mut ident = s.to_snake_case();
+
+    // Use a raw identifier if the identifier matches a Rust keyword.
+    match ident.as_str() {
+        "as" | "break" | "const" | "continue" | "else" | "enum" | "false" | "fn" | "for" | "if"
+        | "impl" | "in" | "let" | "loop" | "match" | "mod" | "move" | "mut" | "pub" | "ref"
+        | "return" | "static" | "struct" | "trait" | "true" | "type" | "unsafe" | "use"
+        | "where" | "while" | "dyn" | "abstract" | "become" | "box" | "do" | "final" | "macro"
+        | "override" | "priv" | "typeof" | "unsized" | "virtual" | "yield" | "async" | "await"
+        | "try" => ident.insert_str(0, "r#"),
+        // these keywords are not supported as raw identifiers, prost suffixes them instead
+        "self" | "super" | "extern" | "crate" => ident += "_",
+        _ => (),
+    }
+    ident
+}
+
+/// Converts a protobuf name to an `UpperCamel` case Rust type identifier.
+pub(crate) fn to_upper_camel(s: &str) -> String {
+    let mut ident = s.to_upper_camel_case();
+    if ident == "Self" {
+        ident += "_";
+    }
+    ident
+}
+
+/// The Rust path of the protobuf type `pb_ident`, relative to the module of `scope`, the same
+/// way prost resolves it.
+pub(crate) fn resolve_ident(scope: &[&str], pb_ident: &str) -> String {
+    let mut ident_path: Vec<_> = pb_ident[1..].split('.').collect();
+    let ident_type = ident_path.pop().unwrap_or_default();
+    let common = scope
+        .iter()
+        .zip(&ident_path)
+        .take_while(|(a, b)| a == b)
+        .count();
+    scope[common..]
+        .iter()
+        .map(|_| "super".to_owned())
+        .chain(ident_path[common..].iter().map(|s| to_snake(s)))
+        .chain(std::iter::once(to_upper_camel(ident_type)))
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ident_should_match_prost() {
+        assert_eq!(to_snake("FooBar"), "foo_bar");
+        assert_eq!(to_snake("type"), "r#type");
+        assert_eq!(to_snake("self"), "self_");
+        assert_eq!(to_upper_camel("foo_bar"), "FooBar");
+        assert_eq!(to_upper_camel("self"), "Self_");
+        assert_eq!(resolve_ident(&["pkg"], ".pkg.Color"), "Color");
+        assert_eq!(resolve_ident(&["pkg", "Msg"], ".pkg.Color"), "super::Color");
+        assert_eq!(resolve_ident(&["pkg"], ".pkg.Msg.Kind"), "msg::Kind");
+        assert_eq!(
+            resolve_ident(&["a"], ".b.foo_bar.Status"),
+            "super::b::foo_bar::Status"
+        );
+    }
+}
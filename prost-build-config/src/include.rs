@@ -0,0 +1,127 @@
+//! The include file, which puts the generated modules into nested `pub mod` blocks following the
+//! package hierarchy, the same way as prost's `include_file`. When the code is generated into a
+//! source dir, it is written as the `mod.rs` of that dir.
+
+use std::{fmt::Write, fs, io, path::Path};
+
+use prost_build::Module;
+
+/// first line of the generated Rust files, so that a hand-written `mod.rs` is never overwritten
+/// and hand-written files next to the generated ones are never reported as stale
+pub(crate) const HEADER: &str = "// This file is @generated by prost-build-config.";
+
+/// Check whether the file at `path` was generated, i.e. whether it can be (over)written.
+pub(crate) fn is_generated(path: &Path) -> io::Result<bool> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content.starts_with(HEADER)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(true),
+        Err(e) => Err(e),
+    }
+}
+
+/// Generate the include file for `modules`. With `from_out_dir` the files are included from
+/// Cargo's `OUT_DIR`, else relative to the include file.
+pub(crate) fn include_file<'a>(
+    modules: impl IntoIterator<Item = &'a Module>,
+    default_package_filename: &str,
+    from_out_dir: bool,
+) -> String {
+    let mut modules: Vec<Vec<&str>> = modules.into_iter().map(|m| m.parts().collect()).collect();
+    modules.sort();
+    let mut buf = format!("{}\n", HEADER);
+    let include = |parts: &[&str]| {
+        let file_name = match parts {
+            [] => format!("{}.rs", default_package_filename),
+            parts => format!("{}.rs", parts.join(".")),
+        };
+        match from_out_dir {
+            true => format!("include!(concat!(env!(\"OUT_DIR\"), \"/{}\"));", file_name),
+            false => format!("include!(\"{}\");", file_name),
+        }
+    };
+    let modules: Vec<&[&str]> = modules.iter().map(|m| &m[..]).collect();
+    write_modules(&mut buf, &modules, 0, &include);
+    buf
+}
+
+/// Write the modules which all share the first `depth` parts.
+fn write_modules(
+    buf: &mut String,
+    modules: &[&[&str]],
+    depth: usize,
+    include: &dyn Fn(&[&str]) -> String,
+) {
+    let indent = "    ".repeat(depth);
+    let (current, nested): (Vec<_>, Vec<_>) =
+        modules.iter().copied().partition(|m| m.len() == depth);
+    if let Some(module) = current.first() {
+        let _ = writeln!(buf, "{}{}", indent, include(module));
+    }
+
+    let mut rest = &nested[..];
+    while let Some(first) = rest.first() {
+        let name = first[depth];
+        let count = rest.iter().take_while(|m| m[depth] == name).count();
+        let (matching, others) = rest.split_at(count);
+        let package = first[..=depth].join(".");
+        let _ = writeln!(buf, "{}/// Protobuf package `{}`.", indent, package);
+        let _ = writeln!(buf, "{}pub mod {} {{", indent, name);
+        write_modules(buf, matching, depth + 1, include);
+        let _ = writeln!(buf, "{}}}", indent);
+        rest = others;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn include_file_should_follow_package_hierarchy() {
+        let modules = [
+            Module::from_protobuf_package_name("a.b"),
+            Module::from_protobuf_package_name("a"),
+            Module::from_protobuf_package_name("a.c.d"),
+            Module::from_protobuf_package_name(""),
+        ];
+        let expected = r#"// This file is @generated by prost-build-config.
+include!("_.rs");
+/// Protobuf package `a`.
+pub mod a {
+    include!("a.rs");
+    /// Protobuf package `a.b`.
+    pub mod b {
+        include!("a.b.rs");
+    }
+    /// Protobuf package `a.c`.
+    pub mod c {
+        /// Protobuf package `a.c.d`.
+        pub mod d {
+            include!("a.c.d.rs");
+        }
+    }
+}
+"#;
+        assert_eq!(include_file(&modules, "_", false), expected);
+
+        let modules = [Module::from_protobuf_package_name("hello")];
+        assert_eq!(
+            include_file(&modules, "_", true),
+            format!(
+                "{}\n/// Protobuf package `hello`.\npub mod hello {{\n    {}\n}}\n",
+                HEADER, "include!(concat!(env!(\"OUT_DIR\"), \"/hello.rs\"));"
+            )
+        );
+    }
+
+    #[test]
+    fn hand_written_mod_rs_should_be_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mod.rs");
+        assert!(is_generated(&path).unwrap());
+        fs::write(&path, include_file(&[], "_", false)).unwrap();
+        assert!(is_generated(&path).unwrap());
+        fs::write(&path, "pub mod hello;\n").unwrap();
+        assert!(!is_generated(&path).unwrap());
+    }
+}
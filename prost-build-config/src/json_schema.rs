@@ -0,0 +1,812 @@
+//! JSON Schema of the messages and enums, describing the JSON which serde produces for the
+//! generated types. The serde attributes put on the types and fields are taken into account:
+//! renames, skipped fields, `skip_serializing_if`, the representation of oneofs and the
+//! serializers of prost-helper, such as `serialize_buf` (base64) and `serialize_id` (uuid).
+//!
+//! A property is required when serde needs it to deserialize the type: the field isn't an
+//! `Option`, has no `default`, nor is in a type with `default`, and isn't skipped when serialized.
+//!
+//! Fields whose `serialize_with` is unknown accept any value. Flattened oneofs are only described
+//! when they are externally tagged, as with the `proto3_json` profile.
+
+use std::collections::{HashMap, HashSet};
+
+use heck::{
+    ToKebabCase, ToLowerCamelCase, ToShoutyKebabCase, ToShoutySnakeCase, ToSnakeCase,
+    ToUpperCamelCase,
+};
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+};
+use serde_json::{json, Map, Value};
+use syn::{meta::ParseNestedMeta, Attribute, LitStr};
+
+use crate::{
+    comments::comments,
+    ident::{to_snake, to_upper_camel},
+    path::{self, is_map_entry, package_prefix, real_oneof},
+};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// The serde attributes of a type or field which change its JSON.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Serde {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    /// `skip` or `skip_serializing`
+    skip: bool,
+    /// `skip_serializing_if`, the field may be missing
+    skip_if: bool,
+    /// `default`, the field may be missing when deserialized
+    default: bool,
+    flatten: bool,
+    deny_unknown_fields: bool,
+    /// the path of the serialize function, from `serialize_with` or `with`
+    serialize_with: Option<String>,
+    tag: Option<String>,
+    content: Option<String>,
+    untagged: bool,
+}
+
+/// How a field is serialized, from its `serialize_with` function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Serializer {
+    /// serde's own form, or a serializer which keeps it, like the redaction helpers
+    Plain,
+    /// the proto3 JSON form of `prost_helper::proto3`
+    Proto3,
+    /// `serialize_buf`, URL-safe base64
+    Base64,
+    /// `serialize_repeat_buf`
+    RepeatedBase64,
+    /// `serialize_id`
+    Uuid,
+    /// `serialize_repeat_id`
+    RepeatedUuid,
+    /// any other function
+    Custom,
+}
+
+/// Schema options of the builder.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Options {
+    pub retain_enum_prefix: bool,
+    pub compile_well_known_types: bool,
+}
+
+/// The descriptors and attributes the schemas are made from.
+pub(crate) struct Context<'a> {
+    /// serde attributes by fully-qualified path
+    attrs: HashMap<String, Serde>,
+    /// all messages and enums by fully-qualified name
+    messages: HashMap<String, &'a DescriptorProto>,
+    enums: HashMap<String, &'a EnumDescriptorProto>,
+    /// messages of proto2 files, whose optional fields are `Option`s
+    proto2_messages: HashSet<String>,
+    /// comments of the declarations by fully-qualified name, for the descriptions
+    comments: HashMap<String, String>,
+    options: Options,
+}
+
+/// The schema of a message or enum.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Schema {
+    /// protobuf package of the type, e.g. `helloworld`
+    pub package: String,
+    /// fully-qualified name of the type, e.g. `.helloworld.Hello`
+    pub fq_name: String,
+    pub value: Value,
+}
+
+/// The schemas of every message and enum in `files`. `attrs` are the attributes by
+/// fully-qualified path, e.g. `#[serde(rename = "id")]`.
+pub(crate) fn schemas(
+    files: &[FileDescriptorProto],
+    attrs: &HashMap<String, Vec<String>>,
+    options: Options,
+) -> Vec<Schema> {
+    let ctx = Context::new(files, attrs, options);
+    let mut schemas = Vec::new();
+    let mut file_schemas = Vec::new();
+    for file in files {
+        if file.package().starts_with("google.protobuf") && !options.compile_well_known_types {
+            continue;
+        }
+        let package = package_prefix(file);
+        let proto2 = matches!(file.syntax(), "" | "proto2");
+        for msg in &file.message_type {
+            ctx.message_schemas(&mut file_schemas, &package, msg, proto2);
+        }
+        for e in &file.enum_type {
+            let fq_name = format!("{}.{}", package, e.name());
+            file_schemas.push(ctx.enum_schema(&fq_name, e));
+        }
+        schemas.extend(file_schemas.drain(..).map(|(fq_name, value)| Schema {
+            package: file.package().to_owned(),
+            fq_name,
+            value,
+        }));
+    }
+    schemas
+}
+
+/// A `<name>.schema.json` file for every schema.
+pub(crate) fn files(schemas: &[Schema]) -> Vec<(String, String)> {
+    schemas
+        .iter()
+        .map(|schema| {
+            let json = serde_json::to_string_pretty(&schema.value).expect("schema is valid JSON");
+            (file_name(&schema.fq_name), json + "\n")
+        })
+        .collect()
+}
+
+/// The file name of the schema of a message or enum, which is also its `$id`.
+fn file_name(fq_name: &str) -> String {
+    format!("{}.schema.json", fq_name.trim_start_matches('.'))
+}
+
+/// The fully-qualified name of the type a `$ref` refers to.
+pub(crate) fn ref_name(reference: &str) -> Option<String> {
+    let name = reference.strip_suffix(".schema.json")?;
+    Some(format!(".{}", name))
+}
+
+impl<'a> Context<'a> {
+    pub fn new(
+        files: &'a [FileDescriptorProto],
+        attrs: &HashMap<String, Vec<String>>,
+        options: Options,
+    ) -> Self {
+        let mut ctx = Self {
+            attrs: attrs
+                .iter()
+                .map(|(path, attrs)| (path.clone(), Serde::parse(attrs)))
+                .collect(),
+            messages: path::messages(files),
+            enums: HashMap::new(),
+            proto2_messages: HashSet::new(),
+            comments: comments(files),
+            options,
+        };
+        for file in files {
+            let package = package_prefix(file);
+            let proto2 = matches!(file.syntax(), "" | "proto2");
+            for msg in &file.message_type {
+                ctx.collect(&package, msg, proto2);
+            }
+            for e in &file.enum_type {
+                ctx.enums.insert(format!("{}.{}", package, e.name()), e);
+            }
+        }
+        ctx
+    }
+
+    /// The comment of the declaration `fq_name`.
+    pub fn comment(&self, fq_name: &str) -> Option<&str> {
+        self.comments.get(fq_name).map(|c| c.as_str())
+    }
+
+    /// The schema of the message `fq_name` as serde writes it on its own.
+    pub fn message_schema(&self, fq_name: &str) -> Value {
+        match fq_name.strip_prefix(".google.protobuf.") {
+            Some(name) if !self.options.compile_well_known_types => well_known_type(name, false),
+            _ => json!({ "$ref": file_name(fq_name) }),
+        }
+    }
+
+    /// The schema of the value of `field` of the message `parent` as serde writes its Rust type
+    /// on its own, without the attributes of the field, e.g. of a field bound to the body of an
+    /// HTTP rule.
+    pub fn type_schema(&self, parent: &str, field: &FieldDescriptorProto) -> Value {
+        let schema = self.value_schema(field, Serializer::Plain);
+        match is_option(field, self.proto2_messages.contains(parent)) {
+            true => nullable(schema),
+            false => schema,
+        }
+    }
+
+    /// Collect the nested enums of `msg`, and whether it is a proto2 message.
+    fn collect(&mut self, parent: &str, msg: &'a DescriptorProto, proto2: bool) {
+        let fq_name = format!("{}.{}", parent, msg.name());
+        for nested in &msg.nested_type {
+            self.collect(&fq_name, nested, proto2);
+        }
+        for e in &msg.enum_type {
+            self.enums.insert(format!("{}.{}", fq_name, e.name()), e);
+        }
+        if proto2 {
+            self.proto2_messages.insert(fq_name);
+        }
+    }
+
+    fn serde(&self, path: &str) -> Serde {
+        self.attrs.get(path).cloned().unwrap_or_default()
+    }
+
+    /// Add the schemas of `msg` and of its nested messages and enums.
+    fn message_schemas(
+        &self,
+        schemas: &mut Vec<(String, Value)>,
+        parent: &str,
+        msg: &DescriptorProto,
+        proto2: bool,
+    ) {
+        let fq_name = format!("{}.{}", parent, msg.name());
+        let container = self.serde(&fq_name);
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        let mut oneofs_done = Vec::new();
+        for field in &msg.field {
+            let oneof = real_oneof(msg, field.oneof_index, field.proto3_optional());
+            if let Some(oneof) = oneof {
+                if !oneofs_done.contains(&oneof) {
+                    oneofs_done.push(oneof);
+                    self.oneof_properties(&mut properties, &fq_name, &container, msg, oneof);
+                }
+                continue;
+            }
+            let serde = self.serde(&format!("{}.{}", fq_name, field.name()));
+            if serde.skip {
+                continue;
+            }
+            let name = field_name(&serde, &container, &to_snake(field.name()));
+            let serializer = serde.serializer();
+            let mut schema = self.value_schema(field, serializer);
+            let is_option = is_option(field, proto2);
+            if is_option && matches!(serializer, Serializer::Plain | Serializer::Proto3) {
+                schema = nullable(schema);
+            }
+            if !(is_option || serde.skip_if || serde.default || container.default) {
+                required.push(Value::String(name.clone()));
+            }
+            let schema = self.describe(schema, &format!("{}.{}", fq_name, field.name()));
+            properties.insert(name, schema);
+        }
+
+        let mut schema = Map::new();
+        schema.insert("$schema".to_owned(), json!(DRAFT));
+        schema.insert("$id".to_owned(), json!(file_name(&fq_name)));
+        schema.insert("title".to_owned(), json!(msg.name()));
+        schema.insert("type".to_owned(), json!("object"));
+        schema.insert("properties".to_owned(), Value::Object(properties));
+        if !required.is_empty() {
+            schema.insert("required".to_owned(), Value::Array(required));
+        }
+        if container.deny_unknown_fields {
+            schema.insert("additionalProperties".to_owned(), json!(false));
+        }
+        let schema = self.describe(Value::Object(schema), &fq_name);
+        schemas.push((fq_name.clone(), schema));
+
+        for nested in &msg.nested_type {
+            if !is_map_entry(nested) {
+                self.message_schemas(schemas, &fq_name, nested, proto2);
+            }
+        }
+        for e in &msg.enum_type {
+            let enum_name = format!("{}.{}", fq_name, e.name());
+            schemas.push(self.enum_schema(&enum_name, e));
+        }
+    }
+
+    /// Add the property of a oneof, or the properties of its variants if it is flattened. Oneofs
+    /// are `Option`s, so they are never required.
+    fn oneof_properties(
+        &self,
+        properties: &mut Map<String, Value>,
+        fq_name: &str,
+        container: &Serde,
+        msg: &DescriptorProto,
+        oneof: &str,
+    ) {
+        let path = format!("{}.{}", fq_name, oneof);
+        let serde = self.serde(&path);
+        if serde.skip {
+            return;
+        }
+        let mut variants = Vec::new();
+        for field in &msg.field {
+            if real_oneof(msg, field.oneof_index, field.proto3_optional()) != Some(oneof) {
+                continue;
+            }
+            let variant = self.serde(&format!("{}.{}", path, field.name()));
+            if variant.skip {
+                continue;
+            }
+            let name = match &variant.rename {
+                Some(rename) => rename.clone(),
+                None => rename_variant(serde.rename_all.as_deref(), &to_upper_camel(field.name())),
+            };
+            let schema = self.value_schema(field, variant.serializer());
+            let schema = self.describe(schema, &format!("{}.{}", fq_name, field.name()));
+            variants.push((name, schema));
+        }
+
+        let is_external = !serde.untagged && serde.tag.is_none();
+        match (serde.flatten, is_external) {
+            (true, true) => properties.extend(variants),
+            // the fields of other flattened representations depend on the set variant
+            (true, false) => {}
+            (false, _) => {
+                let name = field_name(&serde, container, &to_snake(oneof));
+                let schema = nullable(enum_representation(&serde, variants));
+                properties.insert(name, self.describe(schema, &path));
+            }
+        }
+    }
+
+    /// The schema of the value of `field`, without the `null` of optional fields.
+    fn value_schema(&self, field: &FieldDescriptorProto, serializer: Serializer) -> Value {
+        match serializer {
+            Serializer::Plain | Serializer::Proto3 => {}
+            Serializer::Base64 => return base64url(),
+            Serializer::RepeatedBase64 => return array(base64url()),
+            Serializer::Uuid => return uuid(),
+            Serializer::RepeatedUuid => return array(uuid()),
+            Serializer::Custom => return json!({}),
+        }
+        let proto3 = serializer == Serializer::Proto3;
+        if field.label() == Label::Repeated && field.r#type() == Type::Message {
+            if let Some(entry) = self.messages.get(field.type_name()) {
+                if is_map_entry(entry) {
+                    // map keys are always strings in JSON
+                    let value = match entry.field.get(1) {
+                        Some(value) => self.scalar_schema(value, proto3),
+                        None => json!({}),
+                    };
+                    return json!({ "type": "object", "additionalProperties": value });
+                }
+            }
+        }
+        match field.label() {
+            Label::Repeated => array(self.scalar_schema(field, proto3)),
+            _ => self.scalar_schema(field, proto3),
+        }
+    }
+
+    /// The schema of a single value of `field`.
+    fn scalar_schema(&self, field: &FieldDescriptorProto, proto3: bool) -> Value {
+        match field.r#type() {
+            Type::Double | Type::Float if proto3 => json!({
+                "anyOf": [
+                    { "type": "number" },
+                    { "type": "string", "enum": ["NaN", "Infinity", "-Infinity"] }
+                ]
+            }),
+            Type::Double => json!({ "type": "number", "format": "double" }),
+            Type::Float => json!({ "type": "number", "format": "float" }),
+            Type::Int64 | Type::Sint64 | Type::Sfixed64 if proto3 => {
+                json!({ "type": "string", "format": "int64" })
+            }
+            Type::Uint64 | Type::Fixed64 if proto3 => {
+                json!({ "type": "string", "format": "uint64" })
+            }
+            Type::Int64 | Type::Sint64 | Type::Sfixed64 => {
+                json!({ "type": "integer", "format": "int64" })
+            }
+            Type::Uint64 | Type::Fixed64 => {
+                json!({ "type": "integer", "format": "uint64", "minimum": 0 })
+            }
+            Type::Int32 | Type::Sint32 | Type::Sfixed32 => {
+                json!({ "type": "integer", "format": "int32" })
+            }
+            Type::Uint32 | Type::Fixed32 => {
+                json!({ "type": "integer", "format": "uint32", "minimum": 0 })
+            }
+            Type::Bool => json!({ "type": "boolean" }),
+            Type::String => json!({ "type": "string" }),
+            Type::Bytes if proto3 => base64(),
+            Type::Bytes => array(json!({ "type": "integer", "minimum": 0, "maximum": 255 })),
+            // enums are `i32` fields, proto3 JSON writes them by name
+            Type::Enum => match self.enums.get(field.type_name()) {
+                Some(e) if proto3 => {
+                    let names: Vec<_> = e.value.iter().map(|v| v.name()).collect();
+                    json!({ "type": "string", "enum": names })
+                }
+                _ => json!({ "type": "integer", "format": "int32" }),
+            },
+            Type::Message => match field.type_name().strip_prefix(".google.protobuf.") {
+                Some(name) if !self.options.compile_well_known_types => {
+                    well_known_type(name, proto3)
+                }
+                _ => self.message_schema(field.type_name()),
+            },
+            Type::Group => json!({}),
+        }
+    }
+
+    /// The schema of a Rust enum generated for a protobuf enum, as serde writes it. Fields only
+    /// hold its `i32` value.
+    fn enum_schema(&self, fq_name: &str, e: &EnumDescriptorProto) -> (String, Value) {
+        let serde = self.serde(fq_name);
+        let prefix = to_upper_camel(e.name());
+        let mut names: Vec<String> = Vec::new();
+        let mut numbers = Vec::new();
+        for value in &e.value {
+            // prost generates one variant per number, aliases become constants
+            if numbers.contains(&value.number()) {
+                continue;
+            }
+            numbers.push(value.number());
+            let variant = match self.options.retain_enum_prefix {
+                true => to_upper_camel(value.name()),
+                false => strip_enum_prefix(&prefix, &to_upper_camel(value.name())),
+            };
+            names.push(rename_variant(serde.rename_all.as_deref(), &variant));
+        }
+        let schema = json!({
+            "$schema": DRAFT,
+            "$id": file_name(fq_name),
+            "title": e.name(),
+            "type": "string",
+            "enum": names,
+        });
+        (fq_name.to_owned(), self.describe(schema, fq_name))
+    }
+
+    /// Add the comment of the declaration `fq_name` to `schema` as its description.
+    fn describe(&self, mut schema: Value, fq_name: &str) -> Value {
+        if let (Some(object), Some(comment)) = (schema.as_object_mut(), self.comments.get(fq_name))
+        {
+            object.insert("description".to_owned(), json!(comment));
+        }
+        schema
+    }
+}
+
+impl Serde {
+    /// Collect the serde attributes of `attrs`. Attributes which don't parse are ignored.
+    fn parse(attrs: &[String]) -> Self {
+        let mut serde = Self::default();
+        for attr in attrs {
+            let Ok(attrs) = syn::parse::Parser::parse_str(Attribute::parse_outer, attr) else {
+                continue;
+            };
+            for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+                let _ = attr.parse_nested_meta(|meta| serde.parse_meta(meta));
+            }
+        }
+        serde
+    }
+
+    fn parse_meta(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        let key = meta
+            .path
+            .get_ident()
+            .map(|i| i.to_string())
+            .unwrap_or_default();
+        match key.as_str() {
+            "rename" => self.rename = serialize_name(&meta)?,
+            "rename_all" => self.rename_all = serialize_name(&meta)?,
+            "skip" | "skip_serializing" => self.skip = true,
+            "skip_serializing_if" => {
+                self.skip_if = true;
+                skip_value(&meta)?;
+            }
+            "default" => {
+                self.default = true;
+                skip_value(&meta)?;
+            }
+            "flatten" => self.flatten = true,
+            "deny_unknown_fields" => self.deny_unknown_fields = true,
+            "untagged" => self.untagged = true,
+            "tag" => self.tag = Some(meta.value()?.parse::<LitStr>()?.value()),
+            "content" => self.content = Some(meta.value()?.parse::<LitStr>()?.value()),
+            "serialize_with" => {
+                self.serialize_with = Some(meta.value()?.parse::<LitStr>()?.value());
+            }
+            "with" => {
+                let module = meta.value()?.parse::<LitStr>()?.value();
+                self.serialize_with = Some(format!("{}::serialize", module));
+            }
+            _ => skip_value(&meta)?,
+        }
+        Ok(())
+    }
+
+    fn serializer(&self) -> Serializer {
+        let Some(path) = &self.serialize_with else {
+            return Serializer::Plain;
+        };
+        // drop the generic arguments, e.g. of `serialize_enum::<Color, _, _>`
+        let path = path.split("::<").next().unwrap_or_default();
+        let mut segments = path.rsplit("::");
+        match (segments.next(), segments.next()) {
+            (Some("serialize" | "serialize_enum"), Some("proto3")) => Serializer::Proto3,
+            (Some("serialize_buf"), _) => Serializer::Base64,
+            (Some("serialize_repeat_buf"), _) => Serializer::RepeatedBase64,
+            (Some("serialize_id"), _) => Serializer::Uuid,
+            (Some("serialize_repeat_id"), _) => Serializer::RepeatedUuid,
+            // redaction only changes the JSON of `ToJson`
+            (Some("serialize_redacted" | "serialize_redacted_hash"), _) => Serializer::Plain,
+            _ => Serializer::Custom,
+        }
+    }
+}
+
+/// The name of `rename = "..."` or `rename(serialize = "...")`.
+fn serialize_name(meta: &ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(syn::Token![=]) {
+        return Ok(Some(meta.value()?.parse::<LitStr>()?.value()));
+    }
+    let mut name = None;
+    meta.parse_nested_meta(|nested| {
+        let value = nested.value()?.parse::<LitStr>()?.value();
+        if nested.path.is_ident("serialize") {
+            name = Some(value);
+        }
+        Ok(())
+    })?;
+    Ok(name)
+}
+
+/// Skip the value of an attribute argument we don't look at.
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|nested| skip_value(&nested))?;
+    }
+    Ok(())
+}
+
+/// The JSON name of a field or oneof whose Rust name is `ident`.
+fn field_name(serde: &Serde, container: &Serde, ident: &str) -> String {
+    if let Some(rename) = &serde.rename {
+        return rename.clone();
+    }
+    let ident = ident.trim_start_matches("r#");
+    match container.rename_all.as_deref() {
+        Some("UPPERCASE") => ident.to_uppercase(),
+        Some("PascalCase") => ident.to_upper_camel_case(),
+        Some("camelCase") => ident.to_lower_camel_case(),
+        Some("SCREAMING_SNAKE_CASE") => ident.to_shouty_snake_case(),
+        Some("kebab-case") => ident.to_kebab_case(),
+        Some("SCREAMING-KEBAB-CASE") => ident.to_shouty_kebab_case(),
+        _ => ident.to_owned(),
+    }
+}
+
+/// The JSON name of an enum variant whose Rust name is `ident`, with serde's `rename_all`.
+fn rename_variant(rename_all: Option<&str>, ident: &str) -> String {
+    match rename_all {
+        Some("lowercase") => ident.to_lowercase(),
+        Some("UPPERCASE") => ident.to_uppercase(),
+        Some("camelCase") => ident.to_lower_camel_case(),
+        Some("snake_case") => ident.to_snake_case(),
+        Some("SCREAMING_SNAKE_CASE") => ident.to_shouty_snake_case(),
+        Some("kebab-case") => ident.to_kebab_case(),
+        Some("SCREAMING-KEBAB-CASE") => ident.to_shouty_kebab_case(),
+        _ => ident.to_owned(),
+    }
+}
+
+/// The variant name prost generates, without the name of the enum as prefix.
+fn strip_enum_prefix(prefix: &str, name: &str) -> String {
+    let stripped = name.strip_prefix(prefix).unwrap_or(name);
+    // `Foo` is not a prefix of `Foobar`
+    match stripped.starts_with(|c: char| c.is_uppercase()) {
+        true => stripped.to_owned(),
+        false => name.to_owned(),
+    }
+}
+
+/// The schema of a oneof enum in serde's representation given by `serde`.
+fn enum_representation(serde: &Serde, variants: Vec<(String, Value)>) -> Value {
+    if serde.untagged {
+        let schemas: Vec<_> = variants.into_iter().map(|(_, schema)| schema).collect();
+        return json!({ "anyOf": schemas });
+    }
+    let schemas: Vec<_> = variants
+        .into_iter()
+        .map(|(name, schema)| match (&serde.tag, &serde.content) {
+            (Some(tag), Some(content)) => json!({
+                "type": "object",
+                "properties": { tag: { "const": name }, content: schema },
+                "required": [tag, content],
+            }),
+            (Some(tag), None) => json!({
+                "allOf": [
+                    { "type": "object", "properties": { tag: { "const": name } }, "required": [tag] },
+                    schema,
+                ]
+            }),
+            _ => json!({
+                "type": "object",
+                "properties": { name.clone(): schema },
+                "required": [name],
+                "additionalProperties": false,
+            }),
+        })
+        .collect();
+    json!({ "oneOf": schemas })
+}
+
+/// The schema of a well-known type. prost generates the wrappers as `Option`s of their values,
+/// the other types of prost-types only have serde support for the proto3 JSON form of
+/// `prost_helper::proto3`.
+fn well_known_type(name: &str, proto3: bool) -> Value {
+    match (name, proto3) {
+        ("BoolValue", _) => json!({ "type": "boolean" }),
+        ("StringValue", _) => json!({ "type": "string" }),
+        ("Int32Value", _) => json!({ "type": "integer", "format": "int32" }),
+        ("UInt32Value", _) => json!({ "type": "integer", "format": "uint32", "minimum": 0 }),
+        // `()` is a unit in serde, while proto3 JSON writes an empty object
+        ("Empty", false) => json!({ "type": "null" }),
+        ("Empty", true) => json!({ "type": "object", "additionalProperties": false }),
+        ("DoubleValue" | "FloatValue", false) => json!({ "type": "number" }),
+        ("Int64Value", false) => json!({ "type": "integer", "format": "int64" }),
+        ("UInt64Value", false) => json!({ "type": "integer", "format": "uint64", "minimum": 0 }),
+        ("BytesValue", false) => array(json!({ "type": "integer", "minimum": 0, "maximum": 255 })),
+        ("DoubleValue" | "FloatValue", true) => json!({
+            "anyOf": [
+                { "type": "number" },
+                { "type": "string", "enum": ["NaN", "Infinity", "-Infinity"] }
+            ]
+        }),
+        ("Int64Value", true) => json!({ "type": "string", "format": "int64" }),
+        ("UInt64Value", true) => json!({ "type": "string", "format": "uint64" }),
+        ("BytesValue", true) => base64(),
+        ("Timestamp", true) => json!({ "type": "string", "format": "date-time" }),
+        ("Duration", true) => json!({ "type": "string", "pattern": "^-?[0-9]+(\\.[0-9]+)?s$" }),
+        ("FieldMask", true) => json!({ "type": "string" }),
+        ("Struct", true) => json!({ "type": "object" }),
+        ("ListValue", true) => json!({ "type": "array" }),
+        _ => json!({}),
+    }
+}
+
+fn nullable(schema: Value) -> Value {
+    json!({ "anyOf": [schema, { "type": "null" }] })
+}
+
+fn array(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+/// Standard base64 with padding, as proto3 JSON writes bytes.
+fn base64() -> Value {
+    json!({ "type": "string", "format": "byte", "contentEncoding": "base64" })
+}
+
+/// URL-safe base64 without padding, as `prost_helper::serialize_buf` writes bytes.
+fn base64url() -> Value {
+    json!({ "type": "string", "contentEncoding": "base64url", "pattern": "^[A-Za-z0-9_-]*$" })
+}
+
+fn uuid() -> Value {
+    json!({ "type": "string", "format": "uuid" })
+}
+
+/// Whether prost generates an `Option` for `field`.
+fn is_option(field: &FieldDescriptorProto, proto2: bool) -> bool {
+    field.label() != Label::Repeated
+        && (field.r#type() == Type::Message
+            || field.proto3_optional()
+            || (proto2 && field.label() == Label::Optional))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BuildConfig, Builder};
+
+    #[allow(clippy::all)]
+    mod account {
+        include!("../examples/json_schema/account.rs");
+    }
+
+    fn schema(name: &str) -> Value {
+        let path = format!("examples/json_schema/schema/{}.schema.json", name);
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn generate_json_schema() {
+        let config = BuildConfig::from_path("examples/json_schema.yml").unwrap();
+        Builder::from(config).build_protos();
+    }
+
+    #[test]
+    fn serde_attributes_should_be_reflected() {
+        let user = schema("account.User");
+        assert_eq!(user["$id"], "account.User.schema.json");
+        let properties = &user["properties"];
+        assert_eq!(
+            properties["id"],
+            json!({ "type": "string", "format": "uuid" })
+        );
+        assert_eq!(properties["friendIds"]["items"]["format"], "uuid");
+        assert_eq!(properties["avatar"]["contentEncoding"], "base64url");
+        assert_eq!(properties["role"]["type"], "integer");
+        assert_eq!(
+            properties["address"],
+            json!({ "anyOf": [{ "$ref": "account.Address.schema.json" }, { "type": "null" }] })
+        );
+        assert_eq!(
+            properties["counters"]["additionalProperties"]["format"],
+            "int64"
+        );
+        assert!(properties.get("password").is_none());
+        assert_eq!(
+            properties["login"]["anyOf"][0]["oneOf"][1]["properties"],
+            json!({ "type": { "const": "phone" }, "value": { "type": "integer", "format": "uint64", "minimum": 0 } })
+        );
+        // the fields of a `serde(default)` type may be missing
+        assert!(user.get("required").is_none());
+        // `zip` is skipped when empty
+        assert_eq!(schema("account.Address")["required"], json!(["city"]));
+
+        let role = schema("account.Role");
+        assert_eq!(role["enum"], json!(["unspecified", "admin", "member"]));
+    }
+
+    #[test]
+    fn json_schema_should_match_serialized_json() {
+        let user = account::User {
+            id: vec![],
+            display_name: "alice".to_owned(),
+            avatar: vec![1, 2],
+            role: account::Role::Admin as i32,
+            address: Some(account::Address::default()),
+            login: Some(account::user::Login::Phone(42)),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&user).unwrap();
+        let object = value.as_object().unwrap();
+        let user_schema = schema("account.User");
+        let properties = user_schema["properties"].as_object().unwrap();
+        for key in object.keys() {
+            assert!(properties.contains_key(key), "{} is not in the schema", key);
+        }
+        assert_eq!(object["login"], json!({ "type": "phone", "value": 42 }));
+        assert_eq!(object["avatar"], "AQI");
+
+        // the required properties are the ones serde can't deserialize without
+        let address = schema("account.Address");
+        let required = address["required"].as_array().unwrap();
+        for key in required {
+            let mut object = json!({ "city": "Paris", "zip": "75001" });
+            object
+                .as_object_mut()
+                .unwrap()
+                .remove(key.as_str().unwrap());
+            assert!(serde_json::from_value::<account::Address>(object).is_err());
+        }
+        let object = json!({ "city": "Paris" });
+        assert!(serde_json::from_value::<account::Address>(object).is_ok());
+    }
+
+    #[test]
+    fn empty_should_match_its_json() {
+        use prost_helper::proto3::Proto3Json;
+
+        // prost generates `Empty` as `()`
+        assert_eq!(serde_json::to_value(()).unwrap(), Value::Null);
+        assert_eq!(well_known_type("Empty", false), json!({ "type": "null" }));
+        assert_eq!(Proto3Json::to_value(&()).unwrap(), json!({}));
+        assert_eq!(
+            well_known_type("Empty", true),
+            json!({ "type": "object", "additionalProperties": false })
+        );
+    }
+
+    #[test]
+    fn serde_attributes_should_be_parsed() {
+        let serde = Serde::parse(&[
+            "#[serde(rename(serialize = \"a\"), skip_serializing_if = \"Option::is_none\")]"
+                .to_owned(),
+            "#[validate(length(min = 1))]\n#[serde(with = \"::prost_helper::proto3\", default)]"
+                .to_owned(),
+        ]);
+        assert_eq!(serde.rename.as_deref(), Some("a"));
+        assert!(serde.skip_if);
+        assert!(serde.default);
+        assert_eq!(serde.serializer(), Serializer::Proto3);
+        assert_eq!(strip_enum_prefix("Role", "RoleAdmin"), "Admin");
+        assert_eq!(strip_enum_prefix("Foo", "Foobar"), "Foobar");
+    }
+}
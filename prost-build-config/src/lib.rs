@@ -37,15 +37,66 @@
 //! use prost_build_config::{BuildConfig, Builder};
 //!
 //! fn main() {
-//!     let content = include_str!("../examples/build_config.yml");
-//!     let config: BuildConfig = serde_yaml::from_str(content).unwrap();
+//!     let config = BuildConfig::from_path("examples/build_config.yml").unwrap();
 //!     Builder::from(config).build_protos();
 //! }
 //! ```
+//!
+//! The builder prints `cargo:rerun-if-changed` for the config file and every proto file it
+//! compiled, so the build script only reruns when one of them changes.
+//!
+//! To verify checked-in generated code, e.g. in CI, set `PROST_BUILD_CONFIG_CHECK=1`, or call
+//! [`Builder::check`] instead of [`Builder::build_protos`]. The code is then compared with the
+//! files in the output dir, and stale files are reported with a unified diff.
 
-use prost_build::{Config, ServiceGenerator};
+mod check;
+mod comments;
+mod compiler;
+mod descriptor_set;
+mod explain;
+mod format;
+mod gateway;
+mod ident;
+mod include;
+mod json_schema;
+mod load;
+mod oneof;
+mod openapi;
+mod path;
+mod preset;
+mod proto3_json;
+mod redact;
+mod service;
+mod service_trait;
+mod typescript;
+
+use prost::Message;
+use prost_build::{Config, Module, ServiceGenerator};
+use prost_types::{FileDescriptorProto, FileDescriptorSet};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf, process::Command};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use explain::{Rule, Section};
+use gateway::Gateway;
+use json_schema::Schema;
+use oneof::EnumAttrs;
+use redact::Sensitive;
+use service::{ServiceFlag, SharedServiceGenerator};
+
+pub use compiler::Compiler;
+pub use explain::{ExplainedAttr, ExplainedPath, Explanation, RuleRef};
+pub use format::Format;
+pub use oneof::OneofOption;
+pub use path::{PathKind, ProtoPath};
+pub use proto3_json::Profile;
+pub use redact::SensitiveMask;
+pub use service::{Compression, MultiServiceGenerator, ServiceOption};
 
 #[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(default)]
@@ -56,18 +107,79 @@ pub struct BuildConfig {
     pub includes: Vec<String>,
     /// protobuf files
     pub files: Vec<String>,
+    /// backend to parse the protobuf files: `protoc` or `protox`
+    pub compiler: Compiler,
     /// dir for generated code, defaults to Cargo OUT_DIR, else the current dir
     pub output: Option<String>,
+    /// how the generated code is formatted: `none`, `prettyplease` or `rustfmt`
+    pub format: Format,
+    /// predefined attributes for all types, e.g. `proto3_json`
+    pub profile: Option<Profile>,
     /// build options for messages
     pub messages: Vec<BuildOption>,
     /// build options for enums
     pub enums: Vec<BuildOption>,
+    /// build options for oneofs
+    pub oneofs: Vec<OneofOption>,
     /// build options for fields
     pub fields: Vec<BuildOption>,
+    /// build options for gRPC services, generated with tonic or as plain async traits
+    pub services: Vec<ServiceOption>,
     /// build options for bytes
     pub bytes: Vec<String>,
     /// build options for BTreeMap
     pub btree_maps: Vec<String>,
+    /// fields to mask in `ToJson` output and in `Debug`, e.g. `auth.Login.password`
+    pub sensitive: Vec<String>,
+    /// how sensitive fields are masked: `stars` or `hash`
+    pub sensitive_mask: SensitiveMask,
+    /// named lists of attributes, which build options can reference in `presets`
+    pub presets: HashMap<String, Vec<String>>,
+    /// protobuf paths provided by other crates, e.g. `.google.protobuf: ::pbjson_types`
+    pub extern_paths: HashMap<String, String>,
+    /// fields to wrap in `Box`
+    pub boxed: Vec<String>,
+    /// generate the `google.protobuf` types instead of using `prost-types`
+    pub compile_well_known_types: bool,
+    /// paths whose comments are not put into the generated code
+    pub disable_comments: Vec<String>,
+    /// messages and enums which don't derive `Debug`, so that it can be implemented by hand
+    pub skip_debug: Vec<String>,
+    /// file in the output dir which includes all generated modules, e.g. `_includes.rs`. If
+    /// `output` is set, it defaults to `mod.rs`, unless there is a hand-written one
+    pub include_file: Option<String>,
+    /// file in the output dir to write the encoded `FileDescriptorSet` to
+    pub file_descriptor_set_path: Option<String>,
+    /// file in the output dir to write the encoded `FileDescriptorSet` to, which the include file
+    /// includes as `FILE_DESCRIPTOR_SET` for runtime reflection, e.g. `descriptor_set.bin`
+    pub descriptor_set: Option<String>,
+    /// keep the source info, i.e. the comments and locations, in `descriptor_set`
+    pub descriptor_set_source_info: bool,
+    /// dir in the output dir to write a `<message>.schema.json` JSON Schema of every message and
+    /// enum to, e.g. `schema`
+    pub json_schema: Option<String>,
+    /// dir in the output dir to write a `<package>.d.ts` file with TypeScript types of the
+    /// messages and of the enums they use of every package to, matching their JSON like
+    /// `json_schema`
+    pub typescript: Option<String>,
+    /// dir in the output dir to write a `<package>.<Service>.openapi.json` OpenAPI 3.1 document of
+    /// every service to, with the messages described like `json_schema`, e.g. `openapi`
+    pub openapi: Option<String>,
+    /// extra arguments for `protoc`, e.g. `--experimental_allow_proto3_optional`. Not supported
+    /// by `compiler: protox`
+    pub protoc_args: Vec<String>,
+    /// keep the enum name prefix of the enum variants
+    pub retain_enum_prefix: bool,
+    /// implement `prost::Name` for all messages
+    pub enable_type_names: bool,
+    /// file name of the module for protobuf files without package, defaults to `_`
+    pub default_package_filename: Option<String>,
+    /// path of the config file, if it was loaded with `from_path`
+    #[serde(skip)]
+    pub config_path: Option<PathBuf>,
+    /// paths of the config files it extends, if it was loaded with `from_path`
+    #[serde(skip)]
+    pub parent_paths: Vec<PathBuf>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Default)]
@@ -77,18 +189,74 @@ pub struct BuildOption {
     pub paths: Vec<String>,
     /// description of the option
     pub description: String,
+    /// names of presets whose attributes are put before `attrs`, e.g. `serde`, `serde_camel_case`
+    /// or `validator`
+    pub presets: Vec<String>,
     /// extra attributes to put on generated data structure, for example: `derive(Serialize, Deserialize)`
     /// it will be converted to `#[derive(Serialize, Deserialize)]`
     pub attrs: Vec<String>,
 }
 
 pub struct Builder {
-    /// prost configuration
-    config: Config,
+    /// creates the prost configuration of the config options, fresh for every run, as prost
+    /// keeps adding the attributes and service generators set on it
+    base_config: Box<dyn Fn() -> Config>,
     /// protobuf include dirs
     pub includes: Vec<String>,
     /// protobuf files
     pub files: Vec<String>,
+    /// backend to parse the protobuf files
+    pub compiler: Compiler,
+    /// dir for generated code
+    out_dir: PathBuf,
+    /// whether the output dir is Cargo OUT_DIR
+    out_dir_is_env: bool,
+    /// how the generated code is formatted
+    format: Format,
+    /// predefined attributes for all types
+    profile: Option<Profile>,
+    /// attributes for enums, resolved against the descriptors
+    enums: Vec<EnumAttrs>,
+    /// attributes for oneofs, resolved against the descriptors
+    oneofs: Vec<EnumAttrs>,
+    /// paths of sensitive fields
+    sensitive: Vec<String>,
+    /// how sensitive fields are masked
+    sensitive_mask: SensitiveMask,
+    /// paths of the config file and the config files it extends
+    config_paths: Vec<PathBuf>,
+    /// build options with their attributes, to explain them
+    rules: Vec<Rule>,
+    /// service generators, set on the prost config when the code is generated
+    service_generators: Rc<RefCell<MultiServiceGenerator>>,
+    /// services with an HTTP gateway
+    http_services: ServiceFlag,
+    /// whether the well-known types are generated instead of using prost-types
+    compile_well_known_types: bool,
+    /// messages and enums which don't derive `Debug`
+    skip_debug: Vec<String>,
+    /// file which includes all generated modules
+    include_file: Option<PathBuf>,
+    /// whether the include file is the default `mod.rs` of the output dir
+    include_file_is_default: bool,
+    /// file to write the encoded `FileDescriptorSet` to
+    file_descriptor_set_path: Option<PathBuf>,
+    /// file in the output dir which the include file includes as `FILE_DESCRIPTOR_SET`
+    descriptor_set: Option<String>,
+    /// whether the source info is kept in the descriptor set
+    descriptor_set_source_info: bool,
+    /// dir to write the JSON Schemas to
+    json_schema_dir: Option<PathBuf>,
+    /// dir to write the TypeScript types to
+    typescript_dir: Option<PathBuf>,
+    /// dir to write the OpenAPI documents to
+    openapi_dir: Option<PathBuf>,
+    /// whether the enum variants keep the enum name prefix
+    retain_enum_prefix: bool,
+    /// extra arguments for `protoc`
+    protoc_args: Vec<String>,
+    /// file name of the module for protobuf files without package
+    default_package_filename: String,
 }
 
 impl From<BuildConfig> for Builder {
@@ -97,75 +265,527 @@ impl From<BuildConfig> for Builder {
         // OUT_DIR env variable provided by Cargo if it exists (it should!), else
         // fallback to the current directory.
         let output_dir: String = match &config.output {
-            None => {
-                let default_output_dir = std::env::var("OUT_DIR");
+            None => std::env::var("OUT_DIR").unwrap_or_default(),
+            Some(specified_output) => specified_output.to_owned(),
+        };
 
-                match default_output_dir {
-                    Err(_) => String::new(),
-                    Ok(cargo_out_dir) => cargo_out_dir,
+        let base_config = {
+            let btree_maps = config.btree_maps;
+            let bytes = config.bytes;
+            let extern_paths = config.extern_paths;
+            let boxed = config.boxed;
+            let compile_well_known_types = config.compile_well_known_types;
+            let disable_comments = config.disable_comments;
+            let retain_enum_prefix = config.retain_enum_prefix;
+            let enable_type_names = config.enable_type_names;
+            let output_dir = output_dir.clone();
+            move || {
+                let mut c = Config::new();
+                c.btree_map(&btree_maps);
+                c.bytes(&bytes);
+                for (proto_path, rust_path) in &extern_paths {
+                    c.extern_path(proto_path, rust_path);
+                }
+                for path in &boxed {
+                    c.boxed(path);
                 }
+                if compile_well_known_types {
+                    c.compile_well_known_types();
+                }
+                c.disable_comments(&disable_comments);
+                if retain_enum_prefix {
+                    c.retain_enum_prefix();
+                }
+                if enable_type_names {
+                    c.enable_type_names();
+                }
+                c.out_dir(&output_dir);
+                // formatting is done after the extra code is appended to the generated modules
+                c.format(false);
+                c
             }
-            Some(specified_output) => specified_output.to_owned(),
         };
 
-        let mut c = Config::new();
-
-        c.btree_map(config.btree_maps);
-        c.bytes(config.bytes);
+        let mut rules = Vec::new();
+        for (index, opt) in config.messages.into_iter().enumerate() {
+            let attrs = opt.resolve_attrs(&config.presets);
+            rules.push(Rule::new(Section::Messages, index, opt.paths, attrs));
+        }
 
-        for opt in config.messages {
-            for p in opt.paths {
-                c.type_attribute(p, to_attr(&opt.attrs));
-            }
+        // prost's enum attributes apply to oneofs as well, so enums and oneofs are only told
+        // apart once the proto files are compiled
+        let mut enums = Vec::new();
+        for (index, opt) in config.enums.into_iter().enumerate() {
+            let attrs = opt.resolve_attrs(&config.presets);
+            enums.push(EnumAttrs {
+                attrs: to_attr(&attrs),
+                paths: opt.paths.clone(),
+                ..Default::default()
+            });
+            rules.push(Rule::new(Section::Enums, index, opt.paths, attrs));
+        }
+        let mut oneofs = Vec::new();
+        for (index, opt) in config.oneofs.into_iter().enumerate() {
+            let attrs = opt.option.resolve_attrs(&config.presets);
+            oneofs.push(EnumAttrs {
+                attrs: to_attr(&attrs),
+                paths: opt.option.paths.clone(),
+                variant_attrs: to_attr(&opt.variant_attrs),
+            });
+            let mut rule = Rule::new(Section::Oneofs, index, opt.option.paths, attrs);
+            rule.variant_attrs = opt
+                .variant_attrs
+                .iter()
+                .map(|a| format!("#[{}]", a))
+                .collect();
+            rules.push(rule);
         }
 
-        for opt in config.enums {
-            for p in opt.paths {
-                c.type_attribute(p, to_attr(&opt.attrs));
-            }
+        for (index, opt) in config.fields.into_iter().enumerate() {
+            let attrs = opt.resolve_attrs(&config.presets);
+            rules.push(Rule::new(Section::Fields, index, opt.paths, attrs));
         }
 
-        for opt in config.fields {
-            for p in opt.paths {
-                c.field_attribute(p, to_attr(&opt.attrs));
-            }
+        for (index, opt) in config.services.iter().enumerate() {
+            rules.push(opt.rule(index, &config.presets));
         }
 
-        fs::create_dir_all(&output_dir).unwrap();
-        c.out_dir(&output_dir);
+        let http_services = ServiceFlag::new(&config.services, |opt| opt.http);
+        let service_generators = service::service_generators(
+            config.services,
+            &config.presets,
+            config.compile_well_known_types,
+        );
 
         let f = |v: String| match config.base_path {
             Some(ref base_path) => base_path.join(v).to_string_lossy().to_string(),
             None => v,
         };
+        let out_dir = PathBuf::from(&output_dir);
         Self {
-            config: c,
+            base_config: Box::new(base_config),
             includes: config.includes.into_iter().map(f).collect(),
             files: config.files.into_iter().map(f).collect(),
+            compiler: config.compiler,
+            out_dir_is_env: config.output.is_none(),
+            include_file: match (&config.include_file, &config.output) {
+                (Some(include_file), _) => Some(out_dir.join(include_file)),
+                (None, Some(_)) => Some(out_dir.join("mod.rs")),
+                (None, None) => None,
+            },
+            include_file_is_default: config.include_file.is_none(),
+            file_descriptor_set_path: config.file_descriptor_set_path.map(|p| out_dir.join(p)),
+            descriptor_set: config.descriptor_set,
+            descriptor_set_source_info: config.descriptor_set_source_info,
+            json_schema_dir: config.json_schema.map(|p| out_dir.join(p)),
+            typescript_dir: config.typescript.map(|p| out_dir.join(p)),
+            openapi_dir: config.openapi.map(|p| out_dir.join(p)),
+            retain_enum_prefix: config.retain_enum_prefix,
+            out_dir,
+            format: config.format,
+            profile: config.profile,
+            enums,
+            oneofs,
+            sensitive: config.sensitive,
+            sensitive_mask: config.sensitive_mask,
+            config_paths: config
+                .config_path
+                .into_iter()
+                .chain(config.parent_paths)
+                .collect(),
+            rules,
+            service_generators: Rc::new(RefCell::new(service_generators)),
+            http_services,
+            compile_well_known_types: config.compile_well_known_types,
+            skip_debug: config.skip_debug,
+            protoc_args: config.protoc_args,
+            default_package_filename: config
+                .default_package_filename
+                .unwrap_or_else(|| "_".to_owned()),
         }
     }
 }
 
 impl Builder {
-    /// Configures the code generator for service generator
+    /// Add a service generator. It runs after the ones added before, the first being the
+    /// generators of the `services` options.
     pub fn service_generator(&mut self, service_generator: Box<dyn ServiceGenerator>) -> &mut Self {
-        self.config.service_generator(service_generator);
+        self.service_generators.borrow_mut().push(service_generator);
         self
     }
 
-    /// build protobuf with configuration
+    /// build protobuf with configuration. With `PROST_BUILD_CONFIG_CHECK=1` the generated code is
+    /// only checked, like with [`Builder::check`].
     pub fn build_protos(&mut self) {
-        self.config
-            .compile_protos(&self.files, &self.includes)
+        println!("cargo:rerun-if-env-changed={}", check::CHECK_ENV);
+        if check::is_enabled() {
+            if let Err(e) = self.check() {
+                panic!(
+                    "{}\nRerun the build without {} to update it.",
+                    e,
+                    check::CHECK_ENV
+                );
+            }
+        } else {
+            self.write();
+        }
+    }
+
+    /// Generate the code and write it to the output dir, updating stale files in place. The
+    /// output dir is created if needed.
+    pub fn write(&mut self) {
+        for (path, content) in self.generate() {
+            write_if_changed(&path, &content)
+                .unwrap_or_else(|e| panic!("Failed to write generated code. Err: {:?}", e));
+        }
+    }
+
+    /// Generate the code and compare it with the files in the output dir, without writing
+    /// anything. Fails with a unified diff of the stale files.
+    pub fn check(&mut self) -> io::Result<()> {
+        let files = self.generate();
+        let mut diff = check::diff_files(&files)?;
+        diff.push_str(&check::diff_removed(&self.stale_files(&files)?)?);
+        if diff.is_empty() {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Generated code is out of date:\n{}", diff),
+        ))
+    }
+
+    /// The files in the output dir which the config doesn't generate anymore: Rust files with the
+    /// `@generated` header, so that hand-written modules and a hand-written include file are left
+    /// alone, and the files in the dirs of the JSON Schemas, TypeScript types and OpenAPI
+    /// documents. Cargo's `OUT_DIR` isn't checked in, so it is skipped.
+    fn stale_files(&self, files: &[(PathBuf, Vec<u8>)]) -> io::Result<Vec<PathBuf>> {
+        if self.out_dir_is_env {
+            return Ok(Vec::new());
+        }
+        let mut stale = Vec::new();
+        for path in check::extra_files(&self.out_dir, Some("rs"), files)? {
+            if include::is_generated(&path)? {
+                stale.push(path);
+            }
+        }
+        let dirs = [
+            &self.json_schema_dir,
+            &self.typescript_dir,
+            &self.openapi_dir,
+        ];
+        for dir in dirs.into_iter().flatten() {
+            stale.extend(check::extra_files(dir, None, files)?);
+        }
+        Ok(stale)
+    }
+
+    /// List the paths of all messages, enums, oneofs, fields and services in the proto files, to
+    /// be used in the config.
+    pub fn list_paths(&self) -> io::Result<Vec<ProtoPath>> {
+        Ok(path::proto_paths(&self.compile_files()?))
+    }
+
+    /// Explain which attributes the build options, `sensitive` and the `profile` put on each
+    /// message, enum, oneof, field and service of the proto files, without generating code.
+    pub fn explain(&self) -> io::Result<Explanation> {
+        Ok(self.explain_files(&self.compile_files()?))
+    }
+
+    /// Explain the attributes of the build options, `sensitive` and the `profile` on the paths of
+    /// `files`.
+    fn explain_files(&self, files: &[FileDescriptorProto]) -> Explanation {
+        let paths = path::proto_paths(files);
+        let mut rules: Vec<_> = self.rules.iter().collect();
+        let sensitive = Rule {
+            section: Section::Sensitive,
+            index: 0,
+            paths: self.sensitive.clone(),
+            attrs: vec![self.sensitive_mask.serde_attr().to_owned()],
+            variant_attrs: Vec::new(),
+        };
+        rules.push(&sensitive);
+        let resolved = Sensitive::resolve(files, &self.sensitive);
+        let profile = self.profile_rules(files, &resolved);
+        rules.extend(&profile);
+        let mut explanation = explain::explain(paths, &rules);
+        // the fields of types without `Serialize` are only masked in `Debug`
+        let serde_fields = self.sensitive_serde_fields(files, &resolved);
+        for path in &mut explanation.paths {
+            if path.kind == PathKind::Field && !serde_fields.contains(&path.path) {
+                let name = sensitive.source(&path.path).rule;
+                path.attrs.retain(|attr| attr.source.rule != name);
+            }
+        }
+        explanation
+    }
+
+    fn profile_rules(&self, files: &[FileDescriptorProto], sensitive: &Sensitive) -> Vec<Rule> {
+        match &self.profile {
+            Some(profile) => profile.rules(files, &sensitive.fields),
+            None => Vec::new(),
+        }
+    }
+
+    /// The sensitive fields which get the serde attribute that masks them, i.e. those of types
+    /// which derive `Serialize`.
+    fn sensitive_serde_fields(
+        &self,
+        files: &[FileDescriptorProto],
+        sensitive: &Sensitive,
+    ) -> Vec<String> {
+        let profile = self.profile_rules(files, sensitive);
+        let rules: Vec<_> = self.rules.iter().chain(&profile).collect();
+        sensitive.serde_fields(&rules)
+    }
+
+    /// Compile the proto files, without the files they import.
+    fn compile_files(&self) -> io::Result<Vec<FileDescriptorProto>> {
+        let fds = self
+            .compiler
+            .compile(&self.includes, &self.files, &self.protoc_args)?;
+        // skip the imported files, descriptors name files relative to their include dir
+        Ok(fds
+            .file
+            .into_iter()
+            .filter(|file| {
+                self.files
+                    .iter()
+                    .any(|f| Path::new(f).ends_with(file.name()))
+            })
+            .collect())
+    }
+
+    /// The prost configuration with the attributes of the `messages` and `fields` options.
+    fn prost_config(&self) -> Config {
+        let mut config = (self.base_config)();
+        for rule in &self.rules {
+            let attrs = rule.attrs.join("\n");
+            for path in &rule.paths {
+                match rule.section {
+                    Section::Messages => config.message_attribute(path, &attrs),
+                    Section::Fields => config.field_attribute(path, &attrs),
+                    _ => continue,
+                };
+            }
+        }
+        config
+    }
+
+    /// Generate all output files with their paths.
+    fn generate(&self) -> Vec<(PathBuf, Vec<u8>)> {
+        let encoded_fds = self
+            .compiler
+            .compile_encoded(&self.includes, &self.files, &self.protoc_args)
             .unwrap_or_else(|e| panic!("Failed to compile proto files. Err: {:?}", e));
+        let fds = FileDescriptorSet::decode(&*encoded_fds)
+            .unwrap_or_else(|e| panic!("Failed to compile proto files. Err: {:?}", e));
+        self.rerun_if_changed(&fds);
+        let rules = match self.http_services.any() || self.openapi_dir.is_some() {
+            true => gateway::http_rules(&encoded_fds)
+                .unwrap_or_else(|e| panic!("Failed to decode HTTP rules. Err: {:?}", e)),
+            false => HashMap::new(),
+        };
+        let gateway = Gateway::new(
+            self.http_services.clone(),
+            &rules,
+            &fds.file,
+            self.compile_well_known_types,
+        );
+        let mut files = Vec::new();
+        if let Some(path) = &self.file_descriptor_set_path {
+            files.push((path.clone(), encoded_fds.clone()));
+        }
+        if let Some(path) = &self.descriptor_set {
+            let descriptor_set = match self.descriptor_set_source_info {
+                true => encoded_fds.clone(),
+                false => descriptor_set::strip_source_info(&encoded_fds).unwrap_or_else(|e| {
+                    panic!(
+                        "Failed to strip the source info of the descriptors. Err: {:?}",
+                        e
+                    )
+                }),
+            };
+            files.push((self.out_dir.join(path), descriptor_set));
+        }
 
-        Command::new("cargo")
-            .args(["fmt"])
-            .status()
-            .expect("cargo fmt failed");
+        let sensitive = Sensitive::resolve(&fds.file, &self.sensitive);
+        let rules: Vec<_> = self.rules.iter().collect();
+        sensitive
+            .check_conflicts(&rules)
+            .unwrap_or_else(|e| panic!("Failed to mask sensitive fields. Err: {:?}", e));
+        if self.json_schema_dir.is_some()
+            || self.typescript_dir.is_some()
+            || self.openapi_dir.is_some()
+        {
+            let schemas = self.json_schemas(&fds.file);
+            if let Some(dir) = &self.json_schema_dir {
+                for (name, schema) in json_schema::files(&schemas) {
+                    files.push((dir.join(name), schema.into_bytes()));
+                }
+            }
+            if let Some(dir) = &self.typescript_dir {
+                let ts = typescript::files(&fds.file, &schemas, &self.default_package_filename);
+                for (name, code) in ts {
+                    files.push((dir.join(name), code.into_bytes()));
+                }
+            }
+            if let Some(dir) = &self.openapi_dir {
+                let docs = openapi::files(&fds.file, &schemas, &gateway, self.schema_options())
+                    .unwrap_or_else(|e| {
+                        panic!("Failed to generate OpenAPI documents. Err: {:?}", e)
+                    });
+                for (name, doc) in docs {
+                    files.push((dir.join(name), doc.into_bytes()));
+                }
+            }
+        }
+        let mut config = self.prost_config();
+        for path in self.sensitive_serde_fields(&fds.file, &sensitive) {
+            config.field_attribute(path, self.sensitive_mask.serde_attr());
+        }
+        config.skip_debug(self.skip_debug.iter().chain(&sensitive.messages));
+        oneof::configure(&mut config, &fds.file, &self.enums, &self.oneofs);
+        if !self.service_generators.borrow().is_empty() {
+            let generators = SharedServiceGenerator(self.service_generators.clone());
+            config.service_generator(Box::new(generators));
+        }
+        if let Some(profile) = &self.profile {
+            profile.configure(&mut config, &fds.file, &sensitive.fields);
+        }
+
+        let requests = fds
+            .file
+            .iter()
+            .map(|file| {
+                (
+                    Module::from_protobuf_package_name(file.package()),
+                    file.clone(),
+                )
+            })
+            .collect();
+        let mut modules = config
+            .generate(requests)
+            .unwrap_or_else(|e| panic!("Failed to generate code. Err: {:?}", e));
+
+        for file in &fds.file {
+            let module = Module::from_protobuf_package_name(file.package());
+            if let Some(buf) = modules.get_mut(&module) {
+                buf.push_str(&sensitive.debug_impls(file, self.sensitive_mask));
+                let http = gateway
+                    .extra_code(file)
+                    .unwrap_or_else(|e| panic!("Failed to generate HTTP gateway. Err: {:?}", e));
+                buf.push_str(&http);
+                if let Some(profile) = &self.profile {
+                    buf.push_str(&profile.extra_code(file));
+                }
+            }
+        }
+
+        for (module, buf) in modules.iter_mut() {
+            let code = std::mem::take(buf);
+            let name = module.to_file_name_or(&self.default_package_filename);
+            *buf = format!("{}\n\n{}", include::HEADER, self.format.format(&name, code));
+        }
+
+        self.output_files(&mut files, modules)
+            .unwrap_or_else(|e| panic!("Failed to generate code. Err: {:?}", e));
+        files
+    }
+}
+
+impl Builder {
+    /// Tell cargo to rerun the build script when the config or any compiled proto file changes.
+    fn rerun_if_changed(&self, fds: &FileDescriptorSet) {
+        // cargo sets OUT_DIR for build scripts only, e.g. not for the command line tool
+        if std::env::var_os("OUT_DIR").is_none() {
+            return;
+        }
+        for path in &self.config_paths {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+        for file in &fds.file {
+            // descriptors name the files relative to the include dir they were found in
+            let found = self
+                .includes
+                .iter()
+                .map(|include| Path::new(include).join(file.name()))
+                .find(|path| path.is_file());
+            if let Some(path) = found {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+    }
+
+    /// The JSON Schemas of the messages and enums, with the attributes of the build options,
+    /// `sensitive` and the profile.
+    fn json_schemas(&self, files: &[FileDescriptorProto]) -> Vec<Schema> {
+        let mut attrs: HashMap<String, Vec<String>> = HashMap::new();
+        for path in self.explain_files(files).paths {
+            let path_attrs = attrs.entry(path.path).or_default();
+            path_attrs.extend(path.attrs.into_iter().map(|a| a.attr));
+        }
+        json_schema::schemas(files, &attrs, self.schema_options())
+    }
+
+    fn schema_options(&self) -> json_schema::Options {
+        json_schema::Options {
+            retain_enum_prefix: self.retain_enum_prefix,
+            compile_well_known_types: self.compile_well_known_types,
+        }
+    }
+
+    /// Add the files of the generated modules and the include file to `files`.
+    fn output_files(
+        &self,
+        files: &mut Vec<(PathBuf, Vec<u8>)>,
+        modules: HashMap<Module, String>,
+    ) -> io::Result<()> {
+        if let Some(path) = &self.include_file {
+            if self.include_file_is_default && !include::is_generated(path)? {
+                println!(
+                    "cargo:warning=Skipped generating {}, it is not generated by prost-build-config",
+                    path.display()
+                );
+            } else {
+                let mut content = include::include_file(
+                    modules.keys(),
+                    &self.default_package_filename,
+                    self.out_dir_is_env,
+                );
+                if let Some(descriptor_set) = &self.descriptor_set {
+                    content.push_str(&descriptor_set::constant(
+                        descriptor_set,
+                        self.out_dir_is_env,
+                    ));
+                }
+                files.push((path.clone(), content.into_bytes()));
+            }
+        }
+        let mut modules: Vec<_> = modules.into_iter().collect();
+        modules.sort();
+        for (module, content) in modules {
+            let path = self
+                .out_dir
+                .join(module.to_file_name_or(&self.default_package_filename));
+            files.push((path, content.into_bytes()));
+        }
+        Ok(())
     }
 }
 
+/// Write `content` to `path` unless it is already there, so that cargo doesn't rebuild.
+fn write_if_changed(path: &Path, content: &[u8]) -> io::Result<()> {
+    if fs::read(path).map_or(true, |prev| prev != content) {
+        // e.g. the dir of the JSON Schemas
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, content)?;
+    }
+    Ok(())
+}
+
 fn to_attr(attrs: &[String]) -> String {
     attrs
         .iter()
@@ -188,10 +808,160 @@ mod tests {
     }
     #[test]
     fn generate_serde_validator_supported_code() {
-        let content = include_str!("../examples/build_config.yml");
-        let config: BuildConfig = serde_yaml::from_str(content).unwrap();
+        let config = BuildConfig::from_path("examples/build_config.yml").unwrap();
+        assert_eq!(
+            config.config_path.as_deref(),
+            Some(Path::new("examples/build_config.yml"))
+        );
         Builder::from(config)
             .service_generator(Box::new(MyServiceGen))
             .build_protos();
     }
+
+    #[test]
+    fn prost_build_options_should_be_applied() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = BuildConfig {
+            includes: vec!["examples".to_owned()],
+            files: vec!["examples/hello.proto".to_owned()],
+            output: Some(dir.path().to_string_lossy().to_string()),
+            boxed: vec!["Hello.world".to_owned()],
+            skip_debug: vec!["helloworld.World".to_owned()],
+            disable_comments: vec![".".to_owned()],
+            include_file: Some("_includes.rs".to_owned()),
+            file_descriptor_set_path: Some("hello.bin".to_owned()),
+            descriptor_set: Some("descriptor_set.bin".to_owned()),
+            descriptor_set_source_info: true,
+            enable_type_names: true,
+            ..Default::default()
+        };
+        Builder::from(config).build_protos();
+
+        let code = fs::read_to_string(dir.path().join("helloworld.rs")).unwrap();
+        assert!(code.contains("::prost::alloc::boxed::Box<World>"));
+        assert!(code.contains("#[prost(skip_debug)]\npub struct World"));
+        assert!(code.contains("impl ::prost::Name for Hello"));
+        assert!(!code.contains("FILE_DESCRIPTOR_SET"));
+        let include = fs::read_to_string(dir.path().join("_includes.rs")).unwrap();
+        assert!(include.contains("pub mod helloworld {\n    include!(\"helloworld.rs\");\n}\n"));
+        assert!(include.contains(
+            "pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!(\"descriptor_set.bin\");"
+        ));
+        assert!(!dir.path().join("mod.rs").exists());
+        let fds = FileDescriptorSet::decode(&*fs::read(dir.path().join("hello.bin")).unwrap());
+        assert!(fds.unwrap().file.iter().any(|f| f.name() == "hello.proto"));
+        let fds = fs::read(dir.path().join("descriptor_set.bin")).unwrap();
+        let fds = FileDescriptorSet::decode(&*fds).unwrap();
+        assert!(fds.file.iter().all(|f| f.source_code_info.is_some()));
+    }
+
+    #[test]
+    fn sensitive_fields_without_serde_should_only_be_masked_in_debug() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = BuildConfig {
+            includes: vec!["examples".to_owned()],
+            files: vec!["examples/hello.proto".to_owned()],
+            output: Some(dir.path().to_string_lossy().to_string()),
+            sensitive: vec!["Hello.msg".to_owned()],
+            ..Default::default()
+        };
+        let mut builder = Builder::from(config);
+        builder.build_protos();
+
+        let code = fs::read_to_string(dir.path().join("helloworld.rs")).unwrap();
+        assert!(code.contains("&::prost_helper::Redacted(&self.msg)"));
+        assert!(!code.contains("#[serde("));
+        let explanation = builder.explain().unwrap();
+        let msg = explanation
+            .paths
+            .iter()
+            .find(|p| p.path == ".helloworld.Hello.msg")
+            .unwrap();
+        assert!(msg.attrs.is_empty());
+    }
+
+    #[test]
+    fn only_write_should_create_the_output_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let out_dir = dir.path().join("src/pb");
+        let config = BuildConfig {
+            includes: vec!["examples".to_owned()],
+            files: vec!["examples/hello.proto".to_owned()],
+            output: Some(out_dir.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let mut builder = Builder::from(config);
+        assert!(!builder.list_paths().unwrap().is_empty());
+        builder.explain().unwrap();
+        assert!(builder.check().is_err());
+        assert!(!out_dir.exists());
+
+        builder.write();
+        assert!(out_dir.join("helloworld.rs").exists());
+        builder.check().unwrap();
+    }
+
+    #[test]
+    fn mod_rs_should_be_generated_for_output_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = || BuildConfig {
+            includes: vec!["examples".to_owned()],
+            files: vec!["examples/hello.proto".to_owned()],
+            output: Some(dir.path().to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        Builder::from(config()).build_protos();
+        let mod_rs = fs::read_to_string(dir.path().join("mod.rs")).unwrap();
+        assert!(mod_rs.contains("/// Protobuf package `helloworld`.\npub mod helloworld {"));
+
+        // a hand-written mod.rs is left alone
+        fs::write(dir.path().join("mod.rs"), "pub mod helloworld;\n").unwrap();
+        Builder::from(config()).build_protos();
+        let mod_rs = fs::read_to_string(dir.path().join("mod.rs")).unwrap();
+        assert_eq!(mod_rs, "pub mod helloworld;\n");
+    }
+
+    #[test]
+    fn check_should_report_stale_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = || BuildConfig {
+            includes: vec!["examples".to_owned()],
+            files: vec!["examples/hello.proto".to_owned()],
+            output: Some(dir.path().to_string_lossy().to_string()),
+            messages: vec![BuildOption {
+                paths: vec![".".to_owned()],
+                attrs: vec!["derive(serde::Serialize)".to_owned()],
+                ..Default::default()
+            }],
+            sensitive: vec!["Hello.msg".to_owned()],
+            ..Default::default()
+        };
+        // the same builder may run several times without piling up attributes
+        let mut builder = Builder::from(config());
+        assert!(builder.check().is_err());
+        builder.write();
+        builder.check().unwrap();
+        Builder::from(config()).check().unwrap();
+
+        // hand-written modules next to the generated ones aren't stale
+        fs::write(dir.path().join("helpers.rs"), "pub struct Helper;\n").unwrap();
+        Builder::from(config()).check().unwrap();
+
+        let stale = dir.path().join("removed.rs");
+        let code = format!("{}\n\npub struct Removed;\n", include::HEADER);
+        fs::write(&stale, code).unwrap();
+        let err = Builder::from(config()).check().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("+++ /dev/null\n@@ -1,3 +0,0 @@\n-// This file is @generated"));
+        assert!(!err.to_string().contains("helpers.rs"));
+        fs::remove_file(&stale).unwrap();
+
+        let path = dir.path().join("helloworld.rs");
+        let code = fs::read_to_string(&path).unwrap();
+        fs::write(&path, code.replace("pub msg:", "pub message:")).unwrap();
+        let err = Builder::from(config()).check().unwrap_err();
+        assert!(err.to_string().contains("-    pub message:"));
+        assert!(err.to_string().contains("+    pub msg:"));
+    }
 }
@@ -0,0 +1,292 @@
+//! Loading of the build config from YAML, TOML or JSON files and from Cargo.toml metadata.
+//!
+//! A config may `extends` another config file. The parent is loaded first and the child is
+//! merged into it:
+//!
+//! - tables (e.g. `presets`) are merged key by key, the child's entries replace the parent's;
+//! - lists (e.g. `files`, `messages`) are concatenated, the parent's entries first;
+//! - any other value set in the child replaces the parent's.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::BuildConfig;
+
+/// name of the table in `[package.metadata]` which holds the config
+const METADATA_KEY: &str = "prost-build-config";
+
+impl BuildConfig {
+    /// Load the config from `path`. The format is detected from the extension: `.yml`/`.yaml`,
+    /// `.toml` or `.json`.
+    ///
+    /// Relative paths in the config are resolved against the directory of the config file, and
+    /// the path is remembered, so that cargo reruns the build script when the config changes.
+    pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut paths = Vec::new();
+        let value = load(path, &mut paths)?;
+        Self::from_value(path, value, paths)
+    }
+
+    /// Load the config from the `[package.metadata.prost-build-config]` table of the Cargo.toml
+    /// of the crate being built.
+    pub fn from_cargo_metadata() -> io::Result<Self> {
+        let dir = env::var_os("CARGO_MANIFEST_DIR").ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "CARGO_MANIFEST_DIR is not set")
+        })?;
+        Self::from_manifest(Path::new(&dir).join("Cargo.toml"))
+    }
+
+    fn from_manifest(path: PathBuf) -> io::Result<Self> {
+        let content = fs::read_to_string(&path)?;
+        let manifest: Value = parse(&path, toml::from_str(&content))?;
+        let metadata = manifest
+            .pointer(&format!("/package/metadata/{}", METADATA_KEY))
+            .cloned()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no [package.metadata.{}] in {:?}", METADATA_KEY, path),
+                )
+            })?;
+        let mut paths = vec![path.clone()];
+        let value = resolve(&path, metadata, &mut paths)?;
+        Self::from_value(&path, value, paths)
+    }
+
+    fn from_value(path: &Path, value: Value, mut paths: Vec<PathBuf>) -> io::Result<Self> {
+        let mut config: Self = parse(path, serde_json::from_value(value))?;
+        config.config_path = Some(paths.remove(0));
+        config.parent_paths = paths;
+        config
+            .check_presets()
+            .map_err(|e| io::Error::new(e.kind(), format!("invalid config {:?}: {}", path, e)))?;
+        Ok(config)
+    }
+}
+
+/// Load the config file at `path` with the configs it extends into a single value.
+fn load(path: &Path, paths: &mut Vec<PathBuf>) -> io::Result<Value> {
+    let canonical = fs::canonicalize(path)?;
+    if paths
+        .iter()
+        .any(|p| fs::canonicalize(p).ok().as_ref() == Some(&canonical))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("config {:?} extends itself", path),
+        ));
+    }
+    paths.push(path.to_owned());
+
+    let content = fs::read_to_string(path)?;
+    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    let value = match ext {
+        "yml" | "yaml" => parse(path, serde_yaml::from_str(&content))?,
+        "toml" => parse(path, toml::from_str(&content))?,
+        "json" => parse(path, serde_json::from_str(&content))?,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported config format {:?}", path),
+            ))
+        }
+    };
+    resolve(path, value, paths)
+}
+
+/// Resolve the relative paths in the config loaded from `path`, and merge it into the config it
+/// extends, if any.
+fn resolve(path: &Path, value: Value, paths: &mut Vec<PathBuf>) -> io::Result<Value> {
+    let mut config = match value {
+        Value::Object(config) => config,
+        Value::Null => Map::new(),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid config {:?}: expected a table", path),
+            ))
+        }
+    };
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    resolve_paths(&mut config, dir);
+
+    match config.remove("extends") {
+        Some(Value::String(parent)) => {
+            let parent = load(&dir.join(parent), paths)?;
+            Ok(merge(parent, Value::Object(config)))
+        }
+        None | Some(Value::Null) => Ok(Value::Object(config)),
+        Some(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid config {:?}: `extends` must be a path", path),
+        )),
+    }
+}
+
+/// Make `includes`, `files` and `output` relative to `dir` instead of the config file, so that
+/// configs from different dirs can be merged.
+fn resolve_paths(config: &mut Map<String, Value>, dir: &Path) {
+    let base_path = match config.remove("base_path") {
+        Some(Value::String(base_path)) => dir.join(base_path),
+        _ => dir.to_owned(),
+    };
+    for key in ["includes", "files"] {
+        if let Some(Value::Array(items)) = config.get_mut(key) {
+            for item in items {
+                if let Value::String(s) = item {
+                    *s = base_path.join(&*s).to_string_lossy().to_string();
+                }
+            }
+        }
+    }
+    if let Some(Value::String(output)) = config.get_mut("output") {
+        *output = dir.join(&*output).to_string_lossy().to_string();
+    }
+}
+
+fn merge(parent: Value, child: Value) -> Value {
+    let (Value::Object(mut parent), Value::Object(child)) = (parent, child) else {
+        unreachable!("configs are resolved into tables");
+    };
+    for (key, value) in child {
+        let value = match (parent.remove(&key), value) {
+            (Some(Value::Object(mut prev)), Value::Object(table)) => {
+                prev.extend(table);
+                Value::Object(prev)
+            }
+            (Some(Value::Array(mut prev)), Value::Array(list)) => {
+                prev.extend(list);
+                Value::Array(prev)
+            }
+            (_, value) => value,
+        };
+        parent.insert(key, value);
+    }
+    Value::Object(parent)
+}
+
+fn parse<T, E>(path: &Path, result: Result<T, E>) -> io::Result<T>
+where
+    T: DeserializeOwned,
+    E: std::fmt::Display,
+{
+    result.map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid config {:?}: {}", path, e),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Format, SensitiveMask};
+
+    fn join(dir: &Path, path: &str) -> String {
+        dir.join(path).to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn config_should_be_loaded_from_all_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("build.yml", "files: [hello.proto]\noutput: pb\n"),
+            ("build.toml", "files = [\"hello.proto\"]\noutput = \"pb\"\n"),
+            (
+                "build.json",
+                r#"{"files": ["hello.proto"], "output": "pb"}"#,
+            ),
+        ];
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            let config = BuildConfig::from_path(&path).unwrap();
+            assert_eq!(config.files, [join(dir.path(), "hello.proto")]);
+            assert_eq!(config.output, Some(join(dir.path(), "pb")));
+            assert_eq!(config.config_path, Some(path));
+        }
+
+        let path = dir.path().join("build.ini");
+        fs::write(&path, "").unwrap();
+        let err = BuildConfig::from_path(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn config_should_be_loaded_from_cargo_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Cargo.toml");
+        let manifest = r#"
+[package]
+name = "hello"
+
+[package.metadata.prost-build-config]
+base_path = "protos"
+files = ["hello.proto"]
+"#;
+        fs::write(&path, manifest).unwrap();
+        let config = BuildConfig::from_manifest(path.clone()).unwrap();
+        assert_eq!(config.files, [join(dir.path(), "protos/hello.proto")]);
+        assert_eq!(config.config_path, Some(path.clone()));
+
+        fs::write(&path, "[package]\nname = \"hello\"\n").unwrap();
+        let err = BuildConfig::from_manifest(path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn config_should_be_merged_with_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("shared")).unwrap();
+        let parent = dir.path().join("shared/prost.yml");
+        let shared = r#"
+includes: [protos]
+format: rustfmt
+sensitive_mask: hash
+presets:
+  message: [derive(Eq)]
+  enum: [derive(Hash)]
+messages:
+  - paths: [.]
+    presets: [serde]
+"#;
+        fs::write(&parent, shared).unwrap();
+        let path = dir.path().join("build.toml");
+        let child = r#"
+extends = "shared/prost.yml"
+files = ["hello.proto"]
+format = "none"
+
+[presets]
+message = ["derive(PartialOrd)"]
+
+[[messages]]
+paths = ["Hello"]
+attrs = ["serde(default)"]
+"#;
+        fs::write(&path, child).unwrap();
+
+        let config = BuildConfig::from_path(&path).unwrap();
+        assert_eq!(config.includes, [join(dir.path(), "shared/protos")]);
+        assert_eq!(config.files, [join(dir.path(), "hello.proto")]);
+        assert_eq!(config.format, Format::None);
+        assert_eq!(config.sensitive_mask, SensitiveMask::Hash);
+        assert_eq!(config.presets["message"], ["derive(PartialOrd)"]);
+        assert_eq!(config.presets["enum"], ["derive(Hash)"]);
+        let paths: Vec<_> = config.messages.iter().map(|m| &m.paths[0]).collect();
+        assert_eq!(paths, [".", "Hello"]);
+        assert_eq!(config.config_path, Some(path));
+        assert_eq!(config.parent_paths, [dir.path().join("shared/prost.yml")]);
+
+        fs::write(&parent, "extends: ../build.toml\n").unwrap();
+        let err = BuildConfig::from_path(dir.path().join("build.toml")).unwrap_err();
+        assert!(err.to_string().contains("extends itself"));
+    }
+}
@@ -0,0 +1,200 @@
+//! `prost-build-config` command line tool, to generate the code outside of a build script, e.g.
+//! in a Makefile step.
+
+use std::{
+    env,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use prost_build_config::{BuildConfig, Builder};
+
+const USAGE: &str = "\
+Usage: prost-build-config [OPTIONS] <COMMAND>
+
+Commands:
+  generate      generate the code into the output dir
+  check         fail with a diff if the generated code in the output dir is stale
+  print-config  print the effective config, after merging the configs it extends
+  list-paths    list the paths of all messages, enums, oneofs, fields and services in the proto files
+  explain       show which attributes the config puts on which paths, and the unmatched rules
+
+Options:
+  -c, --config <PATH>  config file (.yml, .toml or .json) [default: build_config.yml]
+  -o, --output <DIR>   dir for generated code, overrides `output` in the config
+  -h, --help           print help
+";
+
+/// exit code if the generated code is out of date
+const EXIT_STALE: u8 = 1;
+/// exit code for invalid arguments
+const EXIT_USAGE: u8 = 2;
+/// exit code if the config can't be loaded
+const EXIT_CONFIG: u8 = 3;
+/// exit code if the proto files can't be compiled or the code can't be generated
+const EXIT_BUILD: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Generate,
+    Check,
+    PrintConfig,
+    ListPaths,
+    Explain,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Args {
+    command: Command,
+    config: PathBuf,
+    output: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let mut config = match BuildConfig::from_path(&args.config) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: failed to load config {:?}: {}", args.config, e);
+            return ExitCode::from(EXIT_CONFIG);
+        }
+    };
+    if args.output.is_some() {
+        config.output = args.output;
+    }
+
+    match args.command {
+        Command::PrintConfig => match serde_yaml::to_string(&config) {
+            Ok(yaml) => print!("{}", yaml),
+            Err(e) => {
+                eprintln!("error: failed to print config: {}", e);
+                return ExitCode::from(EXIT_CONFIG);
+            }
+        },
+        Command::ListPaths => match run_builder(|| Builder::from(config).list_paths()) {
+            Some(Ok(paths)) => paths
+                .iter()
+                .for_each(|p| println!("{:<8} {}", p.kind, p.path)),
+            Some(Err(e)) => {
+                eprintln!("error: failed to compile proto files: {}", e);
+                return ExitCode::from(EXIT_BUILD);
+            }
+            None => return ExitCode::from(EXIT_BUILD),
+        },
+        Command::Explain => match run_builder(|| Builder::from(config).explain()) {
+            Some(Ok(explanation)) => print!("{}", explanation),
+            Some(Err(e)) => {
+                eprintln!("error: failed to compile proto files: {}", e);
+                return ExitCode::from(EXIT_BUILD);
+            }
+            None => return ExitCode::from(EXIT_BUILD),
+        },
+        Command::Generate => {
+            if run_builder(|| Builder::from(config).write()).is_none() {
+                return ExitCode::from(EXIT_BUILD);
+            }
+        }
+        Command::Check => match run_builder(|| Builder::from(config).check()) {
+            Some(Ok(())) => {}
+            Some(Err(e)) => {
+                eprintln!("{}", e);
+                return ExitCode::from(EXIT_STALE);
+            }
+            None => return ExitCode::from(EXIT_BUILD),
+        },
+    }
+    ExitCode::SUCCESS
+}
+
+/// Run `f` with the builder, `None` if it panics. The builder panics on errors, as a build
+/// script would, and the panic message has already been printed then.
+fn run_builder<T>(f: impl FnOnce() -> T) -> Option<T> {
+    panic::catch_unwind(AssertUnwindSafe(f)).ok()
+}
+
+/// Parse the arguments, `None` if help is requested.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut command = None;
+    let mut config = PathBuf::from("build_config.yml");
+    let mut output = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // both `--config path` and `--config=path` are accepted
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with('-') => (name.to_owned(), Some(value.into())),
+            _ => (arg, None),
+        };
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("missing value for {}", name))
+        };
+        match name.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-c" | "--config" => config = PathBuf::from(value()?),
+            "-o" | "--output" => output = Some(value()?),
+            cmd if !cmd.starts_with('-') && command.is_none() => {
+                command = Some(match cmd {
+                    "generate" => Command::Generate,
+                    "check" => Command::Check,
+                    "print-config" => Command::PrintConfig,
+                    "list-paths" => Command::ListPaths,
+                    "explain" => Command::Explain,
+                    _ => return Err(format!("unknown command {:?}", cmd)),
+                });
+            }
+            _ => return Err(format!("unexpected argument {:?}", name)),
+        }
+    }
+
+    let command = command.ok_or("missing command")?;
+    Ok(Some(Args {
+        command,
+        config,
+        output,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn args_should_be_parsed() {
+        assert_eq!(
+            parse(&["check", "--config", "protos/build.toml", "-o=src/pb"]),
+            Ok(Some(Args {
+                command: Command::Check,
+                config: PathBuf::from("protos/build.toml"),
+                output: Some("src/pb".to_owned()),
+            }))
+        );
+        assert_eq!(
+            parse(&["list-paths"]).unwrap().unwrap().config,
+            PathBuf::from("build_config.yml")
+        );
+        assert_eq!(parse(&["generate", "--help"]), Ok(None));
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["build"]).is_err());
+        assert!(parse(&["generate", "--config"]).is_err());
+        assert!(parse(&["generate", "check"]).is_err());
+    }
+}
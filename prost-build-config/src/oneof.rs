@@ -0,0 +1,178 @@
+//! Attributes for enums and oneofs. prost generates both as Rust enums and its `enum_attribute`
+//! applies to both, so the configured paths are resolved against the descriptors to tell them
+//! apart.
+
+use prost_build::Config;
+use prost_types::{DescriptorProto, FileDescriptorProto};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    path::{is_match, package_prefix, real_oneof},
+    BuildOption,
+};
+
+/// Build options for oneofs.
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(default)]
+pub struct OneofOption {
+    /// paths of the oneofs (e.g. `helloworld.Hello.choice`) and the attributes of their enums,
+    /// for example: `serde(tag = "type")` or `serde(untagged)`
+    #[serde(flatten)]
+    pub option: BuildOption,
+    /// extra attributes to put on every variant of the oneofs
+    pub variant_attrs: Vec<String>,
+}
+
+/// Attributes of an `enums` or `oneofs` option, ready to be applied.
+#[derive(Debug, Default)]
+pub(crate) struct EnumAttrs {
+    pub paths: Vec<String>,
+    /// attributes of the enum
+    pub attrs: String,
+    /// attributes of every variant, only used for oneofs
+    pub variant_attrs: String,
+}
+
+/// Fully-qualified names of the enums and oneofs in the compiled files.
+#[derive(Debug, Default)]
+struct Enums {
+    enums: Vec<String>,
+    /// oneofs with the paths of their variants
+    oneofs: Vec<(String, Vec<String>)>,
+}
+
+/// Apply the `enums` attributes to the matching enums and the `oneofs` attributes to the
+/// matching oneofs only.
+pub(crate) fn configure(
+    config: &mut Config,
+    files: &[FileDescriptorProto],
+    enums: &[EnumAttrs],
+    oneofs: &[EnumAttrs],
+) {
+    if enums.is_empty() && oneofs.is_empty() {
+        return;
+    }
+    let mut found = Enums::default();
+    for file in files {
+        let package = package_prefix(file);
+        found.enums.extend(
+            file.enum_type
+                .iter()
+                .map(|e| format!("{}.{}", package, e.name())),
+        );
+        for msg in &file.message_type {
+            found.collect(&package, msg);
+        }
+    }
+
+    for opt in enums {
+        for fq_name in found.enums.iter().filter(|name| opt.matches(name)) {
+            config.enum_attribute(fq_name, &opt.attrs);
+        }
+    }
+    for opt in oneofs {
+        for (fq_name, variants) in found.oneofs.iter().filter(|(name, _)| opt.matches(name)) {
+            config.enum_attribute(fq_name, &opt.attrs);
+            if !opt.variant_attrs.is_empty() {
+                for variant in variants {
+                    config.field_attribute(variant, &opt.variant_attrs);
+                }
+            }
+        }
+    }
+}
+
+impl EnumAttrs {
+    fn matches(&self, fq_name: &str) -> bool {
+        self.paths.iter().any(|p| is_match(p, fq_name))
+    }
+}
+
+impl Enums {
+    fn collect(&mut self, parent: &str, msg: &DescriptorProto) {
+        let fq_name = format!("{}.{}", parent, msg.name());
+        self.enums.extend(
+            msg.enum_type
+                .iter()
+                .map(|e| format!("{}.{}", fq_name, e.name())),
+        );
+        for (idx, oneof) in msg.oneof_decl.iter().enumerate() {
+            let fq_oneof = format!("{}.{}", fq_name, oneof.name());
+            let variants: Vec<_> = msg
+                .field
+                .iter()
+                .filter(|f| real_oneof(msg, f.oneof_index, f.proto3_optional()).is_some())
+                .filter(|f| f.oneof_index == Some(idx as i32))
+                .map(|f| format!("{}.{}", fq_oneof, f.name()))
+                .collect();
+            if !variants.is_empty() {
+                self.oneofs.push((fq_oneof, variants));
+            }
+        }
+        for nested in &msg.nested_type {
+            self.collect(&fq_name, nested);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::{EnumDescriptorProto, FieldDescriptorProto, OneofDescriptorProto};
+
+    fn field(name: &str, oneof_index: Option<i32>, proto3_optional: bool) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            oneof_index,
+            proto3_optional: Some(proto3_optional),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn enums_and_oneofs_should_be_told_apart() {
+        let msg = DescriptorProto {
+            name: Some("Hello".to_owned()),
+            field: vec![
+                field("text", Some(0), false),
+                field("number", Some(0), false),
+                field("maybe", Some(1), true),
+            ],
+            oneof_decl: vec![
+                OneofDescriptorProto {
+                    name: Some("choice".to_owned()),
+                    ..Default::default()
+                },
+                OneofDescriptorProto {
+                    name: Some("_maybe".to_owned()),
+                    ..Default::default()
+                },
+            ],
+            enum_type: vec![EnumDescriptorProto {
+                name: Some("Kind".to_owned()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut found = Enums::default();
+        found.collect(".helloworld", &msg);
+        assert_eq!(found.enums, [".helloworld.Hello.Kind"]);
+        assert_eq!(
+            found.oneofs,
+            [(
+                ".helloworld.Hello.choice".to_owned(),
+                vec![
+                    ".helloworld.Hello.choice.text".to_owned(),
+                    ".helloworld.Hello.choice.number".to_owned()
+                ]
+            )]
+        );
+
+        let opt = EnumAttrs {
+            paths: vec![".helloworld.Hello".to_owned()],
+            ..Default::default()
+        };
+        assert!(opt.matches(".helloworld.Hello.choice"));
+        assert!(!opt.matches(".helloworld.World"));
+    }
+}
@@ -0,0 +1,531 @@
+//! OpenAPI 3.1 documents of the services. The operations follow the `google.api.http` options of
+//! the methods like the HTTP gateway, methods without options are `POST /<package>.<Service>/<Method>`
+//! with the request as body, and the messages are components with the JSON Schemas of
+//! `json_schema`, so that they describe the JSON of the serde attributes. Comments of the
+//! services, methods, messages and fields become descriptions.
+//!
+//! Streaming methods are skipped.
+
+use std::io;
+
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    FieldDescriptorProto, FileDescriptorProto, MethodDescriptorProto,
+};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    gateway::{pattern, FieldPath, Gateway, HttpRule},
+    json_schema::{ref_name, Context, Options, Schema},
+    path::package_prefix,
+};
+
+const VERSION: &str = "version not set";
+
+/// The field path of a path variable, with the pattern of its value.
+type Var = (String, Option<String>);
+
+struct Document<'a> {
+    gateway: &'a Gateway<'a>,
+    types: Context<'a>,
+}
+
+/// A `<package>.<Service>.openapi.json` document for every service in `files`, whose messages
+/// have the JSON Schemas `schemas`.
+pub(crate) fn files(
+    files: &[FileDescriptorProto],
+    schemas: &[Schema],
+    gateway: &Gateway,
+    options: Options,
+) -> io::Result<Vec<(String, String)>> {
+    let doc = Document {
+        gateway,
+        types: Context::new(files, &Default::default(), options),
+    };
+    let mut documents = Vec::new();
+    for file in files {
+        let package = package_prefix(file);
+        for service in &file.service {
+            let fq_service = format!("{}.{}", package, service.name());
+            let mut paths = Map::new();
+            for method in &service.method {
+                if method.client_streaming() || method.server_streaming() {
+                    continue;
+                }
+                doc.append_operations(&mut paths, &fq_service, service.name(), method)?;
+            }
+            let mut paths = Value::Object(paths);
+            let components = components(&mut paths, schemas);
+
+            let mut info = Map::new();
+            info.insert(
+                "title".to_owned(),
+                json!(fq_service.trim_start_matches('.')),
+            );
+            if let Some(comment) = doc.types.comment(&fq_service) {
+                info.insert("description".to_owned(), json!(comment));
+            }
+            info.insert("version".to_owned(), json!(VERSION));
+            let document = json!({
+                "openapi": "3.1.0",
+                "info": info,
+                "paths": paths,
+                "components": { "schemas": components },
+            });
+            let json = serde_json::to_string_pretty(&document).expect("document is valid JSON");
+            let name = format!("{}.openapi.json", fq_service.trim_start_matches('.'));
+            documents.push((name, json + "\n"));
+        }
+    }
+    Ok(documents)
+}
+
+impl Document<'_> {
+    /// Add the operations of the bindings of a method to `paths`.
+    fn append_operations(
+        &self,
+        paths: &mut Map<String, Value>,
+        fq_service: &str,
+        service: &str,
+        method: &MethodDescriptorProto,
+    ) -> io::Result<()> {
+        let fq_method = format!("{}.{}", fq_service, method.name());
+        let operation_id = format!("{}_{}", service, method.name());
+        let Some(rule) = self.gateway.rule(&fq_method) else {
+            let path = format!("/{}/{}", fq_service.trim_start_matches('.'), method.name());
+            let operation = self.operation(
+                &operation_id,
+                &fq_method,
+                Vec::new(),
+                Some(self.types.message_schema(method.input_type())),
+                self.types.message_schema(method.output_type()),
+            );
+            paths.insert(path, json!({ "post": operation }));
+            return Ok(());
+        };
+
+        let bindings = std::iter::once(rule).chain(&rule.additional_bindings);
+        for (index, rule) in bindings.enumerate() {
+            let operation_id = match index {
+                0 => operation_id.clone(),
+                index => format!("{}{}", operation_id, index + 1),
+            };
+            let (http_method, path, operation) = self
+                .binding(&operation_id, &fq_method, method, rule)
+                .map_err(|e| invalid(format!("HTTP rule of {}: {}", fq_method, e)))?;
+            let path_item = paths.entry(path).or_insert_with(|| json!({}));
+            path_item[http_method] = operation;
+        }
+        Ok(())
+    }
+
+    /// The HTTP method, the path and the operation of a binding of a method.
+    fn binding(
+        &self,
+        operation_id: &str,
+        fq_method: &str,
+        method: &MethodDescriptorProto,
+        rule: &HttpRule,
+    ) -> Result<(&'static str, String, Value), String> {
+        let request = self
+            .gateway
+            .message(method.input_type())
+            .map_err(|e| e.to_string())?;
+        let response = self
+            .gateway
+            .message(method.output_type())
+            .map_err(|e| e.to_string())?;
+        let (http_method, template) = pattern(rule)?;
+        let (path, vars) = path_template(template)?;
+
+        let mut parameters = Vec::new();
+        let mut bound = Vec::new();
+        for (field_path, value_pattern) in vars {
+            let field = self.gateway.field_path(request, &field_path)?;
+            let mut schema = param_schema(last(&field));
+            if let Some(value_pattern) = value_pattern {
+                schema["pattern"] = json!(value_pattern);
+            }
+            parameters.push(self.parameter(&field, method.input_type(), "path", schema));
+            bound.push(field_path);
+        }
+
+        let body = match rule.body.as_str() {
+            "" => None,
+            "*" => Some(self.types.message_schema(method.input_type())),
+            field_path => {
+                let field = self.gateway.field_path(request, field_path)?;
+                bound.push(field_path.to_owned());
+                let parent = parent(&field, method.input_type());
+                Some(self.types.type_schema(parent, last(&field)))
+            }
+        };
+        if rule.body != "*" {
+            for field in self.gateway.query_fields(request, &bound) {
+                let mut schema = param_schema(last(&field));
+                // repeated fields are read from every occurrence of the parameter
+                if last(&field).label() == Label::Repeated {
+                    schema = json!({ "type": "array", "items": schema });
+                }
+                parameters.push(self.parameter(&field, method.input_type(), "query", schema));
+            }
+        }
+
+        let response = match rule.response_body.as_str() {
+            "" => self.types.message_schema(method.output_type()),
+            field_path => {
+                let field = self.gateway.field_path(response, field_path)?;
+                let parent = parent(&field, method.output_type());
+                self.types.type_schema(parent, last(&field))
+            }
+        };
+        let operation = self.operation(operation_id, fq_method, parameters, body, response);
+        Ok((http_method, path, operation))
+    }
+
+    fn operation(
+        &self,
+        operation_id: &str,
+        fq_method: &str,
+        parameters: Vec<Value>,
+        body: Option<Value>,
+        response: Value,
+    ) -> Value {
+        let mut operation = Map::new();
+        operation.insert("operationId".to_owned(), json!(operation_id));
+        if let Some(comment) = self.types.comment(fq_method) {
+            operation.insert("description".to_owned(), json!(comment));
+        }
+        if !parameters.is_empty() {
+            operation.insert("parameters".to_owned(), Value::Array(parameters));
+        }
+        if let Some(body) = body {
+            let request_body = json!({
+                "required": true,
+                "content": { "application/json": { "schema": body } },
+            });
+            operation.insert("requestBody".to_owned(), request_body);
+        }
+        let responses = json!({
+            "200": {
+                "description": "OK",
+                "content": { "application/json": { "schema": response } },
+            }
+        });
+        operation.insert("responses".to_owned(), responses);
+        Value::Object(operation)
+    }
+
+    /// A path or query parameter bound to `field` of the request message `request`.
+    fn parameter(&self, field: &FieldPath, request: &str, kind: &str, schema: Value) -> Value {
+        let mut parameter = Map::new();
+        parameter.insert("name".to_owned(), json!(field.path));
+        parameter.insert("in".to_owned(), json!(kind));
+        if kind == "path" {
+            parameter.insert("required".to_owned(), json!(true));
+        }
+        let fq_field = format!("{}.{}", parent(field, request), last(field).name());
+        if let Some(comment) = self.types.comment(&fq_field) {
+            parameter.insert("description".to_owned(), json!(comment));
+        }
+        parameter.insert("schema".to_owned(), schema);
+        Value::Object(parameter)
+    }
+}
+
+/// The last field of a field path.
+fn last<'a>(field: &FieldPath<'a>) -> &'a FieldDescriptorProto {
+    field.fields.last().expect("field path is not empty")
+}
+
+/// The fully-qualified name of the message of the last field of a field path of `message`.
+fn parent<'a>(field: &'a FieldPath, message: &'a str) -> &'a str {
+    match field.fields.len() {
+        0 | 1 => message,
+        n => field.fields[n - 2].type_name(),
+    }
+}
+
+/// The schema of a path or query parameter, which is parsed with `FromStr` into the Rust type of
+/// its field.
+fn param_schema(field: &FieldDescriptorProto) -> Value {
+    match field.r#type() {
+        Type::Double => json!({ "type": "number", "format": "double" }),
+        Type::Float => json!({ "type": "number", "format": "float" }),
+        Type::Int64 | Type::Sint64 | Type::Sfixed64 => {
+            json!({ "type": "integer", "format": "int64" })
+        }
+        Type::Uint64 | Type::Fixed64 => {
+            json!({ "type": "integer", "format": "uint64", "minimum": 0 })
+        }
+        // enums are `i32` fields
+        Type::Int32 | Type::Sint32 | Type::Sfixed32 | Type::Enum => {
+            json!({ "type": "integer", "format": "int32" })
+        }
+        Type::Uint32 | Type::Fixed32 => {
+            json!({ "type": "integer", "format": "uint32", "minimum": 0 })
+        }
+        Type::Bool => json!({ "type": "boolean" }),
+        _ => json!({ "type": "string" }),
+    }
+}
+
+/// Point the `$ref`s of `paths` to the components, and return the components with the schemas
+/// they refer to, directly or through other components.
+fn components(paths: &mut Value, schemas: &[Schema]) -> Map<String, Value> {
+    let mut refs = Vec::new();
+    resolve_refs(paths, &mut refs);
+    let mut components = Map::new();
+    while let Some(fq_name) = refs.pop() {
+        let name = component_name(&fq_name);
+        if components.contains_key(&name) {
+            continue;
+        }
+        let mut schema = match schemas.iter().find(|s| s.fq_name == fq_name) {
+            Some(schema) => schema.value.clone(),
+            None => json!({}),
+        };
+        if let Some(object) = schema.as_object_mut() {
+            object.remove("$schema");
+            object.remove("$id");
+        }
+        resolve_refs(&mut schema, &mut refs);
+        components.insert(name, schema);
+    }
+    components
+}
+
+/// Replace the `$ref`s to schema files in `value` by `$ref`s to the components, and add the
+/// fully-qualified names of the referenced types to `refs`.
+fn resolve_refs(value: &mut Value, refs: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            let fq_name = object
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(ref_name);
+            if let Some(fq_name) = fq_name {
+                let reference = format!("#/components/schemas/{}", component_name(&fq_name));
+                object.insert("$ref".to_owned(), json!(reference));
+                refs.push(fq_name);
+            }
+            object.values_mut().for_each(|v| resolve_refs(v, refs));
+        }
+        Value::Array(values) => values.iter_mut().for_each(|v| resolve_refs(v, refs)),
+        _ => {}
+    }
+}
+
+fn component_name(fq_name: &str) -> String {
+    fq_name.trim_start_matches('.').to_owned()
+}
+
+/// The OpenAPI path of an HTTP rule path template, e.g. `/v1/{name}` for
+/// `/v1/{name=shelves/*}`, with the field paths of the variables and the patterns of their values
+/// if they aren't single segments.
+fn path_template(template: &str) -> Result<(String, Vec<Var>), String> {
+    if !template.starts_with('/') {
+        return Err(format!("path {} doesn't start with /", template));
+    }
+    let mut path = String::new();
+    let mut vars = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .ok_or_else(|| format!("unclosed variable in {}", template))?;
+        path.push_str(&rest[..start]);
+        let var = &rest[start + 1..end];
+        let (field_path, value_pattern) = var.split_once('=').unwrap_or((var, "*"));
+        path.push_str(&format!("{{{}}}", field_path));
+        let value_pattern = match value_pattern {
+            "*" => None,
+            value_pattern => {
+                let segments: Vec<_> = value_pattern
+                    .split('/')
+                    .map(|segment| match segment {
+                        "*" => "[^/]+".to_owned(),
+                        "**" => ".+".to_owned(),
+                        literal => escape(literal),
+                    })
+                    .collect();
+                Some(format!("^{}$", segments.join("/")))
+            }
+        };
+        vars.push((field_path.to_owned(), value_pattern));
+        rest = &rest[end + 1..];
+    }
+    path.push_str(rest);
+    Ok((path, vars))
+}
+
+/// Escape the characters of a literal which have a meaning in a regular expression.
+fn escape(literal: &str) -> String {
+    let mut escaped = String::new();
+    for c in literal.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BuildConfig, Builder};
+
+    fn document() -> Value {
+        let path = "examples/openapi/docs/library.Library.openapi.json";
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn generate_openapi() {
+        let config = BuildConfig::from_path("examples/openapi.yml").unwrap();
+        Builder::from(config).build_protos();
+    }
+
+    #[test]
+    fn operations_should_follow_http_rules() {
+        let doc = document();
+        assert_eq!(doc["openapi"], "3.1.0");
+        assert_eq!(
+            doc["info"]["description"],
+            "A library of books on shelves, served over HTTP/JSON."
+        );
+        let paths = &doc["paths"];
+
+        let get_book = &paths["/v1/{name}"]["get"];
+        assert_eq!(get_book["operationId"], "Library_GetBook");
+        assert_eq!(get_book["description"], "Gets a book.");
+        assert_eq!(
+            get_book["parameters"][0],
+            json!({
+                "name": "name",
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string", "pattern": "^shelves/[^/]+/books/[^/]+$" },
+            })
+        );
+        assert_eq!(
+            get_book["responses"]["200"]["content"]["application/json"]["schema"],
+            json!({ "$ref": "#/components/schemas/library.Book" })
+        );
+
+        // the unbound fields are query parameters, the response is a field of the response
+        let list_books = &paths["/v1/shelves/{shelf}/books"]["get"];
+        let names: Vec<_> = list_books["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "shelf",
+                "page_size",
+                "filter.author",
+                "filter.min_pages",
+                "filter.format",
+                "filter.tags"
+            ]
+        );
+        assert_eq!(
+            list_books["parameters"][1]["description"],
+            "Maximum number of books to return."
+        );
+        assert_eq!(
+            list_books["parameters"][5]["schema"],
+            json!({ "type": "array", "items": { "type": "string" } })
+        );
+        assert_eq!(
+            list_books["responses"]["200"]["content"]["application/json"]["schema"],
+            json!({ "type": "array", "items": { "$ref": "#/components/schemas/library.Book" } })
+        );
+        assert_eq!(
+            paths["/v1/books"]["get"]["operationId"],
+            "Library_ListBooks2"
+        );
+
+        let create_book = &paths["/v1/shelves/{shelf}/books"]["post"];
+        assert_eq!(
+            create_book["requestBody"]["content"]["application/json"]["schema"],
+            json!({ "anyOf": [{ "$ref": "#/components/schemas/library.Book" }, { "type": "null" }] })
+        );
+        let move_book = &paths["/v1/books:move"]["post"];
+        assert!(move_book.get("parameters").is_none());
+        assert_eq!(
+            move_book["requestBody"]["content"]["application/json"]["schema"],
+            json!({ "$ref": "#/components/schemas/library.MoveBookRequest" })
+        );
+        // streaming methods are skipped
+        assert!(!doc.to_string().contains("WatchBooks"));
+    }
+
+    #[test]
+    fn methods_without_http_rules_should_be_posted() {
+        let path = "examples/openapi/docs/catalog.Catalog.openapi.json";
+        let doc: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        let operation = &doc["paths"]["/catalog.Catalog/CountBooks"]["post"];
+        assert_eq!(operation["operationId"], "Catalog_CountBooks");
+        assert_eq!(
+            operation["description"],
+            "Counts the books which match a filter."
+        );
+        assert_eq!(
+            operation["requestBody"]["content"]["application/json"]["schema"],
+            json!({ "$ref": "#/components/schemas/library.ListBooksRequest" })
+        );
+        // referenced through other components, also of other packages
+        assert!(doc["components"]["schemas"]["library.Filter"].is_object());
+        assert!(doc["components"]["schemas"]["catalog.CountBooksResponse"].is_object());
+    }
+
+    #[test]
+    fn components_should_match_json_schema() {
+        let doc = document();
+        let book = &doc["components"]["schemas"]["library.Book"];
+        assert_eq!(book["description"], "A book on a shelf.");
+        assert!(book.get("$id").is_none());
+        // the proto3_json profile skips default values, so no property is required
+        assert_eq!(
+            book["properties"]["name"]["description"],
+            "Resource name of the book, e.g. `shelves/1/books/2`."
+        );
+        assert!(book.get("required").is_none());
+        let components = doc["components"]["schemas"].as_object().unwrap();
+        assert!(!components.contains_key("google.api.HttpRule"));
+    }
+
+    #[test]
+    fn path_templates_should_be_converted() {
+        assert_eq!(
+            path_template("/v1/{name=shelves/*/books/**}:get").unwrap(),
+            (
+                "/v1/{name}:get".to_owned(),
+                vec![(
+                    "name".to_owned(),
+                    Some("^shelves/[^/]+/books/.+$".to_owned())
+                )]
+            )
+        );
+        assert_eq!(
+            path_template("/v1/{shelf.id}/books").unwrap(),
+            (
+                "/v1/{shelf.id}/books".to_owned(),
+                vec![("shelf.id".to_owned(), None)]
+            )
+        );
+        assert_eq!(escape("v1.0"), "v1\\.0");
+        assert!(path_template("v1").is_err());
+        assert!(path_template("/v1/{name").is_err());
+    }
+}
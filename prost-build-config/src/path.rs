@@ -0,0 +1,236 @@
+//! Protobuf path matching, following the same rules as `prost_build::Config` so that paths in
+//! `BuildConfig` select the same types and fields no matter who resolves them.
+
+use std::{collections::HashMap, fmt, iter};
+
+use prost_types::{DescriptorProto, FileDescriptorProto};
+use serde::Serialize;
+
+/// What a protobuf path refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathKind {
+    Message,
+    Enum,
+    Oneof,
+    Field,
+    Service,
+}
+
+/// The fully-qualified path of a message, enum, oneof, field or service, as prost matches it
+/// against the paths in the config. Fields of a oneof are under the oneof, e.g. `.helloworld.Hello.body.text`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct ProtoPath {
+    pub kind: PathKind,
+    pub path: String,
+}
+
+impl fmt::Display for PathKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Self::Message => "message",
+            Self::Enum => "enum",
+            Self::Oneof => "oneof",
+            Self::Field => "field",
+            Self::Service => "service",
+        };
+        f.pad(kind)
+    }
+}
+
+/// All paths of the messages, enums, oneofs, fields and services in `files`, in declaration
+/// order.
+pub(crate) fn proto_paths(files: &[FileDescriptorProto]) -> Vec<ProtoPath> {
+    let mut paths = Vec::new();
+    for file in files {
+        let package = package_prefix(file);
+        for msg in &file.message_type {
+            message_paths(&mut paths, &package, msg);
+        }
+        for e in &file.enum_type {
+            paths.push(ProtoPath::new(PathKind::Enum, &package, e.name()));
+        }
+        for service in &file.service {
+            paths.push(ProtoPath::new(PathKind::Service, &package, service.name()));
+        }
+    }
+    paths
+}
+
+fn message_paths(paths: &mut Vec<ProtoPath>, parent: &str, msg: &DescriptorProto) {
+    let message = ProtoPath::new(PathKind::Message, parent, msg.name());
+    let fq_name = message.path.clone();
+    paths.push(message);
+    for field in &msg.field {
+        let parent = match real_oneof(msg, field.oneof_index, field.proto3_optional()) {
+            Some(oneof) => format!("{}.{}", fq_name, oneof),
+            None => fq_name.clone(),
+        };
+        paths.push(ProtoPath::new(PathKind::Field, &parent, field.name()));
+    }
+    for (idx, oneof) in msg.oneof_decl.iter().enumerate() {
+        let is_real = msg
+            .field
+            .iter()
+            .any(|f| f.oneof_index == Some(idx as i32) && !f.proto3_optional());
+        if is_real {
+            paths.push(ProtoPath::new(PathKind::Oneof, &fq_name, oneof.name()));
+        }
+    }
+    for nested in &msg.nested_type {
+        // map entries are generated as maps, not as messages
+        if nested.options.as_ref().and_then(|o| o.map_entry) != Some(true) {
+            message_paths(paths, &fq_name, nested);
+        }
+    }
+    for e in &msg.enum_type {
+        paths.push(ProtoPath::new(PathKind::Enum, &fq_name, e.name()));
+    }
+}
+
+impl ProtoPath {
+    fn new(kind: PathKind, parent: &str, name: &str) -> Self {
+        Self {
+            kind,
+            path: format!("{}.{}", parent, name),
+        }
+    }
+}
+
+/// Check whether `matcher` (e.g. `Hello.msg` or `.helloworld`) selects the fully-qualified
+/// path `fq_path` (e.g. `.helloworld.Hello.msg`).
+pub(crate) fn is_match(matcher: &str, fq_path: &str) -> bool {
+    sub_paths(fq_path).any(|p| p == matcher)
+}
+
+/// The fully-qualified prefix of the types in `file`, e.g. `.helloworld`, or empty.
+pub(crate) fn package_prefix(file: &FileDescriptorProto) -> String {
+    match file.package() {
+        "" => String::new(),
+        package => format!(".{}", package),
+    }
+}
+
+/// All messages of `files` by fully-qualified name, including the nested ones.
+pub(crate) fn messages(files: &[FileDescriptorProto]) -> HashMap<String, &DescriptorProto> {
+    let mut messages = HashMap::new();
+    for file in files {
+        let package = package_prefix(file);
+        for msg in &file.message_type {
+            collect_messages(&mut messages, &package, msg);
+        }
+    }
+    messages
+}
+
+fn collect_messages<'a>(
+    messages: &mut HashMap<String, &'a DescriptorProto>,
+    parent: &str,
+    msg: &'a DescriptorProto,
+) {
+    let fq_name = format!("{}.{}", parent, msg.name());
+    for nested in &msg.nested_type {
+        collect_messages(messages, &fq_name, nested);
+    }
+    messages.insert(fq_name, msg);
+}
+
+/// Whether `msg` is the entry of a map field, which prost generates as a `HashMap` instead of
+/// a message.
+pub(crate) fn is_map_entry(msg: &DescriptorProto) -> bool {
+    msg.options.as_ref().and_then(|o| o.map_entry) == Some(true)
+}
+
+/// The name of the oneof with index `idx` in `msg`. The synthetic oneofs of proto3 `optional`
+/// fields are not generated by prost, so they are skipped.
+pub(crate) fn real_oneof(
+    msg: &DescriptorProto,
+    idx: Option<i32>,
+    proto3_optional: bool,
+) -> Option<&str> {
+    if proto3_optional {
+        return None;
+    }
+    let oneof = msg.oneof_decl.get(usize::try_from(idx?).ok()?)?;
+    Some(oneof.name())
+}
+
+/// Given a fully-qualified path, returns the path itself, its suffixes, its prefixes and the
+/// global path.
+///
+/// Example: sub_paths(".a.b.c") -> [".a.b.c", "a.b.c", "b.c", "c", ".a.b", ".a", "."]
+pub(crate) fn sub_paths(fq_path: &str) -> impl Iterator<Item = &str> {
+    let suffixes = iter::successors(Some(fq_path), |path| {
+        path.split_once('.')
+            .map(|(_, rest)| rest)
+            .filter(|p| !p.is_empty())
+    })
+    .skip(1);
+    let prefixes = iter::successors(Some(fq_path), |path| {
+        path.rsplit_once('.')
+            .map(|(rest, _)| rest)
+            .filter(|p| !p.is_empty())
+    })
+    .skip(1);
+
+    iter::once(fq_path)
+        .chain(suffixes)
+        .chain(prefixes)
+        .chain(iter::once("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sub_paths_should_match_prost() {
+        let paths: Vec<_> = sub_paths(".a.b.c").collect();
+        assert_eq!(paths, [".a.b.c", "a.b.c", "b.c", "c", ".a.b", ".a", "."]);
+    }
+
+    #[test]
+    fn is_match_should_work() {
+        assert!(is_match("Hello.msg", ".helloworld.Hello.msg"));
+        assert!(is_match(".helloworld", ".helloworld.Hello.msg"));
+        assert!(!is_match("World.msg", ".helloworld.Hello.msg"));
+        assert!(!is_match("helloworld", ".helloworld.Hello.msg"));
+    }
+
+    #[test]
+    fn proto_paths_should_list_all_types_and_fields() {
+        use prost_types::{FieldDescriptorProto, OneofDescriptorProto};
+
+        let field = |name: &str, oneof_index| FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            oneof_index,
+            ..Default::default()
+        };
+        let file = FileDescriptorProto {
+            package: Some("helloworld".to_owned()),
+            message_type: vec![DescriptorProto {
+                name: Some("Hello".to_owned()),
+                field: vec![field("msg", None), field("text", Some(0))],
+                oneof_decl: vec![OneofDescriptorProto {
+                    name: Some("body".to_owned()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let paths: Vec<_> = proto_paths(&[file])
+            .into_iter()
+            .map(|p| format!("{} {}", p.kind, p.path))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "message .helloworld.Hello",
+                "field .helloworld.Hello.msg",
+                "field .helloworld.Hello.body.text",
+                "oneof .helloworld.Hello.body"
+            ]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Named lists of attributes which build options can reference.
+
+use std::{collections::HashMap, io};
+
+use crate::{BuildConfig, BuildOption};
+
+/// Presets shipped with the crate. Presets of the same name in the config take precedence.
+const BUILTIN_PRESETS: &[(&str, &[&str])] = &[
+    ("serde", &["derive(serde::Serialize, serde::Deserialize)"]),
+    (
+        "serde_camel_case",
+        &[
+            "derive(serde::Serialize, serde::Deserialize)",
+            "serde(rename_all = \"camelCase\")",
+        ],
+    ),
+    ("validator", &["derive(validator::Validate)"]),
+];
+
+impl BuildConfig {
+    /// Fail if a build option references a preset which is neither in `presets` nor built in.
+    pub(crate) fn check_presets(&self) -> io::Result<()> {
+        let options = self
+            .messages
+            .iter()
+            .map(|opt| ("messages", opt))
+            .chain(self.enums.iter().map(|opt| ("enums", opt)))
+            .chain(self.oneofs.iter().map(|opt| ("oneofs", &opt.option)))
+            .chain(self.fields.iter().map(|opt| ("fields", opt)))
+            .chain(self.services.iter().map(|opt| ("services", &opt.option)));
+        for (section, opt) in options {
+            let unknown = opt.presets.iter().find(|name| {
+                !self.presets.contains_key(*name)
+                    && !BUILTIN_PRESETS.iter().any(|(builtin, _)| builtin == name)
+            });
+            if let Some(name) = unknown {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown preset `{}` in {} {:?}", name, section, opt.paths),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BuildOption {
+    /// The attributes of the referenced presets, followed by the option's own attributes.
+    pub(crate) fn resolve_attrs(&self, presets: &HashMap<String, Vec<String>>) -> Vec<String> {
+        let mut attrs = Vec::new();
+        for name in &self.presets {
+            match presets.get(name) {
+                Some(preset) => attrs.extend(preset.iter().cloned()),
+                None => {
+                    let (_, preset) = BUILTIN_PRESETS
+                        .iter()
+                        .find(|(builtin, _)| builtin == name)
+                        .unwrap_or_else(|| panic!("Unknown preset: {}", name));
+                    attrs.extend(preset.iter().map(|s| s.to_string()));
+                }
+            }
+        }
+        attrs.extend(self.attrs.iter().cloned());
+        attrs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_should_be_resolved() {
+        let opt = BuildOption {
+            presets: vec!["serde_camel_case".to_owned(), "validator".to_owned()],
+            attrs: vec!["serde(default)".to_owned()],
+            ..Default::default()
+        };
+        let mut presets = HashMap::new();
+        assert_eq!(
+            opt.resolve_attrs(&presets),
+            [
+                "derive(serde::Serialize, serde::Deserialize)",
+                "serde(rename_all = \"camelCase\")",
+                "derive(validator::Validate)",
+                "serde(default)"
+            ]
+        );
+
+        presets.insert("validator".to_owned(), vec!["validate".to_owned()]);
+        assert_eq!(opt.resolve_attrs(&presets)[2], "validate");
+    }
+
+    #[test]
+    fn unknown_preset_should_fail_to_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yml");
+        std::fs::write(
+            &path,
+            "presets:\n  custom: [\"derive(Hash)\"]\nfields:\n  - paths: [Hello.msg]\n    presets: [serde, custom]\n  - paths: [Hello.value]\n    presets: [serde_snake]\n",
+        )
+        .unwrap();
+        let err = BuildConfig::from_path(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err
+            .to_string()
+            .ends_with(": unknown preset `serde_snake` in fields [\"Hello.value\"]"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown preset: serde_snake")]
+    fn unknown_preset_should_panic() {
+        let opt = BuildOption {
+            presets: vec!["serde_snake".to_owned()],
+            ..Default::default()
+        };
+        opt.resolve_attrs(&HashMap::new());
+    }
+}
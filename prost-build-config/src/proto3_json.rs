@@ -0,0 +1,457 @@
+//! The `proto3_json` profile: serde attributes for the
+//! [proto3 JSON mapping](https://protobuf.dev/programming-guides/proto3/#json), derived from the
+//! descriptors. The generated code relies on the `proto3` feature of prost-helper.
+
+use std::{collections::HashMap, fmt::Write};
+
+use prost_build::Config;
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    explain::{Rule, Section},
+    ident::{resolve_ident, to_snake, to_upper_camel},
+    path::{is_map_entry, messages, package_prefix, proto_paths, real_oneof, PathKind},
+};
+
+/// Well-known types whose JSON form is handled by `prost_helper::proto3`.
+const WELL_KNOWN_TYPES: &[&str] = &[
+    ".google.protobuf.Timestamp",
+    ".google.protobuf.Duration",
+    ".google.protobuf.FieldMask",
+    ".google.protobuf.Struct",
+    ".google.protobuf.Value",
+    ".google.protobuf.ListValue",
+    ".google.protobuf.DoubleValue",
+    ".google.protobuf.FloatValue",
+    ".google.protobuf.Int64Value",
+    ".google.protobuf.UInt64Value",
+    ".google.protobuf.BytesValue",
+    ".google.protobuf.Empty",
+    ".google.protobuf.Any",
+];
+
+/// The attributes of the profile on all messages.
+const MESSAGE_ATTRS: &[&str] = &[
+    "#[derive(serde::Serialize, serde::Deserialize)]",
+    "#[serde(default)]",
+];
+
+/// The attributes of the profile on all enums.
+const ENUM_ATTRS: &[&str] = &["#[derive(serde::Serialize, serde::Deserialize)]"];
+
+/// A predefined set of attributes for all generated types.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Profile {
+    /// serde support following the proto3 JSON mapping
+    Proto3Json,
+}
+
+/// How a field is mapped to JSON.
+enum Mapping {
+    /// serde's own form is the JSON form
+    Plain,
+    /// `prost_helper::proto3` knows the JSON form
+    Proto3,
+    /// an `i32` of the enum at the given Rust path
+    Enum(String),
+}
+
+impl Profile {
+    /// Add the attributes for all types and fields in `files`. `sensitive` fields are only
+    /// deserialized by the profile, as they are serialized by the redaction helpers.
+    pub(crate) fn configure(
+        &self,
+        config: &mut Config,
+        files: &[FileDescriptorProto],
+        sensitive: &[String],
+    ) {
+        config.message_attribute(".", MESSAGE_ATTRS.join("\n"));
+        config.enum_attribute(".", ENUM_ATTRS.join("\n"));
+        for (path, attr) in self.field_attrs(files, sensitive) {
+            config.field_attribute(&path, attr);
+        }
+        // proto3 JSON puts the set field of a oneof into the message. prost applies the
+        // attributes of a path to the paths under it as well, so the oneof variants would get
+        // `flatten` too, which serde doesn't allow on them. Without the leading dot the path is
+        // only matched as a suffix, i.e. by the oneof itself.
+        for oneof in oneofs(files) {
+            config.field_attribute(&oneof[1..], "#[serde(flatten)]");
+        }
+    }
+
+    /// The attributes of the profile on the messages, enums, fields and oneofs in `files`, as
+    /// rules for `explain`.
+    pub(crate) fn rules(&self, files: &[FileDescriptorProto], sensitive: &[String]) -> Vec<Rule> {
+        let rule = |kind, paths: Vec<String>, attrs: &[&str]| Rule {
+            section: Section::Profile(kind),
+            index: 0,
+            paths,
+            attrs: attrs.iter().map(|a| a.to_string()).collect(),
+            variant_attrs: Vec::new(),
+        };
+        let mut rules = vec![
+            rule(PathKind::Message, vec![".".to_owned()], MESSAGE_ATTRS),
+            rule(PathKind::Enum, vec![".".to_owned()], ENUM_ATTRS),
+        ];
+        for (path, attr) in self.field_attrs(files, sensitive) {
+            rules.push(rule(PathKind::Field, vec![path], &[&attr]));
+        }
+        for oneof in oneofs(files) {
+            rules.push(rule(PathKind::Oneof, vec![oneof], &["#[serde(flatten)]"]));
+        }
+        rules
+    }
+
+    fn field_attrs(
+        &self,
+        files: &[FileDescriptorProto],
+        sensitive: &[String],
+    ) -> Vec<(String, String)> {
+        let ctx = Context {
+            messages: messages(files),
+            sensitive,
+        };
+        let mut attrs = Vec::new();
+        for file in files {
+            let package = package_prefix(file);
+            let scope: Vec<_> = file
+                .package()
+                .split('.')
+                .filter(|s| !s.is_empty())
+                .collect();
+            for msg in &file.message_type {
+                ctx.message_attrs(&mut attrs, &package, &scope, msg);
+            }
+        }
+        attrs
+    }
+
+    /// Code to append to the module of `file`: `Proto3Enum` impls for its enums.
+    pub(crate) fn extra_code(&self, file: &FileDescriptorProto) -> String {
+        let mut buf = String::new();
+        for e in &file.enum_type {
+            append_enum_impl(&mut buf, "", e);
+        }
+        for msg in &file.message_type {
+            append_nested_enum_impls(&mut buf, "", msg);
+        }
+        buf
+    }
+}
+
+struct Context<'a> {
+    /// all messages by their fully-qualified name, to look up map entries
+    messages: HashMap<String, &'a DescriptorProto>,
+    sensitive: &'a [String],
+}
+
+impl Context<'_> {
+    /// `scope` is the protobuf path of the Rust module the message is generated in.
+    fn message_attrs(
+        &self,
+        attrs: &mut Vec<(String, String)>,
+        parent: &str,
+        scope: &[&str],
+        msg: &DescriptorProto,
+    ) {
+        let fq_name = format!("{}.{}", parent, msg.name());
+        // oneofs and nested types are generated in a module named after the message
+        let mut nested_scope = scope.to_vec();
+        nested_scope.push(msg.name());
+
+        for field in &msg.field {
+            let oneof = real_oneof(msg, field.oneof_index, field.proto3_optional());
+            let (path, scope) = match oneof {
+                Some(oneof) => (
+                    format!("{}.{}.{}", fq_name, oneof, field.name()),
+                    &nested_scope[..],
+                ),
+                None => (format!("{}.{}", fq_name, field.name()), scope),
+            };
+            let mapping = self.mapping(field, scope);
+            let sensitive = self.sensitive.contains(&path);
+            attrs.push((
+                path,
+                field_attr(field, &mapping, oneof.is_some(), sensitive),
+            ));
+        }
+
+        for nested in &msg.nested_type {
+            if !is_map_entry(nested) {
+                self.message_attrs(attrs, &fq_name, &nested_scope, nested);
+            }
+        }
+    }
+
+    fn mapping(&self, field: &FieldDescriptorProto, scope: &[&str]) -> Mapping {
+        match field.r#type() {
+            Type::Int64
+            | Type::Uint64
+            | Type::Sint64
+            | Type::Fixed64
+            | Type::Sfixed64
+            | Type::Float
+            | Type::Double
+            | Type::Bytes => Mapping::Proto3,
+            Type::Enum if !field.type_name().starts_with(".google.protobuf.") => {
+                Mapping::Enum(resolve_ident(scope, field.type_name()))
+            }
+            Type::Message if WELL_KNOWN_TYPES.contains(&field.type_name()) => Mapping::Proto3,
+            Type::Message if field.label() == Label::Repeated => {
+                // the type of a map field is the type of its values
+                match self.messages.get(field.type_name()) {
+                    Some(entry) if is_map_entry(entry) => match entry.field.get(1) {
+                        Some(value) => self.mapping(value, scope),
+                        None => Mapping::Plain,
+                    },
+                    _ => Mapping::Plain,
+                }
+            }
+            _ => Mapping::Plain,
+        }
+    }
+}
+
+/// The fully-qualified paths of the oneofs in `files`.
+fn oneofs(files: &[FileDescriptorProto]) -> impl Iterator<Item = String> {
+    proto_paths(files)
+        .into_iter()
+        .filter(|p| p.kind == PathKind::Oneof)
+        .map(|p| p.path)
+}
+
+fn field_attr(
+    field: &FieldDescriptorProto,
+    mapping: &Mapping,
+    in_oneof: bool,
+    sensitive: bool,
+) -> String {
+    let json_name = match &field.json_name {
+        Some(json_name) => json_name.clone(),
+        None => to_lower_camel(field.name()),
+    };
+    let mut args = vec![format!("rename = \"{}\"", json_name)];
+    if json_name != field.name() {
+        args.push(format!("alias = \"{}\"", field.name()));
+    }
+    // variants of a oneof enum are always set, and serde doesn't allow these on them
+    if !in_oneof {
+        args.push("skip_serializing_if = \"::prost_helper::proto3::is_default\"".to_owned());
+    }
+    match mapping {
+        Mapping::Proto3 if sensitive => {
+            args.push("deserialize_with = \"::prost_helper::proto3::deserialize\"".to_owned())
+        }
+        Mapping::Proto3 => args.push("with = \"::prost_helper::proto3\"".to_owned()),
+        Mapping::Enum(path) => {
+            if !sensitive {
+                args.push(format!(
+                    "serialize_with = \"::prost_helper::proto3::serialize_enum::<{}, _, _>\"",
+                    path
+                ));
+            }
+            args.push(format!(
+                "deserialize_with = \"::prost_helper::proto3::deserialize_enum::<{}, _, _>\"",
+                path
+            ));
+        }
+        Mapping::Plain if !in_oneof => {
+            args.push("deserialize_with = \"::prost_helper::deserialize_null_default\"".to_owned())
+        }
+        Mapping::Plain => {}
+    }
+    format!("#[serde({})]", args.join(", "))
+}
+
+fn append_nested_enum_impls(buf: &mut String, module: &str, msg: &DescriptorProto) {
+    let module = format!("{}{}::", module, to_snake(msg.name()));
+    for e in &msg.enum_type {
+        append_enum_impl(buf, &module, e);
+    }
+    for nested in &msg.nested_type {
+        append_nested_enum_impls(buf, &module, nested);
+    }
+}
+
+fn append_enum_impl(buf: &mut String, module: &str, e: &EnumDescriptorProto) {
+    let name = to_upper_camel(e.name());
+    let _ = writeln!(
+        buf,
+        "impl ::prost_helper::proto3::Proto3Enum for {}{} {{",
+        module, name
+    );
+    buf.push_str("    fn name(value: i32) -> ::core::option::Option<&'static str> {\n");
+    buf.push_str("        Self::try_from(value).ok().map(|v| v.as_str_name())\n    }\n");
+    buf.push_str("    fn value(name: &str) -> ::core::option::Option<i32> {\n");
+    buf.push_str("        Self::from_str_name(name).map(|v| v as i32)\n    }\n}\n");
+}
+
+/// The default JSON name of a field, as protoc computes it.
+fn to_lower_camel(name: &str) -> String {
+    let mut s = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        match c {
+            '_' => upper = true,
+            c if upper => {
+                s.push(c.to_ascii_uppercase());
+                upper = false;
+            }
+            c => s.push(c),
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BuildConfig, Builder};
+    use serde_json::{json, Value};
+
+    #[allow(clippy::all)]
+    mod conformance {
+        include!("../examples/proto3_json/conformance.rs");
+    }
+
+    use conformance::{sample, Color, Sample};
+
+    /// JSON as printed by protobuf's `JsonFormat` for the same message.
+    fn known_good() -> Value {
+        json!({
+            "userName": "alice",
+            "count": 3,
+            "big": "-9007199254740993",
+            "ubig": "18446744073709551615",
+            "ratio": "NaN",
+            "score": 1.1,
+            "active": true,
+            "payload": "AQL/",
+            "color": "RED",
+            "kind": "PRIMARY",
+            "ids": ["1", "-2"],
+            "colors": ["RED", "GREEN"],
+            "counters": { "a": "1" },
+            "colorMap": { "1": "GREEN" },
+            "nested": { "id": "7", "color": "GREEN" },
+            "children": [{ "id": "1" }, {}],
+            "maybe": 0,
+            "createdAt": "1970-01-01T00:00:01.500Z",
+            "ttl": "1.500s",
+            "wrapped": "5",
+            "label": "hi",
+            "extra": { "k": [1.0, "v", null, true] },
+            "mask": "userName,createdAt",
+            "number": "42",
+            "nothing": {}
+        })
+    }
+
+    #[test]
+    fn generate_proto3_json_code() {
+        let config = BuildConfig::from_path("examples/conformance.yml").unwrap();
+        assert_eq!(config.profile, Some(Profile::Proto3Json));
+        Builder::from(config).build_protos();
+    }
+
+    #[test]
+    fn generated_code_should_match_proto3_json() {
+        let json = known_good();
+        let sample: Sample = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(sample.big, -9007199254740993);
+        assert_eq!(sample.ubig, u64::MAX);
+        assert!(sample.ratio.is_nan());
+        assert_eq!(sample.payload, [1, 2, 255]);
+        assert_eq!(sample.color, Color::Red as i32);
+        assert_eq!(sample.kind, sample::Kind::Primary as i32);
+        assert_eq!(sample.color_map[&1], Color::Green as i32);
+        assert_eq!(sample.maybe, Some(0));
+        assert_eq!(sample.created_at.as_ref().unwrap().nanos, 500_000_000);
+        assert_eq!(
+            sample.mask.as_ref().unwrap().paths,
+            ["user_name", "created_at"]
+        );
+        assert_eq!(sample.choice, Some(sample::Choice::Number(42)));
+        assert_eq!(sample.score, 1.1);
+        assert_eq!(sample.nothing, Some(()));
+
+        assert_eq!(serde_json::to_value(&sample).unwrap(), json);
+    }
+
+    #[test]
+    fn generated_code_should_accept_proto3_json_variants() {
+        // field names, enum numbers, integers as numbers and null are accepted as well
+        let json = json!({
+            "user_name": "alice",
+            "big": 5,
+            "color": 2,
+            "colors": ["RED", 1],
+            "nested": null,
+            "payload": "AQL_",
+            "shade": "GREEN"
+        });
+        let sample: Sample = serde_json::from_value(json).unwrap();
+        assert_eq!(sample.user_name, "alice");
+        assert_eq!(sample.big, 5);
+        assert_eq!(sample.color, Color::Green as i32);
+        assert_eq!(sample.colors, [Color::Red as i32, Color::Red as i32]);
+        assert_eq!(sample.nested, None);
+        assert_eq!(sample.payload, [1, 2, 255]);
+        assert_eq!(
+            sample.choice,
+            Some(sample::Choice::Shade(Color::Green as i32))
+        );
+
+        let empty = serde_json::to_value(Sample::default()).unwrap();
+        assert_eq!(empty, json!({}));
+    }
+
+    #[test]
+    fn any_should_fail_without_type_registry() {
+        let sample = Sample {
+            detail: Some(prost_types::Any::default()),
+            ..Default::default()
+        };
+        let err = serde_json::to_value(&sample).unwrap_err();
+        assert!(err.to_string().contains("without a type registry"));
+        assert!(serde_json::from_value::<Sample>(json!({ "detail": {} })).is_err());
+    }
+
+    #[test]
+    fn explain_should_include_the_profile() {
+        let config = BuildConfig::from_path("examples/conformance.yml").unwrap();
+        let explanation = Builder::from(config).explain().unwrap();
+        let attrs = |path: &str| {
+            let path = explanation.paths.iter().find(|p| p.path == path).unwrap();
+            path.attrs
+                .iter()
+                .map(|a| (a.attr.as_str(), a.source.to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            attrs(".conformance.Sample")[1],
+            ("#[serde(default)]", "profile (.)".to_owned())
+        );
+        assert_eq!(
+            attrs(".conformance.Sample.choice"),
+            [(
+                "#[serde(flatten)]",
+                "profile (.conformance.Sample.choice)".to_owned()
+            )]
+        );
+        assert!(attrs(".conformance.Sample.big")[0]
+            .0
+            .contains("with = \"::prost_helper::proto3\""));
+        assert!(explanation.unmatched.iter().all(|r| r.rule != "profile"));
+    }
+
+    #[test]
+    fn lower_camel_should_match_protoc() {
+        assert_eq!(to_lower_camel("created_at"), "createdAt");
+    }
+}
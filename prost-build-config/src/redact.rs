@@ -0,0 +1,465 @@
+//! Masking of sensitive fields in the JSON debug output (`prost_helper::ToJson`) and in
+//! generated `Debug` impls.
+
+use std::{fmt::Write, io};
+
+use prost_types::{DescriptorProto, EnumDescriptorProto, FileDescriptorProto};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    explain::{Rule, Section},
+    ident::{to_snake, to_upper_camel},
+    path::{is_match, package_prefix, real_oneof, PathKind},
+};
+
+/// How sensitive fields are masked.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SensitiveMask {
+    /// replace the value with `***`
+    #[default]
+    Stars,
+    /// replace the value with a hash of the same length, so equal values can be correlated
+    Hash,
+}
+
+impl SensitiveMask {
+    /// serde attribute which masks the field in `ToJson` output
+    pub(crate) fn serde_attr(&self) -> &'static str {
+        match self {
+            Self::Stars => r#"#[serde(serialize_with = "prost_helper::serialize_redacted")]"#,
+            Self::Hash => r#"#[serde(serialize_with = "prost_helper::serialize_redacted_hash")]"#,
+        }
+    }
+
+    fn debug_wrapper(&self) -> &'static str {
+        match self {
+            Self::Stars => "::prost_helper::Redacted",
+            Self::Hash => "::prost_helper::RedactedHash",
+        }
+    }
+}
+
+/// Sensitive fields resolved against the descriptors.
+#[derive(Debug, Default)]
+pub(crate) struct Sensitive {
+    /// fully-qualified paths of the sensitive fields, as prost uses them for field attributes
+    pub fields: Vec<String>,
+    /// fully-qualified names of the messages which have sensitive fields
+    pub messages: Vec<String>,
+}
+
+impl Sensitive {
+    /// Resolve the configured `paths` against all messages in `files`.
+    pub fn resolve(files: &[FileDescriptorProto], paths: &[String]) -> Self {
+        let mut sensitive = Self::default();
+        if paths.is_empty() {
+            return sensitive;
+        }
+        for file in files {
+            let package = package_prefix(file);
+            for msg in &file.message_type {
+                sensitive.resolve_message(&package, msg, paths);
+            }
+        }
+        sensitive
+    }
+
+    fn resolve_message(&mut self, parent: &str, msg: &DescriptorProto, paths: &[String]) {
+        let fq_name = format!("{}.{}", parent, msg.name());
+        let mut found = false;
+        for field in &msg.field {
+            let fq_field = format!("{}.{}", fq_name, field.name());
+            let oneof = real_oneof(msg, field.oneof_index, field.proto3_optional());
+            let fq_attr_path = match oneof {
+                Some(oneof) => format!("{}.{}.{}", fq_name, oneof, field.name()),
+                None => fq_field.clone(),
+            };
+            if paths
+                .iter()
+                .any(|p| is_match(p, &fq_field) || is_match(p, &fq_attr_path))
+            {
+                self.fields.push(fq_attr_path);
+                found = true;
+            }
+        }
+        if found {
+            self.messages.push(fq_name.clone());
+        }
+        for nested in &msg.nested_type {
+            self.resolve_message(&fq_name, nested, paths);
+        }
+    }
+
+    /// Fail if a build option already sets how serde serializes a sensitive field, as it would
+    /// conflict with the `serialize_with` which masks the field.
+    pub fn check_conflicts(&self, rules: &[&Rule]) -> io::Result<()> {
+        for rule in rules {
+            let attrs = match rule.section {
+                Section::Fields => &rule.attrs,
+                Section::Oneofs => &rule.variant_attrs,
+                _ => continue,
+            };
+            let Some(attr) = attrs.iter().find(|a| is_serializer(a)) else {
+                continue;
+            };
+            for field in &self.fields {
+                // the variant attributes of a oneof are put on the fields of the oneof
+                let target = match rule.section {
+                    Section::Oneofs => match field.rsplit_once('.') {
+                        Some((oneof, _)) if !self.messages.iter().any(|m| m == oneof) => oneof,
+                        _ => continue,
+                    },
+                    _ => field,
+                };
+                if let Some(path) = rule.paths.iter().find(|p| is_match(p, target)) {
+                    let source = rule.source(path);
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "sensitive field {} can't be masked, as {} ({}) already sets {}",
+                            field, source.rule, source.path, attr
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The sensitive fields whose message, or oneof for the fields of a oneof, derives
+    /// `Serialize` with one of `rules`. Only those can take the serde attribute which masks them,
+    /// the others are masked in `Debug` alone.
+    pub fn serde_fields(&self, rules: &[&Rule]) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter(|field| {
+                let Some((owner, _)) = field.rsplit_once('.') else {
+                    return false;
+                };
+                let is_oneof = !self.messages.iter().any(|m| m == owner);
+                rules.iter().any(|rule| {
+                    // the profile's enum attributes are put on the enums of oneofs as well
+                    let applies = match rule.section {
+                        Section::Messages | Section::Profile(PathKind::Message) => !is_oneof,
+                        Section::Oneofs | Section::Profile(PathKind::Enum) => is_oneof,
+                        _ => false,
+                    };
+                    applies
+                        && rule.attrs.iter().any(|a| is_serialize_derive(a))
+                        && rule.paths.iter().any(|p| is_match(p, owner))
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Generate `Debug` impls for the messages in `file` with sensitive fields. prost skips
+    /// `Debug` for all types nested in those messages as well, so they get an impl too.
+    pub fn debug_impls(&self, file: &FileDescriptorProto, mask: SensitiveMask) -> String {
+        let mut buf = String::new();
+        if self.messages.is_empty() {
+            return buf;
+        }
+        let package = package_prefix(file);
+        for msg in &file.message_type {
+            self.find_messages(&mut buf, &package, "", msg, mask);
+        }
+        buf
+    }
+
+    fn find_messages(
+        &self,
+        buf: &mut String,
+        parent: &str,
+        module: &str,
+        msg: &DescriptorProto,
+        mask: SensitiveMask,
+    ) {
+        let fq_name = format!("{}.{}", parent, msg.name());
+        if self.messages.contains(&fq_name) {
+            self.append_message(buf, &fq_name, module, msg, mask);
+            return;
+        }
+        let module = format!("{}{}::", module, to_snake(msg.name()));
+        for nested in &msg.nested_type {
+            self.find_messages(buf, &fq_name, &module, nested, mask);
+        }
+    }
+
+    fn append_message(
+        &self,
+        buf: &mut String,
+        fq_name: &str,
+        module: &str,
+        msg: &DescriptorProto,
+        mask: SensitiveMask,
+    ) {
+        let name = to_upper_camel(msg.name());
+        let _ = writeln!(buf, "impl ::core::fmt::Debug for {}{} {{", module, name);
+        let _ = writeln!(
+            buf,
+            "    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{"
+        );
+        let _ = writeln!(buf, "        f.debug_struct(\"{}\")", name);
+        for field in &msg.field {
+            if real_oneof(msg, field.oneof_index, field.proto3_optional()).is_some() {
+                continue;
+            }
+            let ident = to_snake(field.name());
+            let fq_field = format!("{}.{}", fq_name, field.name());
+            match self.fields.contains(&fq_field) {
+                true => {
+                    let wrapper = mask.debug_wrapper();
+                    let _ = writeln!(
+                        buf,
+                        "            .field(\"{}\", &{}(&self.{}))",
+                        ident, wrapper, ident
+                    );
+                }
+                false => {
+                    let _ = writeln!(buf, "            .field(\"{}\", &self.{})", ident, ident);
+                }
+            }
+        }
+        // prost puts oneofs after the other fields
+        for (idx, oneof) in msg.oneof_decl.iter().enumerate() {
+            let is_real = msg
+                .field
+                .iter()
+                .any(|f| f.oneof_index == Some(idx as i32) && !f.proto3_optional());
+            if is_real {
+                let ident = to_snake(oneof.name());
+                let _ = writeln!(buf, "            .field(\"{}\", &self.{})", ident, ident);
+            }
+        }
+        buf.push_str("            .finish()\n    }\n}\n");
+
+        let nested_module = format!("{}{}::", module, to_snake(msg.name()));
+        for (idx, oneof) in msg.oneof_decl.iter().enumerate() {
+            let fields: Vec<_> = msg
+                .field
+                .iter()
+                .filter(|f| f.oneof_index == Some(idx as i32) && !f.proto3_optional())
+                .collect();
+            if fields.is_empty() {
+                continue;
+            }
+            let name = to_upper_camel(oneof.name());
+            let _ = writeln!(
+                buf,
+                "impl ::core::fmt::Debug for {}{} {{",
+                nested_module, name
+            );
+            let _ = writeln!(
+                buf,
+                "    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{"
+            );
+            buf.push_str("        match self {\n");
+            for field in fields {
+                let variant = to_upper_camel(field.name());
+                let fq_field = format!("{}.{}.{}", fq_name, oneof.name(), field.name());
+                let value = match self.fields.contains(&fq_field) {
+                    true => format!("&{}(v)", mask.debug_wrapper()),
+                    false => "v".to_owned(),
+                };
+                let _ = writeln!(
+                    buf,
+                    "            Self::{}(v) => f.debug_tuple(\"{}\").field({}).finish(),",
+                    variant, variant, value
+                );
+            }
+            buf.push_str("        }\n    }\n}\n");
+        }
+
+        for nested in &msg.nested_type {
+            if nested.options.as_ref().and_then(|o| o.map_entry) == Some(true) {
+                continue;
+            }
+            let fq_nested = format!("{}.{}", fq_name, nested.name());
+            self.append_message(buf, &fq_nested, &nested_module, nested, mask);
+        }
+        for e in &msg.enum_type {
+            append_enum(buf, &nested_module, e);
+        }
+    }
+}
+
+fn append_enum(buf: &mut String, module: &str, e: &EnumDescriptorProto) {
+    let name = to_upper_camel(e.name());
+    let _ = writeln!(buf, "impl ::core::fmt::Debug for {}{} {{", module, name);
+    let _ = writeln!(
+        buf,
+        "    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{"
+    );
+    buf.push_str("        f.write_str(self.as_str_name())\n    }\n}\n");
+}
+
+/// Whether `attr` derives `Serialize`, e.g. `#[derive(serde::Serialize, serde::Deserialize)]`.
+fn is_serialize_derive(attr: &str) -> bool {
+    let Some(args) = attr
+        .strip_prefix("#[derive(")
+        .and_then(|a| a.strip_suffix(")]"))
+    else {
+        return false;
+    };
+    args.split(',').any(|arg| {
+        matches!(
+            arg.trim(),
+            "Serialize" | "serde::Serialize" | "::serde::Serialize"
+        )
+    })
+}
+
+/// Whether `attr` sets how serde serializes a field, e.g. `#[serde(with = "base64")]`.
+fn is_serializer(attr: &str) -> bool {
+    let Some(args) = attr
+        .strip_prefix("#[serde(")
+        .and_then(|a| a.strip_suffix(")]"))
+    else {
+        return false;
+    };
+    args.split(',').any(|arg| {
+        let key = arg.split('=').next().unwrap_or_default().trim();
+        key == "with" || key == "serialize_with"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::{field_descriptor_proto::Type, FieldDescriptorProto, OneofDescriptorProto};
+
+    fn field(name: &str, number: i32, oneof_index: Option<i32>) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            number: Some(number),
+            r#type: Some(Type::String as i32),
+            oneof_index,
+            ..Default::default()
+        }
+    }
+
+    fn file() -> FileDescriptorProto {
+        FileDescriptorProto {
+            name: Some("login.proto".to_owned()),
+            package: Some("auth".to_owned()),
+            message_type: vec![
+                DescriptorProto {
+                    name: Some("Login".to_owned()),
+                    field: vec![
+                        field("user", 1, None),
+                        field("password", 2, None),
+                        field("token", 3, Some(0)),
+                        field("code", 4, Some(0)),
+                    ],
+                    oneof_decl: vec![OneofDescriptorProto {
+                        name: Some("secret".to_owned()),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+                DescriptorProto {
+                    name: Some("Public".to_owned()),
+                    field: vec![field("password_hint", 1, None)],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolve_should_find_sensitive_fields() {
+        let paths = vec!["password".to_owned(), "Login.token".to_owned()];
+        let sensitive = Sensitive::resolve(&[file()], &paths);
+        assert_eq!(
+            sensitive.fields,
+            [".auth.Login.password", ".auth.Login.secret.token"]
+        );
+        assert_eq!(sensitive.messages, [".auth.Login"]);
+    }
+
+    #[test]
+    fn debug_impls_should_mask_sensitive_fields() {
+        let paths = vec!["password".to_owned(), "Login.token".to_owned()];
+        let sensitive = Sensitive::resolve(&[file()], &paths);
+        let code = sensitive.debug_impls(&file(), SensitiveMask::Stars);
+        assert!(code.contains("impl ::core::fmt::Debug for Login {"));
+        assert!(code.contains(".field(\"user\", &self.user)"));
+        assert!(code.contains(".field(\"password\", &::prost_helper::Redacted(&self.password))"));
+        assert!(code.contains(".field(\"secret\", &self.secret)"));
+        assert!(code.contains("impl ::core::fmt::Debug for login::Secret {"));
+        assert!(code.contains(
+            "Self::Token(v) => f.debug_tuple(\"Token\").field(&::prost_helper::Redacted(v)).finish(),"
+        ));
+        assert!(code.contains("Self::Code(v) => f.debug_tuple(\"Code\").field(v).finish(),"));
+        assert!(!code.contains("Public"));
+    }
+
+    #[test]
+    fn serde_fields_should_need_a_serialize_derive() {
+        let paths = vec!["password".to_owned(), "Login.token".to_owned()];
+        let sensitive = Sensitive::resolve(&[file()], &paths);
+        let rule = |section, paths: &[&str], attrs: &[&str]| {
+            let paths = paths.iter().map(|p| p.to_string()).collect();
+            let attrs = attrs.iter().map(|a| a.to_string()).collect();
+            Rule::new(section, 0, paths, attrs)
+        };
+
+        let debug = rule(Section::Messages, &["."], &["derive(Eq)"]);
+        assert!(sensitive.serde_fields(&[&debug]).is_empty());
+
+        let login = rule(Section::Messages, &["Login"], &["derive(serde::Serialize)"]);
+        assert_eq!(sensitive.serde_fields(&[&login]), [".auth.Login.password"]);
+        // the attributes of messages aren't put on oneofs
+        let all = rule(
+            Section::Messages,
+            &["."],
+            &["derive(Clone, serde::Serialize)"],
+        );
+        assert_eq!(sensitive.serde_fields(&[&all]), [".auth.Login.password"]);
+        let oneof = rule(Section::Oneofs, &["Login.secret"], &["derive(Serialize)"]);
+        assert_eq!(
+            sensitive.serde_fields(&[&oneof]),
+            [".auth.Login.secret.token"]
+        );
+        assert_eq!(
+            sensitive.serde_fields(&[&all, &oneof]),
+            [".auth.Login.password", ".auth.Login.secret.token"]
+        );
+    }
+
+    #[test]
+    fn custom_serializers_of_sensitive_fields_should_conflict() {
+        let paths = vec!["password".to_owned(), "Login.token".to_owned()];
+        let sensitive = Sensitive::resolve(&[file()], &paths);
+        let rule = |section, paths: &[&str], attrs: &[&str]| {
+            let paths = paths.iter().map(|p| p.to_string()).collect();
+            let attrs = attrs.iter().map(|a| a.to_string()).collect();
+            Rule::new(section, 0, paths, attrs)
+        };
+
+        let default = rule(Section::Fields, &["Login"], &["serde(default)"]);
+        let other = rule(Section::Fields, &["Login.user"], &[r#"serde(with = "x")"#]);
+        assert!(sensitive.check_conflicts(&[&default, &other]).is_ok());
+
+        let with = rule(
+            Section::Fields,
+            &["Login.password"],
+            &[r#"serde(default, serialize_with = "x")"#],
+        );
+        let err = sensitive.check_conflicts(&[&with]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "sensitive field .auth.Login.password can't be masked, as fields[0] (Login.password) \
+             already sets #[serde(default, serialize_with = \"x\")]"
+        );
+
+        let mut oneof = rule(Section::Oneofs, &["Login.secret"], &[]);
+        oneof.variant_attrs = vec![r#"#[serde(with = "x")]"#.to_owned()];
+        assert!(sensitive.check_conflicts(&[&oneof]).is_err());
+        oneof.paths = vec!["Public".to_owned()];
+        assert!(sensitive.check_conflicts(&[&oneof]).is_ok());
+    }
+}
@@ -0,0 +1,578 @@
+//! gRPC services, generated with `tonic-build`. Every build option gets its own tonic generator,
+//! so that the server and client toggles and the attributes apply per service. The messages of
+//! the services are generated by the same prost config, so the `messages` and `fields` options
+//! apply to them as usual. Services can also get a plain async trait, see `service_trait`.
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use prost_build::{Service, ServiceGenerator};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    explain::{Rule, Section},
+    path::is_match,
+    service_trait::TraitGenerator,
+    BuildOption,
+};
+
+/// Build options for gRPC services. The server and the client need the `tonic` feature.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(default)]
+pub struct ServiceOption {
+    /// paths of the services, e.g. `helloworld.Greeter`, and attributes for both their server and
+    /// client structs. A service uses the first option which matches it
+    #[serde(flatten)]
+    pub option: BuildOption,
+    /// generate the server, defaults to true unless `trait` or `http` is set
+    pub server: Option<bool>,
+    /// generate the client, defaults to true unless `trait` or `http` is set
+    pub client: Option<bool>,
+    /// generate the `connect` method of the client, which needs tonic's `transport` feature,
+    /// defaults to true
+    pub transport: bool,
+    /// extra attributes for the server structs and traits
+    pub server_attrs: Vec<String>,
+    /// extra attributes for the client structs
+    pub client_attrs: Vec<String>,
+    /// attributes for the `<service>_server` modules, e.g. `cfg(feature = "server")`
+    pub server_mod_attrs: Vec<String>,
+    /// attributes for the `<service>_client` modules
+    pub client_mod_attrs: Vec<String>,
+    /// encodings to accept, the first one is also used to send messages. If set, the functions
+    /// `compress_<service>_server` and `compress_<service>_client` are generated to enable them
+    pub compression: Vec<Compression>,
+    /// generate the `<service>_service` module with an async trait, a dispatcher and a mock,
+    /// which don't depend on tonic
+    #[serde(rename = "trait")]
+    pub service_trait: bool,
+    /// generate the `<service>_http` module with an axum router for the methods with
+    /// `google.api.http` options, which calls the service trait. Implies `trait`
+    pub http: bool,
+}
+
+/// A compression encoding of tonic, which needs the tonic feature of the same name.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+/// Which services have a flag of their `services` option set. A service uses the first option
+/// which matches it.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ServiceFlag(Vec<(Vec<String>, bool)>);
+
+impl ServiceFlag {
+    pub fn new(options: &[ServiceOption], flag: impl Fn(&ServiceOption) -> bool) -> Self {
+        Self(
+            options
+                .iter()
+                .map(|opt| (opt.option.paths.clone(), flag(opt)))
+                .collect(),
+        )
+    }
+
+    /// Whether the flag is set for the service `fq_name`, e.g. `.greeter.Greeter`.
+    pub fn is_set(&self, fq_name: &str) -> bool {
+        self.0
+            .iter()
+            .find(|(paths, _)| paths.iter().any(|p| is_match(p, fq_name)))
+            .is_some_and(|(_, set)| *set)
+    }
+
+    /// Whether the flag is set for any option.
+    pub fn any(&self) -> bool {
+        self.0.iter().any(|(_, set)| *set)
+    }
+}
+
+/// A service generator which hands every service to several generators in order, since prost
+/// accepts only one. Their `finalize` and `finalize_package` run in the same order.
+#[derive(Default)]
+pub struct MultiServiceGenerator {
+    generators: Vec<Box<dyn ServiceGenerator>>,
+}
+
+impl Default for ServiceOption {
+    fn default() -> Self {
+        Self {
+            option: BuildOption::default(),
+            server: None,
+            client: None,
+            transport: true,
+            server_attrs: Vec::new(),
+            client_attrs: Vec::new(),
+            server_mod_attrs: Vec::new(),
+            client_mod_attrs: Vec::new(),
+            compression: Vec::new(),
+            service_trait: false,
+            http: false,
+        }
+    }
+}
+
+impl MultiServiceGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a generator, which runs after the ones added before.
+    pub fn push(&mut self, generator: Box<dyn ServiceGenerator>) -> &mut Self {
+        self.generators.push(generator);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.generators.is_empty()
+    }
+}
+
+impl FromIterator<Box<dyn ServiceGenerator>> for MultiServiceGenerator {
+    fn from_iter<I: IntoIterator<Item = Box<dyn ServiceGenerator>>>(iter: I) -> Self {
+        Self {
+            generators: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl ServiceGenerator for MultiServiceGenerator {
+    fn generate(&mut self, service: Service, buf: &mut String) {
+        if let Some((last, generators)) = self.generators.split_last_mut() {
+            for generator in generators {
+                generator.generate(service.clone(), buf);
+            }
+            last.generate(service, buf);
+        }
+    }
+
+    fn finalize(&mut self, buf: &mut String) {
+        for generator in &mut self.generators {
+            generator.finalize(buf);
+        }
+    }
+
+    fn finalize_package(&mut self, package: &str, buf: &mut String) {
+        for generator in &mut self.generators {
+            generator.finalize_package(package, buf);
+        }
+    }
+}
+
+/// The service generators of a `Builder`, shared by the prost configurations of its runs.
+pub(crate) struct SharedServiceGenerator(pub Rc<RefCell<MultiServiceGenerator>>);
+
+impl ServiceGenerator for SharedServiceGenerator {
+    fn generate(&mut self, service: Service, buf: &mut String) {
+        self.0.borrow_mut().generate(service, buf);
+    }
+
+    fn finalize(&mut self, buf: &mut String) {
+        self.0.borrow_mut().finalize(buf);
+    }
+
+    fn finalize_package(&mut self, package: &str, buf: &mut String) {
+        self.0.borrow_mut().finalize_package(package, buf);
+    }
+}
+
+impl ServiceOption {
+    /// Whether the tonic server is generated. An option which asks for the service trait only
+    /// gets the trait by default, which doesn't need tonic.
+    pub fn build_server(&self) -> bool {
+        self.server.unwrap_or(!self.build_trait())
+    }
+
+    /// Whether the tonic client is generated, by default like the server.
+    pub fn build_client(&self) -> bool {
+        self.client.unwrap_or(!self.build_trait())
+    }
+
+    /// Whether the service trait is generated, which the HTTP gateway needs as well.
+    pub fn build_trait(&self) -> bool {
+        self.service_trait || self.http
+    }
+
+    /// The rule of this option for `explain`, with the attributes labelled by what they are put
+    /// on, e.g. `server: #[derive(Debug)]`.
+    pub(crate) fn rule(&self, index: usize, presets: &HashMap<String, Vec<String>>) -> Rule {
+        let attrs = self.option.resolve_attrs(presets);
+        let mut labelled = Vec::new();
+        let mut label = |name: &str, attrs: &[String]| {
+            labelled.extend(attrs.iter().map(|a| format!("{}: #[{}]", name, a)));
+        };
+        if self.build_server() {
+            label("server", &[&attrs[..], &self.server_attrs].concat());
+            label("server mod", &self.server_mod_attrs);
+        }
+        if self.build_client() {
+            label("client", &[&attrs[..], &self.client_attrs].concat());
+            label("client mod", &self.client_mod_attrs);
+        }
+        Rule {
+            section: Section::Services,
+            index,
+            paths: self.option.paths.clone(),
+            attrs: labelled,
+            variant_attrs: Vec::new(),
+        }
+    }
+}
+
+/// The service generators for the `services` options.
+pub(crate) fn service_generators(
+    options: Vec<ServiceOption>,
+    presets: &HashMap<String, Vec<String>>,
+    compile_well_known_types: bool,
+) -> MultiServiceGenerator {
+    let mut generators = MultiServiceGenerator::new();
+    let traits = ServiceFlag::new(&options, ServiceOption::build_trait);
+    if traits.any() {
+        generators.push(Box::new(TraitGenerator::new(traits)));
+    }
+    if options
+        .iter()
+        .any(|opt| opt.build_server() || opt.build_client())
+    {
+        generators.push(tonic_generator(options, presets, compile_well_known_types));
+    }
+    generators
+}
+
+#[cfg(feature = "tonic")]
+fn tonic_generator(
+    options: Vec<ServiceOption>,
+    presets: &HashMap<String, Vec<String>>,
+    compile_well_known_types: bool,
+) -> Box<dyn ServiceGenerator> {
+    Box::new(generator::ServiceGenerators::new(
+        options,
+        presets,
+        compile_well_known_types,
+    ))
+}
+
+#[cfg(not(feature = "tonic"))]
+fn tonic_generator(
+    _options: Vec<ServiceOption>,
+    _presets: &HashMap<String, Vec<String>>,
+    _compile_well_known_types: bool,
+) -> Box<dyn ServiceGenerator> {
+    panic!("`services` with a server or client need the `tonic` feature of prost-build-config");
+}
+
+#[cfg(feature = "tonic")]
+mod generator {
+    use std::collections::HashMap;
+
+    use prost_build::{Service, ServiceGenerator};
+
+    use super::{Compression, ServiceOption};
+    use crate::{path::is_match, to_attr};
+
+    /// A service generator which hands every service to the tonic generator of its option.
+    pub(crate) struct ServiceGenerators {
+        options: Vec<ServiceOption>,
+        generators: Vec<Box<dyn ServiceGenerator>>,
+        /// helper functions of the services in the current module, appended on `finalize`
+        helpers: String,
+    }
+
+    impl ServiceGenerators {
+        pub fn new(
+            options: Vec<ServiceOption>,
+            presets: &HashMap<String, Vec<String>>,
+            compile_well_known_types: bool,
+        ) -> Self {
+            let mut generators = Self {
+                options: Vec::new(),
+                generators: Vec::new(),
+                helpers: String::new(),
+            };
+            for opt in options {
+                let attrs = opt.option.resolve_attrs(presets);
+                let server_attrs = to_attr(&[&attrs[..], &opt.server_attrs].concat());
+                let client_attrs = to_attr(&[&attrs[..], &opt.client_attrs].concat());
+                // the generator only sees the services of this option, so "." matches them all
+                let builder = tonic_build::configure()
+                    .build_server(opt.build_server())
+                    .build_client(opt.build_client())
+                    .build_transport(opt.transport)
+                    .compile_well_known_types(compile_well_known_types)
+                    .server_attribute(".", server_attrs)
+                    .client_attribute(".", client_attrs)
+                    .server_mod_attribute(".", to_attr(&opt.server_mod_attrs))
+                    .client_mod_attribute(".", to_attr(&opt.client_mod_attrs));
+                generators.options.push(opt);
+                generators.generators.push(builder.service_generator());
+            }
+            generators
+        }
+
+        fn option_of(&self, service: &Service) -> Option<usize> {
+            let fq_name = format!(".{}.{}", service.package, service.proto_name);
+            self.options
+                .iter()
+                .position(|opt| opt.option.paths.iter().any(|p| is_match(p, &fq_name)))
+        }
+    }
+
+    impl ServiceGenerator for ServiceGenerators {
+        fn generate(&mut self, service: Service, buf: &mut String) {
+            let Some(index) = self.option_of(&service) else {
+                return;
+            };
+            self.helpers
+                .push_str(&compression_helpers(&service, &self.options[index]));
+            self.generators[index].generate(service, buf);
+        }
+
+        fn finalize(&mut self, buf: &mut String) {
+            for generator in &mut self.generators {
+                generator.finalize(buf);
+            }
+            buf.push_str(&std::mem::take(&mut self.helpers));
+        }
+
+        fn finalize_package(&mut self, package: &str, buf: &mut String) {
+            for generator in &mut self.generators {
+                generator.finalize_package(package, buf);
+            }
+        }
+    }
+
+    /// Functions which enable the compression of `opt` on the server and the client of `service`.
+    /// They get the attributes of the module they use, so that e.g. a `cfg` removes them with it.
+    fn compression_helpers(service: &Service, opt: &ServiceOption) -> String {
+        let encoding = |c: &Compression| match c {
+            Compression::Gzip => "tonic::codec::CompressionEncoding::Gzip",
+            Compression::Zstd => "tonic::codec::CompressionEncoding::Zstd",
+        };
+        let Some(send) = opt.compression.first().map(encoding) else {
+            return String::new();
+        };
+        let accept: String = opt
+            .compression
+            .iter()
+            .map(|c| format!(".accept_compressed({})", encoding(c)))
+            .collect();
+        let name = &service.name;
+        // the same module names as tonic's
+        let module = naive_snake_case(name);
+
+        let mut buf = String::new();
+        if opt.build_server() {
+            let attrs = to_attr(&opt.server_mod_attrs);
+            buf.push_str(&format!(
+                r#"/// Enable the configured compression on a `{name}Server`.
+{attrs}
+pub fn compress_{module}_server<T: {module}_server::{name}>(
+    server: {module}_server::{name}Server<T>,
+) -> {module}_server::{name}Server<T> {{
+    server{accept}.send_compressed({send})
+}}
+"#
+            ));
+        }
+        if opt.build_client() {
+            let attrs = to_attr(&opt.client_mod_attrs);
+            buf.push_str(&format!(
+                r#"/// Enable the configured compression on a `{name}Client`.
+{attrs}
+pub fn compress_{module}_client<T>(
+    client: {module}_client::{name}Client<T>,
+) -> {module}_client::{name}Client<T>
+where
+    T: tonic::client::GrpcService<tonic::body::BoxBody>,
+    T::Error: Into<tonic::codegen::StdError>,
+    T::ResponseBody: tonic::codegen::Body<Data = tonic::codegen::Bytes> + Send + 'static,
+    <T::ResponseBody as tonic::codegen::Body>::Error: Into<tonic::codegen::StdError> + Send,
+{{
+    client{accept}.send_compressed({send})
+}}
+"#
+            ));
+        }
+        buf
+    }
+
+    /// Snake case of a service name, the way tonic names its modules.
+    fn naive_snake_case(name: &str) -> String {
+        let mut s = String::new();
+        let mut chars = name.chars().peekable();
+        while let Some(c) = chars.next() {
+            s.push(c.to_ascii_lowercase());
+            if chars.peek().is_some_and(|next| next.is_uppercase()) {
+                s.push('_');
+            }
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    /// Logs the calls it gets, prefixed with its name.
+    struct Recorder(&'static str, Rc<RefCell<Vec<String>>>);
+
+    impl ServiceGenerator for Recorder {
+        fn generate(&mut self, service: Service, buf: &mut String) {
+            buf.push_str(self.0);
+            self.1
+                .borrow_mut()
+                .push(format!("{} generate {}", self.0, service.name));
+        }
+
+        fn finalize(&mut self, _buf: &mut String) {
+            self.1.borrow_mut().push(format!("{} finalize", self.0));
+        }
+
+        fn finalize_package(&mut self, package: &str, _buf: &mut String) {
+            self.1
+                .borrow_mut()
+                .push(format!("{} finalize_package {}", self.0, package));
+        }
+    }
+
+    #[test]
+    fn multi_service_generator_should_run_all_generators_in_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut generator = MultiServiceGenerator::new();
+        generator
+            .push(Box::new(Recorder("a", log.clone())))
+            .push(Box::new(Recorder("b", log.clone())));
+        let service = Service {
+            name: "Greeter".to_owned(),
+            proto_name: "Greeter".to_owned(),
+            package: "greeter".to_owned(),
+            comments: Default::default(),
+            methods: vec![],
+            options: Default::default(),
+        };
+
+        let mut buf = String::new();
+        generator.generate(service, &mut buf);
+        generator.finalize(&mut buf);
+        generator.finalize_package("greeter", &mut buf);
+        assert_eq!(buf, "ab");
+        assert_eq!(
+            *log.borrow(),
+            [
+                "a generate Greeter",
+                "b generate Greeter",
+                "a finalize",
+                "b finalize",
+                "a finalize_package greeter",
+                "b finalize_package greeter",
+            ]
+        );
+    }
+}
+
+#[cfg(all(test, feature = "tonic"))]
+mod tonic_tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{BuildConfig, Builder};
+
+    struct Marker;
+
+    impl ServiceGenerator for Marker {
+        fn generate(&mut self, service: Service, buf: &mut String) {
+            buf.push_str(&format!("pub struct {}Marker;\n", service.name));
+        }
+    }
+
+    #[allow(clippy::all)]
+    mod greeter {
+        include!("../examples/tonic/greeter.rs");
+    }
+
+    use std::pin::Pin;
+
+    use futures_core::Stream;
+    use greeter::{
+        greeter_server::{Greeter, GreeterServer},
+        HelloReply, HelloRequest,
+    };
+    use tonic::{Request, Response, Status, Streaming};
+
+    type ReplyStream = Pin<Box<dyn Stream<Item = Result<HelloReply, Status>> + Send>>;
+
+    struct Greeting;
+
+    #[tonic::async_trait]
+    impl Greeter for Greeting {
+        async fn say_hello(
+            &self,
+            request: Request<HelloRequest>,
+        ) -> Result<Response<HelloReply>, Status> {
+            let message = format!("Hello {}!", request.into_inner().name);
+            Ok(Response::new(HelloReply { message }))
+        }
+
+        type StreamHellosStream = ReplyStream;
+
+        async fn stream_hellos(
+            &self,
+            _request: Request<HelloRequest>,
+        ) -> Result<Response<ReplyStream>, Status> {
+            Err(Status::unimplemented("stream_hellos"))
+        }
+
+        async fn greet_all(
+            &self,
+            _request: Request<Streaming<HelloRequest>>,
+        ) -> Result<Response<HelloReply>, Status> {
+            Err(Status::unimplemented("greet_all"))
+        }
+
+        type ChatStream = ReplyStream;
+
+        async fn chat(
+            &self,
+            _request: Request<Streaming<HelloRequest>>,
+        ) -> Result<Response<ReplyStream>, Status> {
+            Err(Status::unimplemented("chat"))
+        }
+    }
+
+    #[test]
+    fn services_should_be_generated_with_tonic() {
+        let config = BuildConfig::from_path("examples/tonic.yml").unwrap();
+        Builder::from(config).build_protos();
+
+        let code = fs::read_to_string("examples/tonic/greeter.rs").unwrap();
+        assert!(code.contains("#[derive(Eq)]\n#[allow(clippy::derive_partial_eq_without_eq)]"));
+        assert!(code.contains("#[cfg(feature = \"tonic\")]\npub mod greeter_server {"));
+        assert!(code.contains("#[allow(missing_docs)]\n    #[derive(Debug)]\n    pub struct GreeterServer<T: Greeter>"));
+        assert!(code.contains(
+            "#[cfg(feature = \"tonic\")]\npub fn compress_greeter_server<T: greeter_server::Greeter>("
+        ));
+        assert!(code.contains("#[cfg(any())]\npub fn compress_greeter_client<T>("));
+        assert!(!code.contains("pub async fn connect"));
+
+        // the client and its helper are always compiled out by `cfg(any())`
+        let server = greeter::compress_greeter_server(GreeterServer::new(Greeting));
+        let _ = server;
+    }
+
+    #[test]
+    fn service_generators_should_run_with_tonic() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = BuildConfig::from_path("examples/tonic.yml").unwrap();
+        config.output = Some(dir.path().to_string_lossy().to_string());
+        Builder::from(config)
+            .service_generator(Box::new(Marker))
+            .build_protos();
+
+        let code = fs::read_to_string(dir.path().join("greeter.rs")).unwrap();
+        assert!(code.contains("pub struct GreeterMarker;\n"));
+        assert!(code.contains("pub mod greeter_server {"));
+    }
+}
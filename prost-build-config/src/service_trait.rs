@@ -0,0 +1,337 @@
+//! Plain async traits for services, which don't depend on tonic: one method per RPC, enums of the
+//! requests and responses with a dispatcher, and a mock implementation for tests. Streams are
+//! `futures_core::Stream`s.
+
+use std::fmt::Write;
+
+use prost_build::{Method, Service, ServiceGenerator};
+
+use crate::{
+    ident::{to_snake, to_upper_camel},
+    service::ServiceFlag,
+};
+
+/// Generates the `<service>_service` modules of the services whose option has `trait` or `http`
+/// set.
+pub(crate) struct TraitGenerator {
+    services: ServiceFlag,
+}
+
+impl TraitGenerator {
+    pub fn new(services: ServiceFlag) -> Self {
+        Self { services }
+    }
+}
+
+impl ServiceGenerator for TraitGenerator {
+    fn generate(&mut self, service: Service, buf: &mut String) {
+        let fq_name = format!(".{}.{}", service.package, service.proto_name);
+        if self.services.is_set(&fq_name) {
+            append_service(buf, &service);
+        }
+    }
+}
+
+fn append_service(buf: &mut String, service: &Service) {
+    let name = &service.name;
+    let _ = writeln!(
+        buf,
+        "/// Async trait, dispatcher and mock of the `{}.{}` service.",
+        service.package, service.proto_name
+    );
+    let _ = writeln!(buf, "pub mod {}_service {{", to_snake(name));
+    buf.push_str("    /// error of the service methods\n");
+    buf.push_str(
+        "    pub type Error = ::std::boxed::Box<dyn ::std::error::Error + Send + Sync>;\n",
+    );
+    buf.push_str("    /// stream of requests or responses\n");
+    buf.push_str("    pub type BoxStream<T> =\n");
+    buf.push_str("        ::core::pin::Pin<::std::boxed::Box<dyn ::futures_core::Stream<Item = T> + Send>>;\n\n");
+
+    // the trait
+    service.comments.append_with_indent(1, buf);
+    let _ = writeln!(buf, "    pub trait {}: Send + Sync + 'static {{", name);
+    for method in &service.methods {
+        method.comments.append_with_indent(2, buf);
+        let _ = writeln!(
+            buf,
+            "        fn {}(&self, request: {}) -> impl ::core::future::Future<Output = Result<{}, Error>> + Send;",
+            method.name,
+            input_type(method),
+            output_type(method)
+        );
+    }
+    buf.push_str("    }\n\n");
+
+    // the request and response enums, and the dispatcher
+    let _ = writeln!(buf, "    /// A request to a method of `{}`.", name);
+    let _ = writeln!(buf, "    pub enum {}Request {{", name);
+    for method in &service.methods {
+        let _ = writeln!(
+            buf,
+            "        {}({}),",
+            to_upper_camel(&method.proto_name),
+            input_type(method)
+        );
+    }
+    buf.push_str("    }\n\n");
+    let _ = writeln!(buf, "    /// The response of a method of `{}`.", name);
+    let _ = writeln!(buf, "    pub enum {}Response {{", name);
+    for method in &service.methods {
+        let _ = writeln!(
+            buf,
+            "        {}({}),",
+            to_upper_camel(&method.proto_name),
+            output_type(method)
+        );
+    }
+    buf.push_str("    }\n\n");
+    buf.push_str("    /// Call the method of `service` which `request` is for.\n");
+    let _ = writeln!(
+        buf,
+        "    pub async fn dispatch<T: {}>(service: &T, request: {}Request) -> Result<{}Response, Error> {{",
+        name, name, name
+    );
+    buf.push_str("        match request {\n");
+    for method in &service.methods {
+        let variant = to_upper_camel(&method.proto_name);
+        let _ = writeln!(
+            buf,
+            "            {}Request::{}(request) => service.{}(request).await.map({}Response::{}),",
+            name, variant, method.name, name, variant
+        );
+    }
+    buf.push_str("        }\n    }\n\n");
+
+    append_mock(buf, service);
+    buf.push_str("}\n");
+}
+
+/// Append `Mock<Service>`, which answers with handlers set by its `with_<method>` methods.
+/// Handlers take and return `Vec`s instead of streams.
+fn append_mock(buf: &mut String, service: &Service) {
+    let name = &service.name;
+    let handler = |method: &Method| {
+        let (input, output) = (
+            qualified(&method.input_type),
+            qualified(&method.output_type),
+        );
+        let input = match method.client_streaming {
+            true => format!("::std::vec::Vec<{}>", input),
+            false => input,
+        };
+        let output = match method.server_streaming {
+            true => format!("::std::vec::Vec<{}>", output),
+            false => output,
+        };
+        format!("Fn({}) -> Result<{}, Error> + Send + Sync", input, output)
+    };
+
+    let _ = writeln!(
+        buf,
+        "    /// `{}` which answers with the handlers set on it, for tests. Methods without a handler\n    /// fail.",
+        name
+    );
+    buf.push_str("    #[allow(clippy::type_complexity)]\n    #[derive(Default)]\n");
+    let _ = writeln!(buf, "    pub struct Mock{} {{", name);
+    for method in &service.methods {
+        let _ = writeln!(
+            buf,
+            "        {}: Option<::std::boxed::Box<dyn {}>>,",
+            method.name,
+            handler(method)
+        );
+    }
+    buf.push_str("    }\n\n");
+
+    let _ = writeln!(buf, "    impl Mock{} {{", name);
+    for method in &service.methods {
+        let _ = writeln!(buf, "        /// Answer `{}` with `handler`.", method.name);
+        let _ = writeln!(
+            buf,
+            "        pub fn with_{}(mut self, handler: impl {} + 'static) -> Self {{",
+            method.name.trim_start_matches("r#"),
+            handler(method)
+        );
+        let _ = writeln!(
+            buf,
+            "            self.{} = Some(::std::boxed::Box::new(handler));\n            self\n        }}",
+            method.name
+        );
+    }
+    buf.push_str("    }\n\n");
+
+    let _ = writeln!(buf, "    impl {} for Mock{} {{", name, name);
+    for method in &service.methods {
+        let _ = writeln!(
+            buf,
+            "        async fn {}(&self, request: {}) -> Result<{}, Error> {{",
+            method.name,
+            input_type(method),
+            output_type(method)
+        );
+        let _ = writeln!(
+            buf,
+            "            let handler = self.{}.as_ref().ok_or(\"Mock{}::{} is not set\")?;",
+            method.name,
+            name,
+            method.name.trim_start_matches("r#")
+        );
+        let request = match method.client_streaming {
+            true => "collect(request).await",
+            false => "request",
+        };
+        match method.server_streaming {
+            true => {
+                let _ = writeln!(
+                    buf,
+                    "            let responses = handler({})?;\n            Ok(::std::boxed::Box::pin(IterStream(responses.into_iter().map(Ok))))",
+                    request
+                );
+            }
+            false => {
+                let _ = writeln!(buf, "            handler({})", request);
+            }
+        }
+        buf.push_str("        }\n");
+    }
+    buf.push_str("    }\n");
+
+    // helpers, only when they are used
+    if service.methods.iter().any(|m| m.server_streaming) {
+        buf.push_str(
+            r#"
+    /// Stream of the items of an iterator.
+    struct IterStream<I>(I);
+
+    impl<I: Iterator + Unpin> ::futures_core::Stream for IterStream<I> {
+        type Item = I::Item;
+
+        fn poll_next(
+            mut self: ::core::pin::Pin<&mut Self>,
+            _cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<Option<I::Item>> {
+            ::core::task::Poll::Ready(self.0.next())
+        }
+    }
+"#,
+        );
+    }
+    if service.methods.iter().any(|m| m.client_streaming) {
+        buf.push_str(
+            r#"
+    /// Collect the items of a stream.
+    async fn collect<T>(mut stream: BoxStream<T>) -> ::std::vec::Vec<T> {
+        let mut items = ::std::vec::Vec::new();
+        while let Some(item) =
+            ::core::future::poll_fn(|cx| ::futures_core::Stream::poll_next(stream.as_mut(), cx)).await
+        {
+            items.push(item);
+        }
+        items
+    }
+"#,
+        );
+    }
+}
+
+/// The type of the request parameter of `method`, from within the service module.
+fn input_type(method: &Method) -> String {
+    let input = qualified(&method.input_type);
+    match method.client_streaming {
+        true => format!("BoxStream<{}>", input),
+        false => input,
+    }
+}
+
+/// The type of the successful response of `method`, from within the service module.
+fn output_type(method: &Method) -> String {
+    let output = qualified(&method.output_type);
+    match method.server_streaming {
+        true => format!("BoxStream<Result<{}, Error>>", output),
+        false => output,
+    }
+}
+
+/// A type of prost from within the service module. prost's types are relative to the package
+/// module, unless they are absolute, e.g. `::prost_types::Empty`.
+fn qualified(ty: &str) -> String {
+    match ty.starts_with("::") {
+        true => ty.to_owned(),
+        false => format!("super::{}", ty),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use futures_core::Stream;
+
+    use crate::{BuildConfig, Builder};
+
+    #[allow(clippy::all, dead_code)]
+    mod greeter {
+        include!("../examples/service_trait/greeter.rs");
+    }
+
+    use greeter::{
+        greeter_service::{dispatch, BoxStream, GreeterRequest, GreeterResponse, MockGreeter},
+        HelloReply, HelloRequest,
+    };
+
+    /// Stream of the given items.
+    struct Items<T>(Vec<T>);
+
+    impl<T: Unpin> Stream for Items<T> {
+        type Item = T;
+
+        fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<T>> {
+            Poll::Ready((!self.0.is_empty()).then(|| self.0.remove(0)))
+        }
+    }
+
+    fn requests(names: &[&str]) -> BoxStream<HelloRequest> {
+        let requests = names.iter().map(|name| HelloRequest {
+            name: name.to_string(),
+        });
+        Box::pin(Items(requests.collect()))
+    }
+
+    #[test]
+    fn service_traits_should_be_generated_without_tonic() {
+        let config = BuildConfig::from_path("examples/service_trait.yml").unwrap();
+        Builder::from(config).build_protos();
+
+        let code = fs::read_to_string("examples/service_trait/greeter.rs").unwrap();
+        assert!(code.contains("pub mod greeter_service {"));
+        assert!(code.contains("pub trait Greeter: Send + Sync + 'static {"));
+        assert!(code.contains("impl Greeter for MockGreeter {"));
+        assert!(!code.contains("greeter_server"));
+        assert!(!code.contains("greeter_client"));
+    }
+
+    #[tokio::test]
+    async fn client_streaming_should_be_dispatched_to_the_mock() {
+        let mock = MockGreeter::default().with_greet_all(|requests| {
+            let names: Vec<_> = requests.into_iter().map(|r| r.name).collect();
+            Ok(HelloReply {
+                message: format!("Hello {}!", names.join(" and ")),
+            })
+        });
+
+        let request = GreeterRequest::GreetAll(requests(&["alice", "bob"]));
+        let Ok(GreeterResponse::GreetAll(reply)) = dispatch(&mock, request).await else {
+            panic!("greet_all should be answered by the mock");
+        };
+        assert_eq!(reply.message, "Hello alice and bob!");
+
+        let request = GreeterRequest::Chat(requests(&["alice"]));
+        let err = dispatch(&mock, request).await.err().unwrap();
+        assert_eq!(err.to_string(), "MockGreeter::chat is not set");
+    }
+}
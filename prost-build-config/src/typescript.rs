@@ -0,0 +1,395 @@
+//! TypeScript types of the messages, rendered from their JSON Schemas so that both describe the
+//! same JSON. Every package gets a `<package>.d.ts` file, nested types are named after their
+//! parents, e.g. `Hello_World`, and types of other packages are imported. Enum fields are `i32`s
+//! in prost, so their types are inlined in the fields. The enums the fields refer to are declared
+//! as well, as the string unions of their JSON Schemas, for the JSON of the Rust enums.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Write,
+};
+
+use prost_types::{field_descriptor_proto::Type, FileDescriptorProto};
+use serde_json::{Map, Value};
+
+use crate::{
+    json_schema::{ref_name, Schema},
+    path,
+};
+
+/// Where a type is declared.
+struct Declared<'a> {
+    package: &'a str,
+    name: String,
+}
+
+struct Context<'a> {
+    /// the declared types by fully-qualified name
+    types: HashMap<&'a str, Declared<'a>>,
+    /// the package of the file being rendered
+    package: &'a str,
+    /// types of other packages used by the file, by package
+    imports: BTreeMap<&'a str, BTreeSet<String>>,
+}
+
+/// A `<package>.d.ts` file for the message schemas of every package, and the schemas of the enums
+/// the fields of `files` refer to. `default_package` is the file name for types without package.
+pub(crate) fn files(
+    files: &[FileDescriptorProto],
+    schemas: &[Schema],
+    default_package: &str,
+) -> Vec<(String, String)> {
+    let enums = referenced_enums(files);
+    let schemas: Vec<_> = schemas
+        .iter()
+        .filter(|s| is_message(s) || enums.contains(s.fq_name.as_str()))
+        .collect();
+    let types = schemas
+        .iter()
+        .map(|schema| {
+            let name = type_name(&schema.package, &schema.fq_name);
+            let declared = Declared {
+                package: &schema.package,
+                name,
+            };
+            (schema.fq_name.as_str(), declared)
+        })
+        .collect();
+    let mut packages: Vec<&str> = Vec::new();
+    for schema in &schemas {
+        if !packages.contains(&schema.package.as_str()) {
+            packages.push(&schema.package);
+        }
+    }
+
+    let mut ctx = Context {
+        types,
+        package: "",
+        imports: BTreeMap::new(),
+    };
+    packages
+        .into_iter()
+        .map(|package| {
+            ctx.package = package;
+            ctx.imports.clear();
+            let mut body = String::new();
+            for schema in schemas.iter().filter(|s| s.package == package) {
+                ctx.append_declaration(&mut body, schema);
+            }
+
+            let mut buf = String::from("// This file is @generated by prost-build-config.\n\n");
+            for (package, names) in &ctx.imports {
+                let names: Vec<_> = names.iter().map(|n| n.as_str()).collect();
+                let _ = writeln!(
+                    buf,
+                    "import type {{ {} }} from \"./{}\";",
+                    names.join(", "),
+                    file_stem(package, default_package)
+                );
+            }
+            if !ctx.imports.is_empty() {
+                buf.push('\n');
+            }
+            buf.push_str(&body);
+            (format!("{}.d.ts", file_stem(package, default_package)), buf)
+        })
+        .collect()
+}
+
+fn file_stem<'a>(package: &'a str, default_package: &'a str) -> &'a str {
+    match package {
+        "" => default_package,
+        package => package,
+    }
+}
+
+/// The name of a type within its package file, e.g. `Hello_World` for `.helloworld.Hello.World`.
+fn type_name(package: &str, fq_name: &str) -> String {
+    let name = fq_name.trim_start_matches('.');
+    let name = match package {
+        "" => name,
+        package => name
+            .strip_prefix(package)
+            .map_or(name, |n| n.trim_start_matches('.')),
+    };
+    name.replace('.', "_")
+}
+
+impl<'a> Context<'a> {
+    fn append_declaration(&mut self, buf: &mut String, schema: &'a Schema) {
+        let name = self.types[schema.fq_name.as_str()].name.clone();
+        let mut lines = description(&schema.value);
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!("`{}`", schema.fq_name.trim_start_matches('.')));
+        append_doc(buf, "", &lines);
+        let object = schema.value.as_object();
+        match object
+            .and_then(|o| o.get("properties"))
+            .and_then(Value::as_object)
+        {
+            Some(properties) => {
+                let _ = writeln!(buf, "export interface {} {{", name);
+                let required = object.map(required).unwrap_or_default();
+                for (key, value) in properties {
+                    let mut lines = description(value);
+                    lines.extend(format_doc(value));
+                    append_doc(buf, "  ", &lines);
+                    let optional = match required.contains(&key.as_str()) {
+                        true => "",
+                        false => "?",
+                    };
+                    let _ = writeln!(
+                        buf,
+                        "  {}{}: {};",
+                        property_name(key),
+                        optional,
+                        self.ts_type(value)
+                    );
+                }
+                buf.push_str("}\n\n");
+            }
+            None => {
+                let _ = writeln!(
+                    buf,
+                    "export type {} = {};\n",
+                    name,
+                    self.ts_type(&schema.value)
+                );
+            }
+        }
+    }
+
+    /// The TypeScript type of the values `schema` accepts.
+    fn ts_type(&mut self, schema: &Value) -> String {
+        let Some(schema) = schema.as_object() else {
+            return "unknown".to_owned();
+        };
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return self.reference(reference);
+        }
+        if let Some(value) = schema.get("const") {
+            return value.to_string();
+        }
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            let values: Vec<_> = values.iter().map(|v| v.to_string()).collect();
+            return values.join(" | ");
+        }
+        for (key, separator) in [("anyOf", " | "), ("oneOf", " | "), ("allOf", " & ")] {
+            if let Some(schemas) = schema.get(key).and_then(Value::as_array) {
+                let types: Vec<_> = schemas
+                    .iter()
+                    .map(|s| parenthesize(self.ts_type(s)))
+                    .collect();
+                return types.join(separator);
+            }
+        }
+        match schema.get("type").and_then(Value::as_str) {
+            Some("string") => "string".to_owned(),
+            Some("integer" | "number") => "number".to_owned(),
+            Some("boolean") => "boolean".to_owned(),
+            Some("null") => "null".to_owned(),
+            Some("array") => match schema.get("items") {
+                Some(items) => format!("{}[]", parenthesize(self.ts_type(items))),
+                None => "unknown[]".to_owned(),
+            },
+            Some("object") => self.object_type(schema),
+            _ => "unknown".to_owned(),
+        }
+    }
+
+    /// An inline object type, e.g. of a oneof variant or a map.
+    fn object_type(&mut self, schema: &Map<String, Value>) -> String {
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            let required = required(schema);
+            let fields: Vec<_> = properties
+                .iter()
+                .map(|(key, value)| {
+                    let optional = match required.contains(&key.as_str()) {
+                        true => "",
+                        false => "?",
+                    };
+                    format!(
+                        "{}{}: {}",
+                        property_name(key),
+                        optional,
+                        self.ts_type(value)
+                    )
+                })
+                .collect();
+            return format!("{{ {} }}", fields.join("; "));
+        }
+        let value = match schema.get("additionalProperties") {
+            Some(value) => self.ts_type(value),
+            None => "unknown".to_owned(),
+        };
+        format!("{{ [key: string]: {} }}", value)
+    }
+
+    /// The name of the referenced type, imported if it is in another package.
+    fn reference(&mut self, reference: &str) -> String {
+        let declared = ref_name(reference).and_then(|name| self.types.get(name.as_str()));
+        let Some(declared) = declared else {
+            return "unknown".to_owned();
+        };
+        if declared.package != self.package {
+            self.imports
+                .entry(declared.package)
+                .or_default()
+                .insert(declared.name.clone());
+        }
+        declared.name.clone()
+    }
+}
+
+fn required(schema: &Map<String, Value>) -> Vec<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// The fully-qualified names of the enums used by the fields of the messages of `files`.
+fn referenced_enums(files: &[FileDescriptorProto]) -> BTreeSet<&str> {
+    path::messages(files)
+        .into_values()
+        .flat_map(|msg| &msg.field)
+        .filter(|field| field.r#type() == Type::Enum)
+        .map(|field| field.type_name())
+        .collect()
+}
+
+/// Whether `schema` is the schema of a message rather than of a Rust enum.
+fn is_message(schema: &Schema) -> bool {
+    schema.value.get("enum").is_none()
+}
+
+/// Append a doc comment with `lines`, on one line if it has one.
+fn append_doc(buf: &mut String, indent: &str, lines: &[String]) {
+    // `*/` would end the comment
+    let lines: Vec<_> = lines.iter().map(|l| l.replace("*/", "*\\/")).collect();
+    match &lines[..] {
+        [] => {}
+        [line] => {
+            let _ = writeln!(buf, "{}/** {} */", indent, line);
+        }
+        lines => {
+            let _ = writeln!(buf, "{}/**", indent);
+            for line in lines {
+                let _ = match line.is_empty() {
+                    true => writeln!(buf, "{} *", indent),
+                    false => writeln!(buf, "{} * {}", indent, line),
+                };
+            }
+            let _ = writeln!(buf, "{} */", indent);
+        }
+    }
+}
+
+/// The lines of the description of `schema`, from the comments of the proto file.
+fn description(schema: &Value) -> Vec<String> {
+    match schema.get("description").and_then(Value::as_str) {
+        Some(description) => description.lines().map(|l| l.to_owned()).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// The doc comment of a property, from the encoding or format of its strings.
+fn format_doc(schema: &Value) -> Option<String> {
+    let schema = match schema.get("items") {
+        Some(items) => items,
+        None => schema,
+    };
+    schema
+        .get("contentEncoding")
+        .or_else(|| schema.get("format"))
+        .and_then(Value::as_str)
+        .filter(|_| schema.get("type").and_then(Value::as_str) == Some("string"))
+        .map(|format| format!("`{}` string", format))
+}
+
+/// A union or intersection in parentheses, to be used as an array item or a union member.
+fn parenthesize(ty: String) -> String {
+    match ty.contains(" | ") || ty.contains(" & ") {
+        true => format!("({})", ty),
+        false => ty,
+    }
+}
+
+/// A property name, quoted unless it is a valid identifier.
+fn property_name(name: &str) -> String {
+    let is_ident = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    match is_ident {
+        true => name.to_owned(),
+        false => Value::String(name.to_owned()).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typescript_types_should_match_json_schema() {
+        let code = std::fs::read_to_string("examples/json_schema/typescript/account.d.ts").unwrap();
+        assert!(code.contains(
+            "/**\n * A user account.\n *\n * `account.User`\n */\nexport interface User {\n"
+        ));
+        assert!(code.contains(
+            "  /**\n   * Name shown to other users,\n   * empty for the `id`.\n   */\n  displayName?: string;\n"
+        ));
+        // the fields of `User` have `serde(default)`, `zip` has it too
+        assert!(code.contains("  /** City or town. */\n  city: string;\n  zip?: string;\n"));
+        assert!(code.contains("  /** `uuid` string */\n  id?: string;\n"));
+        assert!(code.contains("  /** `base64url` string */\n  avatar?: string;\n"));
+        assert!(code.contains("  email?: string | null;\n"));
+        assert!(code.contains("  address?: Address | null;\n"));
+        assert!(code.contains("  counters?: { [key: string]: number };\n"));
+        assert!(code.contains(
+            "  login?: ({ type: \"username\"; value: string } | { type: \"phone\"; value: number }) | null;\n"
+        ));
+        assert!(!code.contains("password"));
+        // the fields hold the numbers of the enum, the Rust enum is declared for its JSON
+        assert!(code.contains("  role?: number;\n"));
+        assert!(code.contains(
+            "/** `account.Role` */\nexport type Role = \"unspecified\" | \"admin\" | \"member\";\n"
+        ));
+    }
+
+    #[test]
+    fn nested_and_imported_types_should_be_named() {
+        assert_eq!(type_name("a.b", ".a.b.Hello.World"), "Hello_World");
+        assert_eq!(type_name("", ".Hello"), "Hello");
+        assert_eq!(property_name("user-name"), "\"user-name\"");
+
+        let schemas = vec![
+            Schema {
+                package: "a".to_owned(),
+                fq_name: ".a.Msg".to_owned(),
+                value: serde_json::json!({
+                    "type": "object",
+                    "properties": { "other": { "type": "array", "items": { "$ref": "b.Other.schema.json" } } },
+                    "required": ["other"],
+                }),
+            },
+            Schema {
+                package: "b".to_owned(),
+                fq_name: ".b.Other".to_owned(),
+                value: serde_json::json!({ "type": "object", "properties": {} }),
+            },
+        ];
+        let files = files(&[], &schemas, "_");
+        assert_eq!(files[0].0, "a.d.ts");
+        assert!(files[0].1.contains("import type { Other } from \"./b\";\n"));
+        assert!(files[0].1.contains("  other: Other[];\n"));
+        assert_eq!(files[1].0, "b.d.ts");
+    }
+}
@@ -2,6 +2,8 @@ use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use bytes::Bytes;
 use serde::{de, ser::SerializeSeq, Deserialize, Deserializer, Serializer};
 
+use crate::Error;
+
 pub fn serialize_buf<S, T>(bytes: T, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -16,9 +18,7 @@ where
     D: Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    URL_SAFE_NO_PAD
-        .decode(s.as_bytes())
-        .map_err(de::Error::custom)
+    decode_buf(s.as_bytes()).map_err(de::Error::custom)
 }
 
 pub fn deserialize_buf_bytes<'de, D>(deserializer: D) -> Result<Bytes, D::Error>
@@ -26,9 +26,7 @@ where
     D: Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    let r = URL_SAFE_NO_PAD
-        .decode(s.as_bytes())
-        .map_err(de::Error::custom)?;
+    let r = decode_buf(s.as_bytes()).map_err(de::Error::custom)?;
     Ok(Bytes::from(r))
 }
 
@@ -63,12 +61,8 @@ where
             S: de::SeqAccess<'de>,
         {
             let mut data: Vec<Vec<u8>> = Vec::with_capacity(seq.size_hint().unwrap_or(0));
-            while let Some(v) = seq.next_element::<Vec<u8>>()? {
-                data.push(
-                    URL_SAFE_NO_PAD
-                        .decode(v.as_slice())
-                        .map_err(de::Error::custom)?,
-                );
+            while let Some(Base64Buf(v)) = seq.next_element()? {
+                data.push(v);
             }
             Ok(data)
         }
@@ -95,11 +89,8 @@ where
             S: de::SeqAccess<'de>,
         {
             let mut data: Vec<Bytes> = Vec::with_capacity(seq.size_hint().unwrap_or(0));
-            while let Some(v) = seq.next_element::<Vec<u8>>()? {
-                let r = URL_SAFE_NO_PAD
-                    .decode(v.as_slice())
-                    .map_err(de::Error::custom)?;
-                data.push(Bytes::from(r));
+            while let Some(Base64Buf(v)) = seq.next_element()? {
+                data.push(Bytes::from(v));
             }
             Ok(data)
         }
@@ -108,6 +99,23 @@ where
     deserializer.deserialize_seq(Visitor)
 }
 
+fn decode_buf(data: &[u8]) -> Result<Vec<u8>, Error> {
+    Ok(URL_SAFE_NO_PAD.decode(data)?)
+}
+
+/// An element of a repeated bytes field. It is decoded while being deserialized, so that errors
+/// are reported against the element rather than the whole field.
+struct Base64Buf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for Base64Buf {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_buf_vec(deserializer).map(Self)
+    }
+}
+
 #[cfg(all(feature = "b64", feature = "json"))]
 #[cfg(test)]
 mod tests {
@@ -219,6 +227,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn repeat_buf_decoded_from_base64() {
+        let hello = Hello {
+            list_vec: vec![b"hello".to_vec(), b"world".to_vec()],
+            list_bytes: vec![Bytes::from("world")],
+            ..Default::default()
+        };
+        let s = serde_json::to_string(&hello).unwrap();
+        let data = serde_json::from_str::<Hello>(&s).unwrap();
+        assert_eq!(data, hello);
+    }
+
+    #[test]
+    fn invalid_base64_should_be_rejected() {
+        let err = serde_json::from_str::<Hello>(r#"{"value_vec":"d29y!GQ"}"#).unwrap_err();
+        assert!(err.to_string().starts_with("invalid base64: "));
+    }
+
     #[test]
     fn repeat_bytes_encoded_with_base64() {
         let hello = Hello {
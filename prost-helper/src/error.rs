@@ -0,0 +1,79 @@
+use thiserror::Error;
+
+use crate::LimitError;
+
+/// Errors returned by the fallible APIs of this crate.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("failed to decode message: {0}")]
+    Decode(#[from] prost::DecodeError),
+    #[error("failed to encode message: {0}")]
+    Encode(#[from] prost::EncodeError),
+    #[cfg(feature = "json")]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "b64")]
+    #[error("invalid base64: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[cfg(feature = "id")]
+    #[error("invalid uuid: {0}")]
+    IdParse(#[from] uuid7::ParseError),
+    #[cfg(feature = "text")]
+    #[error("invalid text format: {0}")]
+    Text(#[from] prost_reflect::text_format::ParseError),
+    #[cfg(feature = "text")]
+    #[error("unknown message type: {0}")]
+    UnknownMessage(String),
+    #[error("invalid id length: expected {expected} bytes, got {actual}")]
+    IdLength { expected: usize, actual: usize },
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+    #[error("{field}: {source}")]
+    Field {
+        field: String,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Attach the name of the field which caused the error.
+    pub fn with_field(self, field: impl Into<String>) -> Self {
+        Self::Field {
+            field: field.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Name of the field which caused the error, if known.
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            Self::Field { field, .. } => Some(field),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_with_field_should_keep_context() {
+        let err = Error::IdLength {
+            expected: 16,
+            actual: 3,
+        }
+        .with_field("id");
+        assert_eq!(err.field(), Some("id"));
+        assert_eq!(
+            err.to_string(),
+            "id: invalid id length: expected 16 bytes, got 3"
+        );
+        let source = std::error::Error::source(&err).unwrap();
+        assert_eq!(
+            source.to_string(),
+            "invalid id length: expected 16 bytes, got 3"
+        );
+    }
+}
@@ -2,20 +2,17 @@ use bytes::Bytes;
 use serde::{de, ser, ser::SerializeSeq, Deserialize, Deserializer, Serializer};
 use uuid7::Uuid;
 
+use crate::Error;
+
+const ID_LENGTH: usize = 16;
+
 pub fn serialize_id<S, T>(bytes: T, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
     T: AsRef<[u8]>,
 {
-    let bytes = bytes.as_ref();
-    if bytes.is_empty() {
-        return serializer.serialize_str("");
-    }
-    if bytes.len() != 16 {
-        return Err(ser::Error::custom("invalid id length"));
-    }
-    let id = u128::from_be_bytes(bytes.try_into().map_err(ser::Error::custom)?);
-    serializer.collect_str(&Uuid::from(id).to_string())
+    let id = id_to_string(bytes.as_ref()).map_err(ser::Error::custom)?;
+    serializer.collect_str(&id)
 }
 
 pub fn deserialize_id_vec<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
@@ -23,11 +20,7 @@ where
     D: Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    if s.is_empty() {
-        return Ok(vec![]);
-    }
-    let id: Uuid = s.parse().map_err(de::Error::custom)?;
-    Ok(id.as_bytes().to_vec())
+    id_from_str(&s).map_err(de::Error::custom)
 }
 
 pub fn deserialize_id_bytes<'de, D>(deserializer: D) -> Result<Bytes, D::Error>
@@ -44,16 +37,7 @@ where
 {
     let mut seq = serializer.serialize_seq(Some(data.len()))?;
     for item in data {
-        let bytes = item.as_ref();
-        if bytes.is_empty() {
-            seq.serialize_element("")?;
-            continue;
-        }
-        if bytes.len() != 16 {
-            return Err(ser::Error::custom("invalid id length"));
-        }
-        let id = u128::from_be_bytes(item.as_ref().try_into().map_err(ser::Error::custom)?);
-        let e = Uuid::from(id).to_string();
+        let e = id_to_string(item.as_ref()).map_err(ser::Error::custom)?;
         seq.serialize_element(&e)?;
     }
     seq.end()
@@ -77,13 +61,8 @@ where
             S: de::SeqAccess<'de>,
         {
             let mut data: Vec<Vec<u8>> = Vec::with_capacity(seq.size_hint().unwrap_or(0));
-            while let Some(s) = seq.next_element::<String>()? {
-                if s.is_empty() {
-                    data.push(vec![]);
-                    continue;
-                }
-                let id: Uuid = s.parse().map_err(de::Error::custom)?;
-                data.push(id.as_bytes().to_vec());
+            while let Some(IdBuf(v)) = seq.next_element()? {
+                data.push(v);
             }
             Ok(data)
         }
@@ -110,13 +89,8 @@ where
             S: de::SeqAccess<'de>,
         {
             let mut data: Vec<Bytes> = Vec::with_capacity(seq.size_hint().unwrap_or(0));
-            while let Some(s) = seq.next_element::<String>()? {
-                if s.is_empty() {
-                    data.push(Bytes::new());
-                    continue;
-                }
-                let id: Uuid = s.parse().map_err(de::Error::custom)?;
-                data.push(Bytes::from(id.as_bytes().to_vec()));
+            while let Some(IdBuf(v)) = seq.next_element()? {
+                data.push(Bytes::from(v));
             }
             Ok(data)
         }
@@ -125,6 +99,40 @@ where
     deserializer.deserialize_seq(Visitor)
 }
 
+/// Format 16 id bytes as a uuid string. Empty bytes are formatted as an empty string.
+pub fn id_to_string(bytes: &[u8]) -> Result<String, Error> {
+    if bytes.is_empty() {
+        return Ok(String::new());
+    }
+    let bytes: [u8; ID_LENGTH] = bytes.try_into().map_err(|_| Error::IdLength {
+        expected: ID_LENGTH,
+        actual: bytes.len(),
+    })?;
+    Ok(Uuid::from(u128::from_be_bytes(bytes)).to_string())
+}
+
+/// Parse a uuid string into 16 id bytes. An empty string is parsed as empty bytes.
+pub fn id_from_str(s: &str) -> Result<Vec<u8>, Error> {
+    if s.is_empty() {
+        return Ok(vec![]);
+    }
+    let id: Uuid = s.parse()?;
+    Ok(id.as_bytes().to_vec())
+}
+
+/// An element of a repeated id field. It is parsed while being deserialized, so that errors
+/// are reported against the element rather than the whole field.
+struct IdBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for IdBuf {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_id_vec(deserializer).map(Self)
+    }
+}
+
 #[cfg(all(feature = "id", feature = "json"))]
 #[cfg(test)]
 mod tests {
@@ -257,6 +265,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn invalid_id_should_be_rejected() {
+        let hello = Hello {
+            value_vec: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let err = serde_json::to_string(&hello).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid id length: expected 16 bytes, got 3"
+        );
+
+        let err = serde_json::from_str::<Hello>(r#"{"value_vec":"not-a-uuid"}"#).unwrap_err();
+        assert!(err.to_string().starts_with("invalid uuid: "));
+    }
+
+    #[test]
+    fn invalid_id_should_report_field_path() {
+        let s = format!(r#"{{"list_vec":["{0}","{0}","{0}","not-a-uuid"]}}"#, uuid());
+        let err = crate::from_json_with_path::<Hello>(&s).unwrap_err();
+        assert_eq!(err.field(), Some("list_vec[3]"));
+        assert!(err.to_string().starts_with("list_vec[3]: invalid uuid"));
+
+        let s = format!(
+            r#"{{"list_bytes":["{0}","{0}","{0}","not-a-uuid"]}}"#,
+            uuid()
+        );
+        let err = crate::from_json_with_path::<Hello>(&s).unwrap_err();
+        assert_eq!(err.field(), Some("list_bytes[3]"));
+        assert!(err.to_string().starts_with("list_bytes[3]: invalid uuid"));
+    }
+
     fn uuid() -> &'static str {
         "018c8afb-43d7-7f73-be38-95ed30027670"
     }
@@ -34,6 +34,14 @@ use serde::{Deserialize, Deserializer};
 
 pub mod macros;
 
+mod error;
+mod limits;
+mod redact;
+
+pub use error::Error;
+pub use limits::*;
+pub use redact::*;
+
 #[cfg(feature = "b64")]
 mod buf;
 
@@ -46,10 +54,44 @@ mod id;
 #[cfg(feature = "id")]
 pub use id::*;
 
+#[cfg(feature = "proto3")]
+pub mod proto3;
+
+#[cfg(feature = "text")]
+mod text;
+
+#[cfg(feature = "text")]
+pub use text::*;
+
 #[cfg(feature = "json")]
 /// Convert the prost message to JSON string for debugging purpose. Need serde_json support.
 pub trait ToJson {
+    /// Serialize the message, panicking if it can't be serialized.
     fn to_json(&self) -> String;
+
+    /// Same as `to_json`, but returns the error if the message can't be serialized. Implement it
+    /// along with `to_json` when serialization can fail, as `prost_to_json!` does.
+    fn try_to_json(&self) -> Result<String, Error> {
+        Ok(self.to_json())
+    }
+}
+
+/// Deserialize a message from JSON, reporting the path of the field which failed, e.g.
+/// `items[2].list_vec[3]: invalid uuid`.
+#[cfg(feature = "json")]
+pub fn from_json_with_path<T>(s: &str) -> Result<T, Error>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let deserializer = &mut serde_json::Deserializer::from_str(s);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let err = Error::from(e.into_inner());
+        match path.as_str() {
+            "." => err,
+            _ => err.with_field(path),
+        }
+    })
 }
 
 /// customized skip_serializing_if function to skip 0 for numbers.
@@ -80,4 +122,33 @@ mod tests {
     fn is_zero_work_for_u64() {
         assert!(is_zero(0u64));
     }
+
+    #[cfg(all(feature = "json", feature = "b64"))]
+    #[test]
+    fn from_json_with_path_should_report_nested_field() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Inner {
+            #[serde(deserialize_with = "deserialize_repeat_buf_vec")]
+            list_vec: Vec<Vec<u8>>,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Outer {
+            items: Vec<Inner>,
+        }
+
+        let s = r#"{"items":[{"list_vec":["d29ybGQ"]}]}"#;
+        let outer = from_json_with_path::<Outer>(s).unwrap();
+        assert_eq!(outer.items[0].list_vec, vec![b"world".to_vec()]);
+
+        let s = r#"{"items":[{"list_vec":["d29ybGQ"]},{"list_vec":["d29ybGQ","d29y!GQ"]}]}"#;
+        let err = from_json_with_path::<Outer>(s).unwrap_err();
+        assert_eq!(err.field(), Some("items[1].list_vec[1]"));
+        assert!(err
+            .to_string()
+            .starts_with("items[1].list_vec[1]: invalid base64: "));
+
+        let err = from_json_with_path::<Outer>("1").unwrap_err();
+        assert!(matches!(err, Error::Json(_)));
+    }
 }
@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use prost::{encoding::decode_varint, Message};
+
+use crate::Error;
+
+/// Resource limits applied before a message is decoded from untrusted input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// maximum size of the encoded message in bytes
+    pub max_bytes: usize,
+    /// maximum nesting depth of sub-messages, the root message is at depth 0. Needs a
+    /// [`LimitSchema`] which knows the sub-messages to be changed from the default
+    pub max_depth: usize,
+    /// maximum number of occurrences of a single field within one message. Packed repeated
+    /// scalars are a single field on the wire, so their elements are only bounded by `max_bytes`.
+    /// Needs a [`LimitSchema`] to be changed from the default
+    pub max_repeated: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 4 * 1024 * 1024,
+            max_depth: 32,
+            max_repeated: 65536,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    #[error("message is {size} bytes, exceeds the limit of {max} bytes")]
+    TooLarge { size: usize, max: usize },
+    #[error("message nesting exceeds the depth limit of {max}")]
+    TooDeep { max: usize },
+    #[error("field {tag} occurs more than {max} times")]
+    TooManyRepeated { tag: u32, max: usize },
+    #[error("max_depth and max_repeated can't be enforced without a LimitSchema")]
+    SchemaRequired,
+}
+
+/// Which fields of a message hold sub-messages, so that the limits follow the real nesting
+/// instead of treating every string or bytes value which happens to be valid wire format as a
+/// message.
+pub trait LimitSchema: Sized {
+    /// The schema of the sub-messages in field `tag`, or `None` if it isn't a message field.
+    fn message_field(&self, tag: u32) -> Option<Self>;
+}
+
+/// A message without sub-messages: only its own fields are counted.
+impl LimitSchema for () {
+    fn message_field(&self, _tag: u32) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(feature = "reflect")]
+impl LimitSchema for prost_reflect::MessageDescriptor {
+    fn message_field(&self, tag: u32) -> Option<Self> {
+        match self.get_field(tag)?.kind() {
+            prost_reflect::Kind::Message(message) => Some(message),
+            _ => None,
+        }
+    }
+}
+
+/// Decode a prost message from `buf`, rejecting input that exceeds `limits`.
+///
+/// Without a schema the scan can't tell sub-messages from string or bytes values, so only
+/// `max_bytes` is checked and a non-default `max_depth` or `max_repeated` is rejected with
+/// [`LimitError::SchemaRequired`]. Use [`decode_with_limits_and_schema`] to enforce them.
+pub fn decode_with_limits<M>(buf: &[u8], limits: Limits) -> Result<M, Error>
+where
+    M: Message + Default,
+{
+    check_limits(buf, limits)?;
+    Ok(M::decode(buf)?)
+}
+
+/// Decode a prost message from `buf`, rejecting input that exceeds `limits`. The sub-messages
+/// are found with `schema`, e.g. the `prost_reflect::MessageDescriptor` of `M` with the
+/// `reflect` feature, or `()` for a message without sub-messages.
+pub fn decode_with_limits_and_schema<M, S>(
+    buf: &[u8],
+    limits: Limits,
+    schema: &S,
+) -> Result<M, Error>
+where
+    M: Message + Default,
+    S: LimitSchema,
+{
+    check_limits_with_schema(buf, limits, schema)?;
+    Ok(M::decode(buf)?)
+}
+
+/// Check the size of `buf` against `limits` without decoding it. Fails with
+/// [`LimitError::SchemaRequired`] if `max_depth` or `max_repeated` isn't the default, as they
+/// can't be checked without a schema.
+pub fn check_limits(buf: &[u8], limits: Limits) -> Result<(), LimitError> {
+    let defaults = Limits::default();
+    if limits.max_depth != defaults.max_depth || limits.max_repeated != defaults.max_repeated {
+        return Err(LimitError::SchemaRequired);
+    }
+    check_size(buf, &limits)
+}
+
+/// Check `buf` against `limits` without decoding it, following the sub-messages of `schema`.
+pub fn check_limits_with_schema<S: LimitSchema>(
+    buf: &[u8],
+    limits: Limits,
+    schema: &S,
+) -> Result<(), LimitError> {
+    check_size(buf, &limits)?;
+    // invalid input is left to prost to report a proper decode error
+    scan(buf, 0, &limits, schema).unwrap_or(Ok(()))
+}
+
+fn check_size(buf: &[u8], limits: &Limits) -> Result<(), LimitError> {
+    if buf.len() > limits.max_bytes {
+        return Err(LimitError::TooLarge {
+            size: buf.len(),
+            max: limits.max_bytes,
+        });
+    }
+    Ok(())
+}
+
+/// Scan the fields of a message at `depth`. Returns `None` if `buf` is not valid wire format.
+fn scan<S: LimitSchema>(
+    mut buf: &[u8],
+    depth: usize,
+    limits: &Limits,
+    schema: &S,
+) -> Option<Result<(), LimitError>> {
+    let mut counts: HashMap<u32, usize> = HashMap::new();
+
+    while !buf.is_empty() {
+        let (tag, payload) = next_field(&mut buf)?;
+        let count = counts.entry(tag).or_default();
+        *count += 1;
+        if *count > limits.max_repeated {
+            return Some(Err(LimitError::TooManyRepeated {
+                tag,
+                max: limits.max_repeated,
+            }));
+        }
+
+        let nested = payload.and_then(|payload| Some((payload, schema.message_field(tag)?)));
+        if let Some((payload, nested)) = nested {
+            if depth + 1 > limits.max_depth {
+                return Some(Err(LimitError::TooDeep {
+                    max: limits.max_depth,
+                }));
+            }
+            // an invalid sub-message is left to prost as well
+            if let Some(Err(e)) = scan(payload, depth + 1, limits, &nested) {
+                return Some(Err(e));
+            }
+        }
+    }
+
+    Some(Ok(()))
+}
+
+/// Split the next field off `buf`, returning its tag and the payload if it is length-delimited.
+fn next_field<'a>(buf: &mut &'a [u8]) -> Option<(u32, Option<&'a [u8]>)> {
+    let key = decode_varint(buf).ok()?;
+    let tag = u32::try_from(key >> 3).ok().filter(|tag| *tag > 0)?;
+    let mut payload = None;
+    *buf = match key & 0x07 {
+        0 => decode_varint(buf).map(|_| *buf).ok()?,
+        1 => buf.get(8..)?,
+        5 => buf.get(4..)?,
+        2 => {
+            let len = usize::try_from(decode_varint(buf).ok()?).ok()?;
+            payload = Some(buf.get(..len)?);
+            &buf[len..]
+        }
+        // groups are not supported in proto3, treat them as opaque data
+        _ => return None,
+    };
+    Some((tag, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Node {
+        #[prost(message, optional, boxed, tag = "1")]
+        pub child: Option<Box<Node>>,
+        #[prost(string, repeated, tag = "2")]
+        pub names: Vec<String>,
+    }
+
+    /// Field 1 of a `Node` is a `Node`, field 2 holds strings.
+    struct NodeSchema;
+
+    impl LimitSchema for NodeSchema {
+        fn message_field(&self, tag: u32) -> Option<Self> {
+            (tag == 1).then_some(NodeSchema)
+        }
+    }
+
+    fn nested(depth: usize) -> Node {
+        let leaf = Node {
+            child: None,
+            names: vec!["x".to_owned()],
+        };
+        (0..depth).fold(leaf, |node, _| Node {
+            child: Some(Box::new(node)),
+            names: vec!["x".to_owned()],
+        })
+    }
+
+    #[test]
+    fn decode_within_limits_should_work() {
+        let node = nested(3);
+        let data = node.encode_to_vec();
+        let result: Node = decode_with_limits(&data, Limits::default()).unwrap();
+        assert_eq!(result, node);
+    }
+
+    #[test]
+    fn decode_too_large_should_fail() {
+        let data = nested(3).encode_to_vec();
+        let limits = Limits {
+            max_bytes: 4,
+            ..Default::default()
+        };
+        let err = decode_with_limits::<Node>(&data, limits).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Limit(LimitError::TooLarge { size, max: 4 }) if size == data.len()
+        ));
+    }
+
+    #[test]
+    fn decode_too_deep_should_fail() {
+        let data = nested(5).encode_to_vec();
+        let limits = Limits {
+            max_depth: 4,
+            ..Default::default()
+        };
+        let err = check_limits_with_schema(&data, limits, &NodeSchema).unwrap_err();
+        assert_eq!(err, LimitError::TooDeep { max: 4 });
+        // without a schema the depth isn't known
+        assert_eq!(check_limits(&data, limits), Err(LimitError::SchemaRequired));
+        let err = decode_with_limits::<Node>(&data, limits).unwrap_err();
+        assert!(matches!(err, Error::Limit(LimitError::SchemaRequired)));
+
+        let limits = Limits {
+            max_depth: 5,
+            ..Default::default()
+        };
+        assert!(decode_with_limits_and_schema::<Node, _>(&data, limits, &NodeSchema).is_ok());
+    }
+
+    #[test]
+    fn decode_strings_looking_like_messages_should_work() {
+        // every name is valid wire format of a deeply nested message with a repeated field
+        let inner = nested(5).encode_to_vec();
+        let name = String::from_utf8(inner).unwrap();
+        let node = Node {
+            child: Some(Box::new(Node {
+                child: None,
+                names: vec![name; 3],
+            })),
+            names: vec![],
+        };
+        let data = node.encode_to_vec();
+        let limits = Limits {
+            max_depth: 2,
+            max_repeated: 3,
+            ..Default::default()
+        };
+        let result = decode_with_limits_and_schema::<Node, _>(&data, limits, &NodeSchema);
+        assert_eq!(result.unwrap(), node);
+    }
+
+    #[test]
+    fn decode_too_many_repeated_should_fail() {
+        let node = Node {
+            child: None,
+            names: vec!["a".to_owned(); 11],
+        };
+        let data = node.encode_to_vec();
+        let limits = Limits {
+            max_repeated: 10,
+            ..Default::default()
+        };
+        let err = check_limits_with_schema(&data, limits, &()).unwrap_err();
+        assert_eq!(err, LimitError::TooManyRepeated { tag: 2, max: 10 });
+        assert_eq!(check_limits(&data, limits), Err(LimitError::SchemaRequired));
+
+        let node = Node {
+            child: Some(Box::new(node)),
+            names: vec![],
+        };
+        let data = node.encode_to_vec();
+        let err = check_limits_with_schema(&data, limits, &NodeSchema).unwrap_err();
+        assert_eq!(err, LimitError::TooManyRepeated { tag: 2, max: 10 });
+    }
+
+    #[cfg(feature = "reflect")]
+    #[test]
+    fn decode_with_message_descriptor_should_follow_sub_messages() {
+        use prost_reflect::DescriptorPool;
+        use prost_types::{
+            field_descriptor_proto::{Label, Type},
+            DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+        };
+
+        let field = |name: &str, number, ty: Type, label: Label| FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            number: Some(number),
+            r#type: Some(ty as i32),
+            label: Some(label as i32),
+            type_name: (ty == Type::Message).then(|| ".test.Node".to_owned()),
+            ..Default::default()
+        };
+        let file = FileDescriptorProto {
+            name: Some("test.proto".to_owned()),
+            package: Some("test".to_owned()),
+            syntax: Some("proto3".to_owned()),
+            message_type: vec![DescriptorProto {
+                name: Some("Node".to_owned()),
+                field: vec![
+                    field("child", 1, Type::Message, Label::Optional),
+                    field("names", 2, Type::String, Label::Repeated),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let pool = DescriptorPool::from_file_descriptor_set(FileDescriptorSet { file: vec![file] })
+            .unwrap();
+        let schema = pool.get_message_by_name("test.Node").unwrap();
+
+        let data = nested(5).encode_to_vec();
+        let limits = Limits {
+            max_depth: 4,
+            ..Default::default()
+        };
+        let err = decode_with_limits_and_schema::<Node, _>(&data, limits, &schema).unwrap_err();
+        assert!(matches!(err, Error::Limit(LimitError::TooDeep { max: 4 })));
+        let limits = Limits {
+            max_depth: 5,
+            ..Default::default()
+        };
+        assert!(decode_with_limits_and_schema::<Node, _>(&data, limits, &schema).is_ok());
+    }
+
+    #[test]
+    fn decode_invalid_data_should_return_decode_error() {
+        let err = decode_with_limits::<Node>(&[0x0a, 0x05, 0x01], Limits::default()).unwrap_err();
+        assert!(matches!(err, Error::Decode(_)));
+    }
+}
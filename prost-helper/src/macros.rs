@@ -42,6 +42,52 @@ macro_rules! prost_into_bytes {
     };
 }
 
+/// Same as `prost_into_vec!`, but generates `TryFrom` which returns
+/// [`Error::Encode`](crate::Error::Encode) instead of panicking if the message can't be encoded.
+///
+/// Use it instead of `prost_into_vec!` for the same type, as the `From` impls conflict with it.
+#[macro_export]
+macro_rules! prost_try_into_vec {
+    ($(($type:ty, $cap:expr)),*) => {
+        $(impl std::convert::TryFrom<$type> for Vec<u8> {
+            type Error = $crate::Error;
+            fn try_from(msg: $type) -> Result<Self, Self::Error> {
+                Self::try_from(&msg)
+            }
+        }
+        impl std::convert::TryFrom<&$type> for Vec<u8> {
+            type Error = $crate::Error;
+            fn try_from(msg: &$type) -> Result<Self, Self::Error> {
+                let mut buf = bytes::BytesMut::with_capacity($cap);
+                msg.encode(&mut buf)?;
+                Ok(buf.to_vec())
+            }
+        })*
+    };
+}
+
+/// Same as `prost_into_bytes!`, but generates `TryFrom` which returns
+/// [`Error::Encode`](crate::Error::Encode) instead of panicking if the message can't be encoded.
+#[macro_export]
+macro_rules! prost_try_into_bytes {
+    ($(($type:ty, $cap:expr)),*) => {
+        $(impl std::convert::TryFrom<$type> for bytes::Bytes {
+            type Error = $crate::Error;
+            fn try_from(msg: $type) -> Result<Self, Self::Error> {
+                Self::try_from(&msg)
+            }
+        }
+        impl std::convert::TryFrom<&$type> for bytes::Bytes {
+            type Error = $crate::Error;
+            fn try_from(msg: &$type) -> Result<Self, Self::Error> {
+                let mut buf = bytes::BytesMut::with_capacity($cap);
+                msg.encode(&mut buf)?;
+                Ok(buf.freeze())
+            }
+        })*
+    };
+}
+
 /// Generate `TryFrom` and `TryInto` for your prost message from `Vec<u8>`.
 ///
 /// When you use it, please include `use prost::Message;` in your code.
@@ -50,7 +96,7 @@ macro_rules! prost_into_bytes {
 macro_rules! vec_try_into_prost {
     ($($type:ty),*) => {
         $(impl std::convert::TryFrom<Vec<u8>> for $type {
-            type Error = prost::DecodeError;
+            type Error = $crate::Error;
             fn try_from(buf: Vec<u8>) -> Result<Self, Self::Error> {
                 let msg: $type = Message::decode(&buf[..])?;
                 Ok(msg)
@@ -58,7 +104,7 @@ macro_rules! vec_try_into_prost {
         }
 
         impl std::convert::TryFrom<&[u8]> for $type {
-            type Error = prost::DecodeError;
+            type Error = $crate::Error;
             fn try_from(buf: &[u8]) -> Result<Self, Self::Error> {
                 let msg: $type = Message::decode(buf)?;
                 Ok(msg)
@@ -71,7 +117,7 @@ macro_rules! vec_try_into_prost {
 macro_rules! bytes_try_into_prost {
     ($($type:ty),*) => {
         $(impl std::convert::TryFrom<bytes::Bytes> for $type {
-            type Error = prost::DecodeError;
+            type Error = $crate::Error;
             fn try_from(buf: bytes::Bytes) -> Result<Self, Self::Error> {
                 let msg: $type = Message::decode(buf)?;
                 Ok(msg)
@@ -80,6 +126,71 @@ macro_rules! bytes_try_into_prost {
     };
 }
 
+/// Generate `TryFrom` for your prost message from `Vec<u8>` and `&[u8]`, enforcing the given
+/// [`Limits`](crate::Limits) on the input. Give each type a [`LimitSchema`](crate::LimitSchema),
+/// e.g. `Type = NodeSchema`, to also limit the depth and the fields of its sub-messages. Without
+/// one, only `max_bytes` is checked and other non-default limits fail to decode with
+/// `LimitError::SchemaRequired`, see [`decode_with_limits`](crate::decode_with_limits).
+///
+/// Please remember to `use std::convert::TryInto;` when you want to use generated functions.
+#[macro_export]
+macro_rules! vec_try_into_prost_with_limits {
+    ($limits:expr; $($type:ty),*) => {
+        $(impl std::convert::TryFrom<Vec<u8>> for $type {
+            type Error = $crate::Error;
+            fn try_from(buf: Vec<u8>) -> Result<Self, Self::Error> {
+                $crate::decode_with_limits(&buf[..], $limits)
+            }
+        }
+
+        impl std::convert::TryFrom<&[u8]> for $type {
+            type Error = $crate::Error;
+            fn try_from(buf: &[u8]) -> Result<Self, Self::Error> {
+                $crate::decode_with_limits(buf, $limits)
+            }
+        })*
+    };
+    ($limits:expr; $($type:ty = $schema:expr),*) => {
+        $(impl std::convert::TryFrom<Vec<u8>> for $type {
+            type Error = $crate::Error;
+            fn try_from(buf: Vec<u8>) -> Result<Self, Self::Error> {
+                $crate::decode_with_limits_and_schema(&buf[..], $limits, &$schema)
+            }
+        }
+
+        impl std::convert::TryFrom<&[u8]> for $type {
+            type Error = $crate::Error;
+            fn try_from(buf: &[u8]) -> Result<Self, Self::Error> {
+                $crate::decode_with_limits_and_schema(buf, $limits, &$schema)
+            }
+        })*
+    };
+}
+
+#[macro_export]
+macro_rules! bytes_try_into_prost_with_limits {
+    ($limits:expr; $($type:ty),*) => {
+        $(impl std::convert::TryFrom<bytes::Bytes> for $type {
+            type Error = $crate::Error;
+            fn try_from(buf: bytes::Bytes) -> Result<Self, Self::Error> {
+                $crate::decode_with_limits(&buf[..], $limits)
+            }
+        })*
+    };
+    ($limits:expr; $($type:ty = $schema:expr),*) => {
+        $(impl std::convert::TryFrom<bytes::Bytes> for $type {
+            type Error = $crate::Error;
+            fn try_from(buf: bytes::Bytes) -> Result<Self, Self::Error> {
+                $crate::decode_with_limits_and_schema(&buf[..], $limits, &$schema)
+            }
+        })*
+    };
+}
+
+/// Generate `ToJson` for your prost message.
+///
+/// Fields serialized with `serialize_redacted` or `serialize_redacted_hash` are masked in the
+/// generated JSON.
 #[cfg(feature = "json")]
 #[macro_export]
 macro_rules! prost_to_json {
@@ -87,7 +198,44 @@ macro_rules! prost_to_json {
     ($($type:ty),*) => {
         $(impl ToJson for $type {
             fn to_json(&self) -> String {
-                serde_json::to_string(self).unwrap()
+                self.try_to_json().unwrap()
+            }
+
+            fn try_to_json(&self) -> Result<String, $crate::Error> {
+                Ok($crate::with_redaction(|| serde_json::to_string(self))?)
+            }
+        })*
+    };
+}
+
+/// Generate `ToText` and `FromText` for your prost message.
+///
+/// Text format needs the message schema, so `$pool` is an expression giving a
+/// `&'static prost_reflect::DescriptorPool` with the messages, and each type is paired with its
+/// fully-qualified protobuf name, e.g. `prost_to_text!(pool(); Hello = "helloworld.Hello")`.
+/// `$pool` is evaluated on every call, so build the pool once, e.g. in a `OnceLock`:
+///
+/// ```ignore
+/// fn pool() -> &'static DescriptorPool {
+///     static POOL: OnceLock<DescriptorPool> = OnceLock::new();
+///     POOL.get_or_init(|| DescriptorPool::decode(FILE_DESCRIPTOR_SET).unwrap())
+/// }
+/// ```
+#[cfg(feature = "text")]
+#[macro_export]
+macro_rules! prost_to_text {
+    ($pool:expr; $($type:ty = $name:expr),*) => {
+        $(impl $crate::ToText for $type {
+            fn try_to_text(&self) -> Result<String, $crate::Error> {
+                let pool: &'static _ = $pool;
+                $crate::encode_text(self, pool, $name)
+            }
+        }
+
+        impl $crate::FromText for $type {
+            fn from_text(s: &str) -> Result<Self, $crate::Error> {
+                let pool: &'static _ = $pool;
+                $crate::decode_text(s, pool, $name)
             }
         })*
     };
@@ -99,6 +247,9 @@ mod tests {
     use serde::{Deserialize, Serialize};
     use std::convert::TryInto;
 
+    #[cfg(feature = "json")]
+    use crate::ToJson;
+
     #[derive(Clone, PartialEq, Eq, Message, Serialize, Deserialize)]
     pub struct Hello {
         #[prost(string, tag = "1")]
@@ -113,16 +264,21 @@ mod tests {
         pub msg: String,
     }
 
+    prost_into_vec!((Hello, 32), (World, 256));
+    vec_try_into_prost!(Hello, World);
+    prost_into_bytes!((Hello, 32), (World, 256));
+    bytes_try_into_prost!(Hello, World);
+    #[cfg(feature = "json")]
+    prost_to_json!(Hello, World);
+
     #[test]
     fn test_prost_try_into_vec() {
-        prost_into_vec!((Hello, 32), (World, 256));
-        vec_try_into_prost!(Hello, World);
         let hello = Hello::default();
         let data: Vec<u8> = hello.clone().into();
         let data_ref = &data[..];
 
-        let hello_result1: Result<Hello, prost::DecodeError> = data_ref.try_into();
-        let hello_result2: Result<Hello, prost::DecodeError> = data.try_into();
+        let hello_result1: Result<Hello, crate::Error> = data_ref.try_into();
+        let hello_result2: Result<Hello, crate::Error> = data.try_into();
         assert!(hello_result1.is_ok());
         assert_eq!(hello_result1.unwrap(), hello);
         assert!(hello_result2.is_ok());
@@ -131,23 +287,174 @@ mod tests {
     #[test]
     fn test_prost_try_into_bytes() {
         use bytes::Bytes;
-        prost_into_bytes!((Hello, 32), (World, 256));
-        bytes_try_into_prost!(Hello, World);
         let hello = Hello::default();
         let data: Bytes = hello.clone().into();
 
-        let hello_result: Result<Hello, prost::DecodeError> = data.try_into();
+        let hello_result: Result<Hello, crate::Error> = data.try_into();
         assert!(hello_result.is_ok());
         assert_eq!(hello_result.unwrap(), hello);
     }
+    #[test]
+    fn test_prost_try_into_with_limits() {
+        use crate::{Error, LimitError, Limits};
+        use bytes::Bytes;
+
+        #[derive(Clone, PartialEq, Eq, Message)]
+        struct Limited {
+            #[prost(string, tag = "1")]
+            pub msg: String,
+        }
+
+        const LIMITS: Limits = Limits {
+            max_bytes: 16,
+            max_depth: 4,
+            max_repeated: 16,
+        };
+        // a message without sub-messages needs no schema beyond `()`
+        vec_try_into_prost_with_limits!(LIMITS; Limited = ());
+        bytes_try_into_prost_with_limits!(LIMITS; Limited = ());
+
+        let limited = Limited {
+            msg: "hello".to_owned(),
+        };
+        let data = limited.encode_to_vec();
+        let result: Result<Limited, Error> = data.try_into();
+        assert_eq!(result.unwrap(), limited);
+
+        let limited = Limited {
+            msg: "a long message which is over the limit".to_owned(),
+        };
+        let data = Bytes::from(limited.encode_to_vec());
+        let result: Result<Limited, Error> = data.try_into();
+        assert!(matches!(
+            result,
+            Err(Error::Limit(LimitError::TooLarge { max: 16, .. }))
+        ));
+
+        #[derive(Clone, PartialEq, Eq, Message)]
+        struct Unchecked {
+            #[prost(string, tag = "1")]
+            pub msg: String,
+        }
+
+        vec_try_into_prost_with_limits!(LIMITS; Unchecked);
+        let data = Unchecked::default().encode_to_vec();
+        let result: Result<Unchecked, Error> = data.try_into();
+        assert!(matches!(
+            result,
+            Err(Error::Limit(LimitError::SchemaRequired))
+        ));
+    }
+
+    #[test]
+    fn test_prost_try_into_with_limits_and_schema() {
+        use crate::{Error, LimitError, LimitSchema, Limits};
+
+        #[derive(Clone, PartialEq, Message)]
+        struct Tree {
+            #[prost(message, repeated, tag = "1")]
+            pub children: Vec<Tree>,
+        }
+
+        struct TreeSchema;
+
+        impl LimitSchema for TreeSchema {
+            fn message_field(&self, _tag: u32) -> Option<Self> {
+                Some(TreeSchema)
+            }
+        }
+
+        const LIMITS: Limits = Limits {
+            max_bytes: 1024,
+            max_depth: 1,
+            max_repeated: 16,
+        };
+        vec_try_into_prost_with_limits!(LIMITS; Tree = TreeSchema);
+
+        let leaf = Tree { children: vec![] };
+        let tree = Tree {
+            children: vec![leaf.clone()],
+        };
+        let result: Result<Tree, Error> = tree.encode_to_vec().try_into();
+        assert_eq!(result.unwrap(), tree);
+
+        let tree = Tree {
+            children: vec![tree],
+        };
+        let result: Result<Tree, Error> = tree.encode_to_vec().try_into();
+        assert!(matches!(
+            result,
+            Err(Error::Limit(LimitError::TooDeep { max: 1 }))
+        ));
+    }
+
+    #[test]
+    fn test_prost_try_into_vec_and_bytes() {
+        use bytes::Bytes;
+
+        #[derive(Clone, PartialEq, Eq, Message)]
+        struct Fallible {
+            #[prost(string, tag = "1")]
+            pub msg: String,
+        }
+
+        prost_try_into_vec!((Fallible, 32));
+        prost_try_into_bytes!((Fallible, 32));
+        vec_try_into_prost!(Fallible);
+
+        let msg = Fallible {
+            msg: "hello".to_owned(),
+        };
+        let data: Vec<u8> = (&msg).try_into().unwrap();
+        let bytes: Bytes = msg.clone().try_into().unwrap();
+        assert_eq!(data, &bytes[..]);
+        let result: Fallible = data.try_into().unwrap();
+        assert_eq!(result, msg);
+    }
+
     #[cfg(feature = "json")]
     #[test]
-    fn test_prost_to_json() {
-        use crate::ToJson;
+    fn test_manual_to_json() {
+        struct Manual;
 
-        prost_to_json!(Hello, World);
+        impl ToJson for Manual {
+            fn to_json(&self) -> String {
+                "{}".to_owned()
+            }
+        }
+
+        assert_eq!(Manual.try_to_json().unwrap(), "{}");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_prost_to_json() {
         let hello = Hello::default();
         let result = hello.to_json();
         assert_eq!(result, r#"{"msg":"","value":0}"#);
     }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_prost_to_json_redacted() {
+        #[derive(Clone, PartialEq, Eq, Message, Serialize)]
+        struct Login {
+            #[prost(string, tag = "1")]
+            pub user: String,
+            #[serde(serialize_with = "crate::serialize_redacted")]
+            #[prost(string, tag = "2")]
+            pub password: String,
+        }
+        prost_to_json!(Login);
+
+        let login = Login {
+            user: "tyr".to_owned(),
+            password: "secret".to_owned(),
+        };
+        assert_eq!(login.to_json(), r#"{"user":"tyr","password":"***"}"#);
+        assert_eq!(
+            serde_json::to_string(&login).unwrap(),
+            r#"{"user":"tyr","password":"secret"}"#
+        );
+    }
 }
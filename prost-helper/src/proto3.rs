@@ -0,0 +1,642 @@
+//! Helpers for the [proto3 JSON mapping](https://protobuf.dev/programming-guides/proto3/#json),
+//! used by the code generated with the `proto3_json` profile of prost-build-config.
+//!
+//! Use the module with `#[serde(with = "prost_helper::proto3")]` on fields whose JSON form
+//! differs from their serde form: 64-bit integers are strings, floats may be `"NaN"` or
+//! `"Infinity"`, bytes are base64 and well-known types have their own format. Enum fields are
+//! `i32` in prost, so they use `serialize_enum` and `deserialize_enum` with the enum type.
+//!
+//! `google.protobuf.Any` needs the schema of the packed message to be mapped, so it fails to
+//! serialize and deserialize.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    hash::Hash,
+    str::FromStr,
+};
+
+use base64::{
+    alphabet,
+    engine::{general_purpose::STANDARD, DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine as _,
+};
+use bytes::Bytes;
+use prost_types::{value::Kind, Any, Duration, FieldMask, ListValue, Struct, Timestamp};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Number, Value};
+
+/// accepts standard base64 with or without padding, url-safe chars are mapped before decoding
+const BASE64_LENIENT: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// A type with a proto3 JSON form which differs from its serde form.
+pub trait Proto3Json: Sized {
+    fn to_value(&self) -> Result<Value, String>;
+    fn from_value(value: Value) -> Result<Self, String>;
+}
+
+/// A prost enum. prost stores enum fields as `i32`, so this is used to map them to their names.
+pub trait Proto3Enum {
+    fn name(value: i32) -> Option<&'static str>;
+    fn value(name: &str) -> Option<i32>;
+}
+
+/// An enum field: `i32`, or an optional, repeated or map field of it.
+pub trait Proto3EnumJson: Sized {
+    fn to_value<E: Proto3Enum>(&self) -> Value;
+    fn from_value<E: Proto3Enum>(value: Value) -> Result<Self, String>;
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Proto3Json,
+    S: Serializer,
+{
+    value
+        .to_value()
+        .map_err(ser::Error::custom)?
+        .serialize(serializer)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Proto3Json,
+    D: Deserializer<'de>,
+{
+    T::from_value(Value::deserialize(deserializer)?).map_err(de::Error::custom)
+}
+
+/// serialize an enum field of enum `E` by its names, e.g. `serialize_enum::<Status, _, _>`.
+pub fn serialize_enum<E, T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    E: Proto3Enum,
+    T: Proto3EnumJson,
+    S: Serializer,
+{
+    value.to_value::<E>().serialize(serializer)
+}
+
+/// deserialize an enum field of enum `E` from its names or numbers.
+pub fn deserialize_enum<'de, E, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    E: Proto3Enum,
+    T: Proto3EnumJson,
+    D: Deserializer<'de>,
+{
+    T::from_value::<E>(Value::deserialize(deserializer)?).map_err(de::Error::custom)
+}
+
+/// proto3 JSON omits fields with default values.
+pub fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+macro_rules! int_as_string {
+    ($($type:ty => $as:ident),*) => {
+        $(impl Proto3Json for $type {
+            fn to_value(&self) -> Result<Value, String> {
+                Ok(Value::String(self.to_string()))
+            }
+
+            fn from_value(value: Value) -> Result<Self, String> {
+                match value {
+                    Value::Null => Ok(0),
+                    Value::String(s) => s
+                        .parse()
+                        .map_err(|e| format!("invalid integer {:?}: {}", s, e)),
+                    // `MAX as f64` rounds up to the next power of two, which is out of range
+                    Value::Number(n) => n
+                        .$as()
+                        .or_else(|| {
+                            n.as_f64()
+                                .filter(|f| f.fract() == 0.0)
+                                .filter(|f| *f >= <$type>::MIN as f64 && *f < <$type>::MAX as f64)
+                                .map(|f| f as $type)
+                        })
+                        .ok_or_else(|| format!("invalid integer {}", n)),
+                    v => Err(format!("invalid integer {}", v)),
+                }
+            }
+        })*
+    };
+}
+
+int_as_string!(i64 => as_i64, u64 => as_u64);
+
+macro_rules! float {
+    ($($type:ty => $to_f64:expr),*) => {
+        $(impl Proto3Json for $type {
+            fn to_value(&self) -> Result<Value, String> {
+                let value = match Number::from_f64($to_f64(*self)) {
+                    Some(n) => Value::Number(n),
+                    None if self.is_nan() => Value::String("NaN".to_owned()),
+                    None if *self > 0.0 => Value::String("Infinity".to_owned()),
+                    None => Value::String("-Infinity".to_owned()),
+                };
+                Ok(value)
+            }
+
+            fn from_value(value: Value) -> Result<Self, String> {
+                match value {
+                    Value::Null => Ok(0.0),
+                    Value::Number(n) => n
+                        .as_f64()
+                        .map(|f| f as $type)
+                        .ok_or_else(|| format!("invalid number {}", n)),
+                    Value::String(s) => match s.as_str() {
+                        "NaN" => Ok(<$type>::NAN),
+                        "Infinity" => Ok(<$type>::INFINITY),
+                        "-Infinity" => Ok(<$type>::NEG_INFINITY),
+                        _ => s.parse().map_err(|e| format!("invalid number {:?}: {}", s, e)),
+                    },
+                    v => Err(format!("invalid number {}", v)),
+                }
+            }
+        })*
+    };
+}
+
+// the shortest repr of an f32 is parsed as f64, so that e.g. `1.1` isn't `1.100000023841858`
+float!(
+    f32 => |f: f32| f.to_string().parse().unwrap_or(f64::NAN),
+    f64 => |f: f64| f
+);
+
+impl Proto3Json for Vec<u8> {
+    fn to_value(&self) -> Result<Value, String> {
+        Ok(Value::String(STANDARD.encode(self)))
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        match value {
+            Value::Null => Ok(Vec::new()),
+            Value::String(s) => {
+                let s = s.replace('-', "+").replace('_', "/");
+                BASE64_LENIENT
+                    .decode(s)
+                    .map_err(|e| format!("invalid base64: {}", e))
+            }
+            v => Err(format!("invalid base64 {}", v)),
+        }
+    }
+}
+
+impl Proto3Json for Bytes {
+    fn to_value(&self) -> Result<Value, String> {
+        Ok(Value::String(STANDARD.encode(self)))
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        Vec::<u8>::from_value(value).map(Bytes::from)
+    }
+}
+
+impl Proto3Json for Timestamp {
+    fn to_value(&self) -> Result<Value, String> {
+        Ok(Value::String(self.to_string()))
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        match value {
+            Value::String(s) => s
+                .parse()
+                .map_err(|e| format!("invalid timestamp {:?}: {}", s, e)),
+            v => Err(format!("invalid timestamp {}", v)),
+        }
+    }
+}
+
+impl Proto3Json for Duration {
+    fn to_value(&self) -> Result<Value, String> {
+        Ok(Value::String(self.to_string()))
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        match value {
+            Value::String(s) => s
+                .parse()
+                .map_err(|e| format!("invalid duration {:?}: {}", s, e)),
+            v => Err(format!("invalid duration {}", v)),
+        }
+    }
+}
+
+impl Proto3Json for FieldMask {
+    fn to_value(&self) -> Result<Value, String> {
+        let paths: Vec<_> = self.paths.iter().map(|p| to_lower_camel(p)).collect();
+        Ok(Value::String(paths.join(",")))
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        match value {
+            Value::String(s) => Ok(FieldMask {
+                paths: s
+                    .split(',')
+                    .filter(|p| !p.is_empty())
+                    .map(to_snake)
+                    .collect(),
+            }),
+            v => Err(format!("invalid field mask {}", v)),
+        }
+    }
+}
+
+impl Proto3Json for prost_types::Value {
+    fn to_value(&self) -> Result<Value, String> {
+        match &self.kind {
+            None | Some(Kind::NullValue(_)) => Ok(Value::Null),
+            Some(Kind::NumberValue(n)) => n.to_value(),
+            Some(Kind::StringValue(s)) => Ok(Value::String(s.clone())),
+            Some(Kind::BoolValue(b)) => Ok(Value::Bool(*b)),
+            Some(Kind::StructValue(s)) => s.to_value(),
+            Some(Kind::ListValue(l)) => l.to_value(),
+        }
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        let kind = match value {
+            Value::Null => Kind::NullValue(0),
+            Value::Bool(b) => Kind::BoolValue(b),
+            Value::Number(n) => Kind::NumberValue(n.as_f64().unwrap_or_default()),
+            Value::String(s) => Kind::StringValue(s),
+            v @ Value::Array(_) => Kind::ListValue(ListValue::from_value(v)?),
+            v @ Value::Object(_) => Kind::StructValue(Struct::from_value(v)?),
+        };
+        Ok(prost_types::Value { kind: Some(kind) })
+    }
+}
+
+impl Proto3Json for Struct {
+    fn to_value(&self) -> Result<Value, String> {
+        self.fields.to_value()
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        Ok(Struct {
+            fields: Proto3Json::from_value(value)?,
+        })
+    }
+}
+
+impl Proto3Json for ListValue {
+    fn to_value(&self) -> Result<Value, String> {
+        self.values.to_value()
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        Ok(ListValue {
+            values: Proto3Json::from_value(value)?,
+        })
+    }
+}
+
+/// `google.protobuf.Empty`, which prost generates as `()`.
+impl Proto3Json for () {
+    fn to_value(&self) -> Result<Value, String> {
+        Ok(Value::Object(Map::new()))
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        match value {
+            Value::Object(fields) if fields.is_empty() => Ok(()),
+            v => Err(format!("invalid empty message {}", v)),
+        }
+    }
+}
+
+impl Proto3Json for Any {
+    fn to_value(&self) -> Result<Value, String> {
+        Err(format!(
+            "can't map google.protobuf.Any of {:?} to JSON without a type registry",
+            self.type_url
+        ))
+    }
+
+    fn from_value(_value: Value) -> Result<Self, String> {
+        Err("can't map google.protobuf.Any from JSON without a type registry".to_owned())
+    }
+}
+
+impl<T: Proto3Json> Proto3Json for Option<T> {
+    fn to_value(&self) -> Result<Value, String> {
+        self.as_ref().map_or(Ok(Value::Null), T::to_value)
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        match value {
+            Value::Null => Ok(None),
+            v => T::from_value(v).map(Some),
+        }
+    }
+}
+
+impl<T: Proto3Json> Proto3Json for Vec<T> {
+    fn to_value(&self) -> Result<Value, String> {
+        self.iter().map(T::to_value).collect()
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        from_array(value, T::from_value)
+    }
+}
+
+impl<K, V> Proto3Json for HashMap<K, V>
+where
+    K: Display + FromStr + Eq + Hash,
+    V: Proto3Json,
+{
+    fn to_value(&self) -> Result<Value, String> {
+        to_object(self.iter(), V::to_value)
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        from_object(value, V::from_value)
+    }
+}
+
+impl<K, V> Proto3Json for BTreeMap<K, V>
+where
+    K: Display + FromStr + Ord,
+    V: Proto3Json,
+{
+    fn to_value(&self) -> Result<Value, String> {
+        to_object(self.iter(), V::to_value)
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        from_object(value, V::from_value)
+    }
+}
+
+impl Proto3EnumJson for i32 {
+    fn to_value<E: Proto3Enum>(&self) -> Value {
+        match E::name(*self) {
+            Some(name) => Value::String(name.to_owned()),
+            None => Value::Number((*self).into()),
+        }
+    }
+
+    fn from_value<E: Proto3Enum>(value: Value) -> Result<Self, String> {
+        match value {
+            Value::Null => Ok(0),
+            Value::String(s) => E::value(&s).ok_or_else(|| format!("unknown enum value {:?}", s)),
+            Value::Number(n) => n
+                .as_i64()
+                .and_then(|n| i32::try_from(n).ok())
+                .ok_or_else(|| format!("invalid enum value {}", n)),
+            v => Err(format!("invalid enum value {}", v)),
+        }
+    }
+}
+
+impl<T: Proto3EnumJson> Proto3EnumJson for Option<T> {
+    fn to_value<E: Proto3Enum>(&self) -> Value {
+        self.as_ref().map_or(Value::Null, T::to_value::<E>)
+    }
+
+    fn from_value<E: Proto3Enum>(value: Value) -> Result<Self, String> {
+        match value {
+            Value::Null => Ok(None),
+            v => T::from_value::<E>(v).map(Some),
+        }
+    }
+}
+
+impl<T: Proto3EnumJson> Proto3EnumJson for Vec<T> {
+    fn to_value<E: Proto3Enum>(&self) -> Value {
+        Value::Array(self.iter().map(T::to_value::<E>).collect())
+    }
+
+    fn from_value<E: Proto3Enum>(value: Value) -> Result<Self, String> {
+        from_array(value, T::from_value::<E>)
+    }
+}
+
+impl<K, V> Proto3EnumJson for HashMap<K, V>
+where
+    K: Display + FromStr + Eq + Hash,
+    V: Proto3EnumJson,
+{
+    fn to_value<E: Proto3Enum>(&self) -> Value {
+        let entries = self.iter().map(|(k, v)| (k.to_string(), v.to_value::<E>()));
+        Value::Object(entries.collect())
+    }
+
+    fn from_value<E: Proto3Enum>(value: Value) -> Result<Self, String> {
+        from_object(value, V::from_value::<E>)
+    }
+}
+
+impl<K, V> Proto3EnumJson for BTreeMap<K, V>
+where
+    K: Display + FromStr + Ord,
+    V: Proto3EnumJson,
+{
+    fn to_value<E: Proto3Enum>(&self) -> Value {
+        let entries = self.iter().map(|(k, v)| (k.to_string(), v.to_value::<E>()));
+        Value::Object(entries.collect())
+    }
+
+    fn from_value<E: Proto3Enum>(value: Value) -> Result<Self, String> {
+        from_object(value, V::from_value::<E>)
+    }
+}
+
+fn from_array<T, C>(value: Value, f: impl Fn(Value) -> Result<T, String>) -> Result<C, String>
+where
+    C: FromIterator<T>,
+{
+    match value {
+        Value::Null => Ok(std::iter::empty().collect()),
+        Value::Array(items) => items.into_iter().map(f).collect(),
+        v => Err(format!("invalid list {}", v)),
+    }
+}
+
+fn to_object<'a, K, V>(
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+    f: impl Fn(&V) -> Result<Value, String>,
+) -> Result<Value, String>
+where
+    K: Display + 'a,
+    V: 'a,
+{
+    entries.map(|(k, v)| Ok((k.to_string(), f(v)?))).collect()
+}
+
+fn from_object<K, V, C>(value: Value, f: impl Fn(Value) -> Result<V, String>) -> Result<C, String>
+where
+    K: FromStr,
+    C: FromIterator<(K, V)>,
+{
+    match value {
+        Value::Null => Ok(std::iter::empty().collect()),
+        Value::Object(entries) => entries
+            .into_iter()
+            .map(|(k, v)| {
+                let key = k.parse().map_err(|_| format!("invalid map key {:?}", k))?;
+                Ok((key, f(v)?))
+            })
+            .collect(),
+        v => Err(format!("invalid map {}", v)),
+    }
+}
+
+fn to_lower_camel(path: &str) -> String {
+    let mut s = String::with_capacity(path.len());
+    let mut upper = false;
+    for c in path.chars() {
+        match c {
+            '_' => upper = true,
+            c if upper => {
+                s.push(c.to_ascii_uppercase());
+                upper = false;
+            }
+            c => s.push(c),
+        }
+    }
+    s
+}
+
+fn to_snake(path: &str) -> String {
+    let mut s = String::with_capacity(path.len() + 4);
+    for c in path.chars() {
+        if c.is_ascii_uppercase() {
+            s.push('_');
+            s.push(c.to_ascii_lowercase());
+        } else {
+            s.push(c);
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Proto3Json + PartialEq + std::fmt::Debug>(value: T, json: &str) {
+        assert_eq!(
+            serde_json::to_string(&value.to_value().unwrap()).unwrap(),
+            json
+        );
+        let parsed: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(T::from_value(parsed).unwrap(), value);
+    }
+
+    #[test]
+    fn scalars_should_use_proto3_json_form() {
+        round_trip(-42i64, r#""-42""#);
+        round_trip(u64::MAX, r#""18446744073709551615""#);
+        round_trip(1.5f64, "1.5");
+        round_trip(f32::INFINITY, r#""Infinity""#);
+        round_trip(b"hello?".to_vec(), r#""aGVsbG8/""#);
+        round_trip(Bytes::from("hi"), r#""aGk=""#);
+        assert_eq!(i64::from_value(Value::from(42)).unwrap(), 42);
+        assert_eq!(
+            Vec::<u8>::from_value(Value::from("aGVsbG8_")).unwrap(),
+            b"hello?"
+        );
+        assert!(f64::from_value(Value::from("NaN")).unwrap().is_nan());
+        assert!(i64::from_value(Value::from("4x")).is_err());
+    }
+
+    #[test]
+    fn floats_should_use_shortest_repr() {
+        round_trip(1.1f32, "1.1");
+        round_trip(f32::MAX, "3.4028235e+38");
+        round_trip(0.1f64, "0.1");
+    }
+
+    #[test]
+    fn integers_from_floats_should_be_exact() {
+        assert_eq!(i64::from_value(serde_json::from_str("2.0").unwrap()), Ok(2));
+        assert_eq!(
+            u64::from_value(serde_json::from_str("1e3").unwrap()),
+            Ok(1000)
+        );
+        for json in ["1.5", "1e19", "-1e19", "-1.0"] {
+            let value: Value = serde_json::from_str(json).unwrap();
+            let result = match json {
+                "-1.0" => u64::from_value(value).map(|_| ()),
+                _ => i64::from_value(value).map(|_| ()),
+            };
+            assert!(result.is_err(), "{} should be rejected", json);
+        }
+        assert!(u64::from_value(serde_json::from_str("1.8446744073709552e19").unwrap()).is_err());
+    }
+
+    #[test]
+    fn well_known_types_should_use_proto3_json_form() {
+        let ts = Timestamp {
+            seconds: 1_700_000_000,
+            nanos: 5_000_000,
+        };
+        round_trip(ts, r#""2023-11-14T22:13:20.005Z""#);
+        round_trip(
+            Duration {
+                seconds: 3,
+                nanos: 500_000_000,
+            },
+            r#""3.500s""#,
+        );
+        round_trip(
+            FieldMask {
+                paths: vec!["user.display_name".to_owned(), "photo".to_owned()],
+            },
+            r#""user.displayName,photo""#,
+        );
+        let json = r#"{"a":[1.0,"x",true,null],"b":{}}"#;
+        let value = Struct::from_value(serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            serde_json::to_string(&value.to_value().unwrap()).unwrap(),
+            json
+        );
+
+        round_trip((), "{}");
+        assert!(<()>::from_value(serde_json::from_str(r#"{"a":1}"#).unwrap()).is_err());
+        let any = Any {
+            type_url: "type.googleapis.com/test.Hello".to_owned(),
+            value: vec![],
+        };
+        assert!(any.to_value().is_err());
+        assert!(Any::from_value(serde_json::from_str("{}").unwrap()).is_err());
+    }
+
+    #[test]
+    fn containers_should_use_proto3_json_form() {
+        round_trip(Some(7u64), r#""7""#);
+        round_trip(None::<u64>, "null");
+        round_trip(vec![vec![1u8], vec![2u8]], r#"["AQ==","Ag=="]"#);
+        round_trip(BTreeMap::from([(1i32, 2i64)]), r#"{"1":"2"}"#);
+        assert!(Vec::<i64>::from_value(Value::Null).unwrap().is_empty());
+    }
+
+    struct Status;
+
+    impl Proto3Enum for Status {
+        fn name(value: i32) -> Option<&'static str> {
+            ["UNKNOWN", "OK"].get(value as usize).copied()
+        }
+
+        fn value(name: &str) -> Option<i32> {
+            ["UNKNOWN", "OK"]
+                .iter()
+                .position(|n| *n == name)
+                .map(|v| v as i32)
+        }
+    }
+
+    #[test]
+    fn enums_should_use_names() {
+        let value = vec![1, 0, 5];
+        let json = Proto3EnumJson::to_value::<Status>(&value);
+        assert_eq!(json.to_string(), r#"["OK","UNKNOWN",5]"#);
+        let parsed: Vec<i32> = Proto3EnumJson::from_value::<Status>(json).unwrap();
+        assert_eq!(parsed, value);
+        assert!(<i32 as Proto3EnumJson>::from_value::<Status>(Value::from("NOPE")).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use std::{cell::Cell, fmt};
+
+use serde::{Serialize, Serializer};
+
+/// The mask used for redacted values.
+pub const REDACTED: &str = "***";
+
+thread_local! {
+    static REDACTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` with redaction turned on for the current thread. Fields serialized with
+/// `serialize_redacted` or `serialize_redacted_hash` are masked while `f` runs.
+///
+/// `ToJson` uses this, so normal serde serialization is not affected.
+pub fn with_redaction<R>(f: impl FnOnce() -> R) -> R {
+    struct Guard(bool);
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            REDACTING.with(|r| r.set(self.0));
+        }
+    }
+
+    let _guard = Guard(REDACTING.with(|r| r.replace(true)));
+    f()
+}
+
+/// Whether redaction is turned on for the current thread.
+pub fn is_redacting() -> bool {
+    REDACTING.with(|r| r.get())
+}
+
+/// customized serialize function to mask sensitive fields with `***` when redacting.
+pub fn serialize_redacted<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize + fmt::Debug + ?Sized,
+{
+    match is_redacting() {
+        true => serializer.serialize_str(REDACTED),
+        false => value.serialize(serializer),
+    }
+}
+
+/// customized serialize function to mask sensitive fields with a length-preserving hash when
+/// redacting. Equal values get the same mask, so they can still be correlated in logs.
+pub fn serialize_redacted_hash<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize + fmt::Debug + ?Sized,
+{
+    match is_redacting() {
+        true => serializer.collect_str(&RedactedHash(value)),
+        false => value.serialize(serializer),
+    }
+}
+
+/// Debug wrapper which prints `***` instead of the value.
+pub struct Redacted<'a, T: ?Sized>(pub &'a T);
+
+impl<T: ?Sized> fmt::Debug for Redacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// Debug wrapper which prints a hash of the value, with the same length as its debug output.
+///
+/// The hash is not cryptographically secure, it is only meant to hide the value from casual
+/// readers of logs.
+pub struct RedactedHash<'a, T: ?Sized>(pub &'a T);
+
+impl<T: fmt::Debug + ?Sized> fmt::Debug for RedactedHash<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = format!("{:?}", self.0);
+        let len = text.chars().count();
+        let mut hash = FNV_OFFSET;
+        let mut mask = String::with_capacity(len + 16);
+        while mask.len() < len {
+            hash = fnv1a(hash, text.as_bytes());
+            mask.push_str(&format!("{:016x}", hash));
+        }
+        f.write_str(&mask[..len])
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> fmt::Display for RedactedHash<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(seed: u64, data: &[u8]) -> u64 {
+    data.iter().fold(seed, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(feature = "json")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Serialize, serde::Deserialize, PartialEq)]
+    struct Login {
+        user: String,
+        #[serde(serialize_with = "serialize_redacted")]
+        password: String,
+        #[serde(serialize_with = "serialize_redacted_hash")]
+        token: String,
+    }
+
+    fn login() -> Login {
+        Login {
+            user: "tyr".to_owned(),
+            password: "secret".to_owned(),
+            token: "abcdef".to_owned(),
+        }
+    }
+
+    #[test]
+    fn redaction_should_mask_sensitive_fields() {
+        let s = with_redaction(|| serde_json::to_string(&login()).unwrap());
+        let token = format!("{:?}", RedactedHash(&"abcdef".to_owned()));
+        assert_eq!(token.len(), r#""abcdef""#.len());
+        assert_eq!(
+            s,
+            format!(r#"{{"user":"tyr","password":"***","token":"{token}"}}"#)
+        );
+        assert!(!is_redacting());
+    }
+
+    #[test]
+    fn serde_should_not_be_affected() {
+        let s = serde_json::to_string(&login()).unwrap();
+        assert_eq!(s, r#"{"user":"tyr","password":"secret","token":"abcdef"}"#);
+        assert_eq!(serde_json::from_str::<Login>(&s).unwrap(), login());
+    }
+
+    #[test]
+    fn redacted_debug_should_mask_value() {
+        let hash1 = format!("{:?}", RedactedHash("a long secret value, longer than 16"));
+        let hash2 = format!("{:?}", RedactedHash("a long secret value, longer than 16"));
+        assert_eq!(hash1, hash2);
+        assert_eq!(hash1.len(), 37);
+        assert_eq!(format!("{:?}", Redacted(&42)), "***");
+    }
+}
@@ -0,0 +1,156 @@
+use prost::Message;
+use prost_reflect::{
+    text_format::FormatOptions, DescriptorPool, DynamicMessage, MessageDescriptor,
+};
+
+use crate::Error;
+
+/// Convert the prost message to protobuf text format, the same format `protoc --decode` prints.
+pub trait ToText {
+    fn try_to_text(&self) -> Result<String, Error>;
+
+    fn to_text(&self) -> String {
+        self.try_to_text().unwrap()
+    }
+}
+
+/// Parse the prost message from protobuf text format, as accepted by `protoc --encode`.
+pub trait FromText: Sized {
+    fn from_text(s: &str) -> Result<Self, Error>;
+}
+
+/// Encode `msg` in protobuf text format, using the message `name` in `pool` as its schema.
+///
+/// Each field is printed on its own line and nested messages are indented.
+pub fn encode_text<M: Message>(
+    msg: &M,
+    pool: &DescriptorPool,
+    name: &str,
+) -> Result<String, Error> {
+    let desc = message_descriptor(pool, name)?;
+    let dynamic = DynamicMessage::decode(desc, msg.encode_to_vec().as_slice())?;
+    let options = FormatOptions::new().pretty(true);
+    Ok(dynamic.to_text_format_with_options(&options))
+}
+
+/// Decode a message from protobuf text format, using the message `name` in `pool` as its schema.
+pub fn decode_text<M: Message + Default>(
+    s: &str,
+    pool: &DescriptorPool,
+    name: &str,
+) -> Result<M, Error> {
+    let desc = message_descriptor(pool, name)?;
+    let dynamic = DynamicMessage::parse_text_format(desc, s)?;
+    Ok(M::decode(dynamic.encode_to_vec().as_slice())?)
+}
+
+fn message_descriptor(pool: &DescriptorPool, name: &str) -> Result<MessageDescriptor, Error> {
+    pool.get_message_by_name(name)
+        .ok_or_else(|| Error::UnknownMessage(name.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::OnceLock;
+
+    use prost_types::{
+        field_descriptor_proto::{Label, Type},
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+    };
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Hello {
+        #[prost(string, tag = "1")]
+        pub msg: String,
+        #[prost(uint64, tag = "2")]
+        pub value: u64,
+        #[prost(bytes = "vec", tag = "3")]
+        pub data: Vec<u8>,
+        #[prost(message, repeated, tag = "4")]
+        pub children: Vec<Hello>,
+    }
+
+    fn field(name: &str, number: i32, ty: Type, label: Label) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            number: Some(number),
+            r#type: Some(ty as i32),
+            label: Some(label as i32),
+            type_name: (ty == Type::Message).then(|| ".test.Hello".to_owned()),
+            json_name: Some(name.to_owned()),
+            ..Default::default()
+        }
+    }
+
+    fn pool() -> &'static DescriptorPool {
+        static POOL: OnceLock<DescriptorPool> = OnceLock::new();
+        POOL.get_or_init(build_pool)
+    }
+
+    fn build_pool() -> DescriptorPool {
+        let file = FileDescriptorProto {
+            name: Some("test.proto".to_owned()),
+            package: Some("test".to_owned()),
+            syntax: Some("proto3".to_owned()),
+            message_type: vec![DescriptorProto {
+                name: Some("Hello".to_owned()),
+                field: vec![
+                    field("msg", 1, Type::String, Label::Optional),
+                    field("value", 2, Type::Uint64, Label::Optional),
+                    field("data", 3, Type::Bytes, Label::Optional),
+                    field("children", 4, Type::Message, Label::Repeated),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        DescriptorPool::from_file_descriptor_set(FileDescriptorSet { file: vec![file] }).unwrap()
+    }
+
+    crate::prost_to_text!(pool(); Hello = "test.Hello");
+
+    fn hello() -> Hello {
+        Hello {
+            msg: "hello".to_owned(),
+            value: 42,
+            data: b"\x00\xff".to_vec(),
+            children: vec![Hello {
+                msg: "world".to_owned(),
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[test]
+    fn to_text_should_work() {
+        // repeated messages use the list syntax, which `protoc --encode` accepts as well
+        assert_eq!(
+            hello().to_text(),
+            "msg: \"hello\"\nvalue: 42\ndata: \"\\000\\377\"\nchildren: [{\n  msg: \"world\"\n}]"
+        );
+    }
+
+    #[test]
+    fn text_should_round_trip() {
+        let hello = hello();
+        assert_eq!(Hello::from_text(&hello.to_text()).unwrap(), hello);
+    }
+
+    #[test]
+    fn from_text_should_accept_protoc_and_go_output() {
+        let protoc =
+            "msg: \"hello\"\nvalue: 42\ndata: \"\\000\\377\"\nchildren {\n  msg: \"world\"\n}\n";
+        let go = r#"msg:"hello" value:42 data:"\x00\xff" children:{msg:"world"}"#;
+        assert_eq!(Hello::from_text(protoc).unwrap(), hello());
+        assert_eq!(Hello::from_text(go).unwrap(), hello());
+    }
+
+    #[test]
+    fn invalid_text_should_be_rejected() {
+        let err = Hello::from_text("msg: 42").unwrap_err();
+        assert!(matches!(err, Error::Text(_)));
+        let err = decode_text::<Hello>("", pool(), "test.World").unwrap_err();
+        assert_eq!(err.to_string(), "unknown message type: test.World");
+    }
+}